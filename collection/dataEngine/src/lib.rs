@@ -19,7 +19,50 @@ pub struct DataEngine
     pub seed : u64,
     pub statement : String,
     pub pool_con : Option<PooledConn>,
-    pub sender : Sender<String>
+    pub sender : Sender<String>,
+    pub columns : Vec<String>
+}
+
+/// columns added to `runs` after the table was first created, with the
+/// statement adding them to an older table
+const RUNS_MIGRATIONS : [(&str, &str);1] = [
+    //rows stored before the engine had versioned rngs were played with the legacy one
+    ("rng_version", "ALTER TABLE runs ADD COLUMN rng_version INT UNSIGNED NOT NULL DEFAULT 1")
+];
+
+fn runs_columns(connection : &mut PooledConn) -> Result<Vec<String>, mysql::Error>{
+    connection.query_map(
+        "SELECT COLUMN_NAME FROM information_schema.COLUMNS
+        WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = 'runs' ORDER BY ORDINAL_POSITION;",
+        |name : String| name
+    )
+}
+
+/// adds the columns of [`RUNS_MIGRATIONS`] missing from `runs`,
+/// returns the columns of the table in order
+fn migrate_runs(connection : &mut PooledConn) -> Result<Vec<String>, mysql::Error>{
+    let mut columns = runs_columns(connection)?;
+    for (column, statement) in RUNS_MIGRATIONS{
+        if !columns.iter().any(|name| name == column){
+            connection.query_drop(statement)?;
+            eprintln!("Added the column {column} to runs");
+            columns.push(column.to_string());
+        }
+    }
+    Ok(columns)
+}
+
+/// the statement storing a row in `runs`, naming the columns when they are
+/// known, an unmigrated table or a failed migration leave them empty and the
+/// values are given in the order of the table
+fn insert_statement(columns : &[String], row : &str) -> String{
+    if columns.is_empty(){
+        return format!("INSERT INTO runs
+        VALUES ({row});");
+    }
+    let columns = columns.iter().map(|column| format!("`{column}`")).collect::<Vec<String>>().join(", ");
+    format!("INSERT INTO runs ({columns})
+        VALUES ({row});")
 }
 
 fn conection_from_pool(pool : Option<Pool>) -> Option<PooledConn>{
@@ -51,9 +94,20 @@ impl DataEngine
             }
         };
 
-        let conn_main = conection_from_pool(pool.clone());
+        let mut conn_main = conection_from_pool(pool.clone());
         let conn_thread = conection_from_pool(pool);
 
+        let columns = match &mut conn_main {
+            Some(connection) => match migrate_runs(connection) {
+                Ok(columns) => columns,
+                Err(err) => {
+                    eprintln!("Failed to migrate the runs table {err}");
+                    vec![]
+                }
+            },
+            None => vec![]
+        };
+
 
         let (tx, rx) = channel();
         if let Some(mut connection) = conn_thread {
@@ -80,15 +134,20 @@ impl DataEngine
             seed : 1, 
             statement : "".to_string(),
             pool_con : conn_main,
-            sender : tx
+            sender : tx,
+            columns
         };
         return de;
     }
 
+    /// adds a step to the run, the row is the flattened world followed by the
+    /// action, direction, seed, score and the rng version of the engine
+    ///
+    /// The values are named with the columns read when connecting, so the
+    /// row keeps matching a table migrated by [`DataEngine::new`].
     pub fn create_statement(&mut self, flattened_world : String)
     {
-        self.statement += &format!("INSERT INTO runs
-        VALUES ({});", flattened_world);
+        self.statement += &insert_statement(&self.columns, &flattened_world);
     }
 
     pub fn send_run(&mut self) -> Result<(), Box<dyn Error>>
@@ -142,6 +201,22 @@ impl DataEngine
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_statement_columns(){
+        let columns = vec!["c0".to_string(), "action".to_string(), "rng_version".to_string()];
+        assert_eq!(insert_statement(&columns, "1, 2,3"), "INSERT INTO runs (`c0`, `action`, `rng_version`)
+        VALUES (1, 2,3);");
+
+        //without known columns the row is stored like before the migration
+        assert_eq!(insert_statement(&[], "1, 2,3"), "INSERT INTO runs
+        VALUES (1, 2,3);");
+    }
+}
 }
 
 /*mod data_engine_mod;
@@ -0,0 +1,42 @@
+/*
+ * plays a game with a fixed action sequence and prints the result
+ *
+ * cargo build --release
+ * cc examples/play.c -Iinclude -Ltarget/release -lengine_ffi -o play
+ * LD_LIBRARY_PATH=target/release ./play
+ */
+#include <stdio.h>
+#include "engine_ffi.h"
+
+int main(void) {
+    SnakeEngine *engine = NULL;
+    SnakeStatus status = snake_engine_create(10, 10, 5, 5, 1, "square", 42, &engine);
+    if (status != SNAKE_STATUS_OK) {
+        fprintf(stderr, "create failed: %s\n", snake_status_message(status));
+        return 1;
+    }
+
+    size_t size = 0;
+    snake_engine_observation_size(engine, SNAKE_OBSERVATION_FEATURES, &size);
+    float observation[12];
+
+    SnakeStepResult result = {0};
+    int steps = 0;
+    while (!result.done && steps < 100) {
+        size_t action = steps % 7 == 6 ? 2 : 1;
+        status = snake_engine_step(engine, action, &result);
+        if (status != SNAKE_STATUS_OK) {
+            fprintf(stderr, "step failed: %s\n", snake_status_message(status));
+            break;
+        }
+        snake_engine_observe(engine, SNAKE_OBSERVATION_FEATURES, observation, size);
+        steps++;
+    }
+
+    size_t score = 0;
+    snake_engine_get_score(engine, &score);
+    printf("steps %d message %d score %zu\n", steps, (int)result.message, score);
+
+    snake_engine_destroy(engine);
+    return 0;
+}
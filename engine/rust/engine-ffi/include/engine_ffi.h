@@ -0,0 +1,210 @@
+#ifndef ENGINE_FFI_H
+#define ENGINE_FFI_H
+
+/* Generated with cbindgen from src/lib.rs, do not edit by hand */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * the encodings of [`snake_engine_observe`]
+ */
+typedef enum SnakeObservation {
+  /**
+   * the flattened world, one value per cell, row by row
+   */
+  SNAKE_OBSERVATION_GRID = 0,
+  /**
+   * the compact feature vector, 12 values on the square grid
+   */
+  SNAKE_OBSERVATION_FEATURES = 1,
+} SnakeObservation;
+
+/**
+ * the result of every function of the library
+ */
+typedef enum SnakeStatus {
+  /**
+   * the call succeeded
+   */
+  SNAKE_STATUS_OK = 0,
+  /**
+   * a pointer argument was null
+   */
+  SNAKE_STATUS_NULL_POINTER = 1,
+  /**
+   * an argument was outside of its valid range, like an unknown topology,
+   * an action the topology does not have or a snake outside of the world
+   */
+  SNAKE_STATUS_INVALID_ARGUMENT = 2,
+  /**
+   * the output buffer is shorter than the data
+   */
+  SNAKE_STATUS_BUFFER_TOO_SMALL = 3,
+  /**
+   * the engine panicked, the engine should be destroyed
+   */
+  SNAKE_STATUS_PANIC = 4,
+} SnakeStatus;
+
+/**
+ * how the last step ended, the `msg` of the Rust engine
+ */
+typedef enum SnakeStepMessage {
+  SNAKE_STEP_MESSAGE_ALIVE = 0,
+  SNAKE_STEP_MESSAGE_BODY = 1,
+  SNAKE_STEP_MESSAGE_WALL = 2,
+  SNAKE_STEP_MESSAGE_OBSTACLE = 3,
+  SNAKE_STEP_MESSAGE_VICTORY = 4,
+  /**
+   * the episode reached its step limit
+   */
+  SNAKE_STEP_MESSAGE_TIMEOUT = 5,
+  /**
+   * the snake did not eat within its limit
+   */
+  SNAKE_STEP_MESSAGE_STARVED = 6,
+} SnakeStepMessage;
+
+/**
+ * an opaque handle of an engine
+ */
+typedef struct SnakeEngine SnakeEngine;
+
+/**
+ * the result of a step
+ */
+typedef struct SnakeStepResult {
+  bool done;
+  bool food_eaten;
+  enum SnakeStepMessage message;
+  size_t executed_action;
+} SnakeStepResult;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * creates an engine, reset with the seed, and writes its handle to `out`
+ *
+ * * `width`, `height` - the size of the world, `width` is the number of rows
+ * * `start_x`, `start_y` - the starting position of the head
+ * * `direction` - the starting direction in the topology
+ * * `topology` - `"square"`, `"hex"` or `"octile"`, null for the square grid
+ *
+ * The snake of length 3 starting behind the head has to fit into the world.
+ *
+ * # Safety
+ *
+ * `topology` has to be null or a valid null terminated string and `out`
+ * has to be null or valid for writing a pointer.
+ */
+enum SnakeStatus snake_engine_create(size_t width,
+                                     size_t height,
+                                     size_t start_x,
+                                     size_t start_y,
+                                     size_t direction,
+                                     const char *topology,
+                                     uint64_t seed,
+                                     struct SnakeEngine **out);
+
+/**
+ * destroys an engine created with [`snake_engine_create`], null is ignored
+ *
+ * # Safety
+ *
+ * `engine` has to be null or a handle returned by [`snake_engine_create`]
+ * that was not destroyed yet.
+ */
+enum SnakeStatus snake_engine_destroy(struct SnakeEngine *engine);
+
+/**
+ * resets the engine to its starting state with the seed
+ *
+ * # Safety
+ *
+ * `engine` has to be null or a valid handle.
+ */
+enum SnakeStatus snake_engine_reset(struct SnakeEngine *engine, uint64_t seed);
+
+/**
+ * advances the game by one step with a relative action,
+ * 0 turns left, 1 keeps going forward and 2 turns right on the square grid
+ *
+ * # Safety
+ *
+ * `engine` has to be null or a valid handle and `out` has to be null
+ * or valid for writing a [`SnakeStepResult`].
+ */
+enum SnakeStatus snake_engine_step(struct SnakeEngine *engine,
+                                   size_t action,
+                                   struct SnakeStepResult *out);
+
+/**
+ * writes the length of the observation of the given type to `out`,
+ * `observation` is one of the [`SnakeObservation`] values
+ *
+ * # Safety
+ *
+ * `engine` has to be null or a valid handle and `out` has to be null
+ * or valid for writing a `size_t`.
+ */
+enum SnakeStatus snake_engine_observation_size(const struct SnakeEngine *engine,
+                                               uint32_t observation,
+                                               size_t *out);
+
+/**
+ * writes the encoded observation of the current state to `buffer`,
+ * which holds `len` floats, see [`snake_engine_observation_size`],
+ * `observation` is one of the [`SnakeObservation`] values
+ *
+ * # Safety
+ *
+ * `engine` has to be null or a valid handle and `buffer` has to be null
+ * or valid for writing `len` floats.
+ */
+enum SnakeStatus snake_engine_observe(const struct SnakeEngine *engine,
+                                      uint32_t observation,
+                                      float *buffer,
+                                      size_t len);
+
+/**
+ * writes the world to `buffer`, which holds `len` integers,
+ * row by row, `width * height` values
+ *
+ * # Safety
+ *
+ * `engine` has to be null or a valid handle and `buffer` has to be null
+ * or valid for writing `len` integers.
+ */
+enum SnakeStatus snake_engine_get_world(const struct SnakeEngine *engine,
+                                        int32_t *buffer,
+                                        size_t len);
+
+/**
+ * writes the score, the length of the snake minus 3, to `out`
+ *
+ * # Safety
+ *
+ * `engine` has to be null or a valid handle and `out` has to be null
+ * or valid for writing a `size_t`.
+ */
+enum SnakeStatus snake_engine_get_score(const struct SnakeEngine *engine, size_t *out);
+
+/**
+ * returns a static description of a status, never null
+ *
+ * `status` is one of the [`SnakeStatus`] values, unknown values get
+ * `"unknown status"`.
+ */
+const char *snake_status_message(uint32_t status);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* ENGINE_FFI_H */
@@ -0,0 +1,371 @@
+//! C ABI bindings of the snake game engine
+//!
+//! The crate builds a shared and a static library exposing the
+//! [`snake_game_rust`] engine to C, C++, C# and every other language with a
+//! C foreign function interface. The declarations are in
+//! `include/engine_ffi.h`, generated from this file with
+//! `cbindgen --config cbindgen.toml --output include/engine_ffi.h`.
+//!
+//! Every function returns a [`SnakeStatus`], no panic crosses the boundary.
+//! Arguments are checked before they reach the engine, and a panic inside
+//! the engine is caught and reported as `SNAKE_STATUS_PANIC`, after which
+//! the engine should only be destroyed.
+//!
+//! The engine is deterministic, the same seed and actions give the same game
+//! as the Rust crate and the python wrapper.
+//!
+//! ```c
+//! #include "engine_ffi.h"
+//!
+//! SnakeEngine *engine = NULL;
+//! if (snake_engine_create(10, 10, 5, 5, 1, "square", 42, &engine) != SNAKE_STATUS_OK) {
+//!     return 1;
+//! }
+//!
+//! SnakeStepResult result;
+//! snake_engine_step(engine, 1, &result);
+//!
+//! float observation[12];
+//! snake_engine_observe(engine, SNAKE_OBSERVATION_FEATURES, observation, 12);
+//!
+//! snake_engine_destroy(engine);
+//! ```
+
+use std::ffi::{c_char, CStr};
+use std::panic::{self, AssertUnwindSafe};
+use snake_game_rust::snake::*;
+use snake_game_rust::snake::snake::Snake;
+use snake_game_rust::snake::data_types::Vec2;
+use snake_game_rust::snake::observation::ObservationType;
+use snake_game_rust::snake::topology;
+
+/// the result of every function of the library
+#[repr(C)]
+#[derive(Clone,Copy,PartialEq,Eq,Debug)]
+pub enum SnakeStatus{
+    /// the call succeeded
+    Ok = 0,
+    /// a pointer argument was null
+    NullPointer = 1,
+    /// an argument was outside of its valid range, like an unknown topology,
+    /// an action the topology does not have or a snake outside of the world
+    InvalidArgument = 2,
+    /// the output buffer is shorter than the data
+    BufferTooSmall = 3,
+    /// the engine panicked, the engine should be destroyed
+    Panic = 4
+}
+
+/// how the last step ended, the `msg` of the Rust engine
+#[repr(C)]
+#[derive(Clone,Copy,PartialEq,Eq,Debug)]
+pub enum SnakeStepMessage{
+    Alive = 0,
+    Body = 1,
+    Wall = 2,
+    Obstacle = 3,
+    Victory = 4,
+    /// the episode reached its step limit
+    Timeout = 5,
+    /// the snake did not eat within its limit
+    Starved = 6
+}
+
+/// the encodings of [`snake_engine_observe`]
+#[repr(C)]
+#[derive(Clone,Copy,PartialEq,Eq,Debug)]
+pub enum SnakeObservation{
+    /// the flattened world, one value per cell, row by row
+    Grid = 0,
+    /// the compact feature vector, 12 values on the square grid
+    Features = 1
+}
+
+/// the result of a step
+#[repr(C)]
+#[derive(Clone,Copy,PartialEq,Eq,Debug)]
+pub struct SnakeStepResult{
+    pub done : bool,
+    pub food_eaten : bool,
+    pub message : SnakeStepMessage,
+    pub executed_action : usize
+}
+
+/// an opaque handle of an engine
+pub struct SnakeEngine{
+    engine : GameEngine
+}
+
+/// the observation type of a [`SnakeObservation`] value, the value is passed
+/// as an integer so that invalid values from C are reported instead of
+/// being undefined behaviour
+fn observation_type(observation : u32) -> Option<ObservationType>{
+    match observation {
+        0 => Some(ObservationType::Grid),
+        1 => Some(ObservationType::Features),
+        _ => None
+    }
+}
+
+/// the status of a [`SnakeStatus`] value, `None` for unknown values
+fn snake_status(status : u32) -> Option<SnakeStatus>{
+    match status {
+        0 => Some(SnakeStatus::Ok),
+        1 => Some(SnakeStatus::NullPointer),
+        2 => Some(SnakeStatus::InvalidArgument),
+        3 => Some(SnakeStatus::BufferTooSmall),
+        4 => Some(SnakeStatus::Panic),
+        _ => None
+    }
+}
+
+impl SnakeStepMessage {
+
+    fn from_msg(msg : &str) -> SnakeStepMessage{
+        match msg {
+            "body" => SnakeStepMessage::Body,
+            "wall" => SnakeStepMessage::Wall,
+            "obstacle" => SnakeStepMessage::Obstacle,
+            "victory" => SnakeStepMessage::Victory,
+            "timeout" => SnakeStepMessage::Timeout,
+            "starved" => SnakeStepMessage::Starved,
+            _ => SnakeStepMessage::Alive
+        }
+    }
+}
+
+/// runs the body and turns a panic into [`SnakeStatus::Panic`]
+fn guard(body : impl FnOnce() -> SnakeStatus) -> SnakeStatus{
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or(SnakeStatus::Panic)
+}
+
+/// creates an engine, reset with the seed, and writes its handle to `out`
+///
+/// * `width`, `height` - the size of the world, `width` is the number of rows
+/// * `start_x`, `start_y` - the starting position of the head
+/// * `direction` - the starting direction in the topology
+/// * `topology` - `"square"`, `"hex"` or `"octile"`, null for the square grid
+///
+/// The snake of length 3 starting behind the head has to fit into the world.
+///
+/// # Safety
+///
+/// `topology` has to be null or a valid null terminated string and `out`
+/// has to be null or valid for writing a pointer.
+#[no_mangle]
+pub unsafe extern "C" fn snake_engine_create(
+    width : usize,
+    height : usize,
+    start_x : usize,
+    start_y : usize,
+    direction : usize,
+    topology : *const c_char,
+    seed : u64,
+    out : *mut *mut SnakeEngine
+) -> SnakeStatus{
+
+    if out.is_null(){
+        return SnakeStatus::NullPointer;
+    }
+    let name = match topology.is_null() {
+        true => "square",
+        false => match CStr::from_ptr(topology).to_str() {
+            Ok(name) => name,
+            Err(_) => return SnakeStatus::InvalidArgument
+        }
+    };
+
+    guard(|| {
+        let Some(topology) = topology::from_name(name) else {
+            return SnakeStatus::InvalidArgument;
+        };
+        if direction >= topology.num_directions() || start_x > i32::MAX as usize || start_y > i32::MAX as usize{
+            return SnakeStatus::InvalidArgument;
+        }
+
+        //the engine panics on snakes outside of the world, so the starting
+        //snake is validated first
+        let head = Vec2{ x : start_x as i32, y : start_y as i32 };
+        let body = Snake::new(head, direction, topology.as_ref()).snake_body;
+        if GameEngine::from_parts_with_topology((width, height), body, direction, None, seed, topology.clone()).is_err(){
+            return SnakeStatus::InvalidArgument;
+        }
+
+        let mut engine = GameEngine::with_topology((width, height), (start_x, start_y), direction, topology);
+        engine.reset(seed);
+        *out = Box::into_raw(Box::new(SnakeEngine { engine }));
+        SnakeStatus::Ok
+    })
+}
+
+/// destroys an engine created with [`snake_engine_create`], null is ignored
+///
+/// # Safety
+///
+/// `engine` has to be null or a handle returned by [`snake_engine_create`]
+/// that was not destroyed yet.
+#[no_mangle]
+pub unsafe extern "C" fn snake_engine_destroy(engine : *mut SnakeEngine) -> SnakeStatus{
+    if engine.is_null(){
+        return SnakeStatus::Ok;
+    }
+    guard(|| {
+        drop(Box::from_raw(engine));
+        SnakeStatus::Ok
+    })
+}
+
+/// resets the engine to its starting state with the seed
+///
+/// # Safety
+///
+/// `engine` has to be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn snake_engine_reset(engine : *mut SnakeEngine, seed : u64) -> SnakeStatus{
+    let Some(engine) = engine.as_mut() else {
+        return SnakeStatus::NullPointer;
+    };
+    guard(|| {
+        engine.engine.reset(seed);
+        SnakeStatus::Ok
+    })
+}
+
+/// advances the game by one step with a relative action,
+/// 0 turns left, 1 keeps going forward and 2 turns right on the square grid
+///
+/// # Safety
+///
+/// `engine` has to be null or a valid handle and `out` has to be null
+/// or valid for writing a [`SnakeStepResult`].
+#[no_mangle]
+pub unsafe extern "C" fn snake_engine_step(engine : *mut SnakeEngine, action : usize, out : *mut SnakeStepResult) -> SnakeStatus{
+    let (Some(engine), false) = (engine.as_mut(), out.is_null()) else {
+        return SnakeStatus::NullPointer;
+    };
+    guard(|| {
+        if action >= engine.engine.topology.num_actions(){
+            return SnakeStatus::InvalidArgument;
+        }
+        let outcome = engine.engine.step_outcome(action);
+        *out = SnakeStepResult {
+            done : outcome.done,
+            food_eaten : outcome.food_eaten,
+            message : SnakeStepMessage::from_msg(&outcome.msg),
+            executed_action : outcome.executed_action
+        };
+        SnakeStatus::Ok
+    })
+}
+
+/// writes the length of the observation of the given type to `out`,
+/// `observation` is one of the [`SnakeObservation`] values
+///
+/// # Safety
+///
+/// `engine` has to be null or a valid handle and `out` has to be null
+/// or valid for writing a `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn snake_engine_observation_size(
+    engine : *const SnakeEngine,
+    observation : u32,
+    out : *mut usize
+) -> SnakeStatus{
+    let (Some(engine), false) = (engine.as_ref(), out.is_null()) else {
+        return SnakeStatus::NullPointer;
+    };
+    let Some(observation_type) = observation_type(observation) else {
+        return SnakeStatus::InvalidArgument;
+    };
+    guard(|| {
+        *out = observation_type.size(engine.engine.world_size, engine.engine.topology.as_ref());
+        SnakeStatus::Ok
+    })
+}
+
+/// writes the encoded observation of the current state to `buffer`,
+/// which holds `len` floats, see [`snake_engine_observation_size`],
+/// `observation` is one of the [`SnakeObservation`] values
+///
+/// # Safety
+///
+/// `engine` has to be null or a valid handle and `buffer` has to be null
+/// or valid for writing `len` floats.
+#[no_mangle]
+pub unsafe extern "C" fn snake_engine_observe(
+    engine : *const SnakeEngine,
+    observation : u32,
+    buffer : *mut f32,
+    len : usize
+) -> SnakeStatus{
+    let (Some(engine), false) = (engine.as_ref(), buffer.is_null()) else {
+        return SnakeStatus::NullPointer;
+    };
+    let Some(observation_type) = observation_type(observation) else {
+        return SnakeStatus::InvalidArgument;
+    };
+    guard(|| {
+        let values = engine.engine.observe(observation_type);
+        if values.len() > len{
+            return SnakeStatus::BufferTooSmall;
+        }
+        std::ptr::copy_nonoverlapping(values.as_ptr(), buffer, values.len());
+        SnakeStatus::Ok
+    })
+}
+
+/// writes the world to `buffer`, which holds `len` integers,
+/// row by row, `width * height` values
+///
+/// # Safety
+///
+/// `engine` has to be null or a valid handle and `buffer` has to be null
+/// or valid for writing `len` integers.
+#[no_mangle]
+pub unsafe extern "C" fn snake_engine_get_world(engine : *const SnakeEngine, buffer : *mut i32, len : usize) -> SnakeStatus{
+    let (Some(engine), false) = (engine.as_ref(), buffer.is_null()) else {
+        return SnakeStatus::NullPointer;
+    };
+    guard(|| {
+        let world = &engine.engine.game_world;
+        if world.size.0*world.size.1 > len{
+            return SnakeStatus::BufferTooSmall;
+        }
+        for (i, cell) in world.matrix.iter().flatten().enumerate(){
+            *buffer.add(i) = *cell;
+        }
+        SnakeStatus::Ok
+    })
+}
+
+/// writes the score, the length of the snake minus 3, to `out`
+///
+/// # Safety
+///
+/// `engine` has to be null or a valid handle and `out` has to be null
+/// or valid for writing a `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn snake_engine_get_score(engine : *const SnakeEngine, out : *mut usize) -> SnakeStatus{
+    let (Some(engine), false) = (engine.as_ref(), out.is_null()) else {
+        return SnakeStatus::NullPointer;
+    };
+    *out = engine.engine.get_score();
+    SnakeStatus::Ok
+}
+
+/// returns a static description of a status, never null
+///
+/// `status` is one of the [`SnakeStatus`] values, unknown values get
+/// `"unknown status"`.
+#[no_mangle]
+pub extern "C" fn snake_status_message(status : u32) -> *const c_char{
+    let message : &'static CStr = match snake_status(status) {
+        Some(SnakeStatus::Ok) => c"ok",
+        Some(SnakeStatus::NullPointer) => c"a pointer argument was null",
+        Some(SnakeStatus::InvalidArgument) => c"an argument was outside of its valid range",
+        Some(SnakeStatus::BufferTooSmall) => c"the output buffer is too small",
+        Some(SnakeStatus::Panic) => c"the engine panicked, destroy it",
+        None => c"unknown status"
+    };
+    message.as_ptr()
+}
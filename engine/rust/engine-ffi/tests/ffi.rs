@@ -0,0 +1,105 @@
+//! Tests of the C ABI, calling the exported functions like a C program would
+
+use std::ffi::{CStr, CString};
+use std::ptr;
+use engine_ffi::*;
+use snake_game_rust::snake::*;
+
+fn create(topology : &str, seed : u64) -> *mut SnakeEngine{
+    let name = CString::new(topology).unwrap();
+    let mut engine = ptr::null_mut();
+    let status = unsafe { snake_engine_create(10, 10, 5, 5, 1, name.as_ptr(), seed, &mut engine) };
+    assert_eq!(status, SnakeStatus::Ok);
+    assert!(!engine.is_null());
+    engine
+}
+
+#[test]
+fn plays_the_same_game_as_the_engine(){
+    let engine = create("square", 42);
+    let mut game = GameEngine::new((10,10), (5,5), 1);
+    game.reset(42);
+
+    let actions = [1, 0, 0, 2, 1, 1, 2, 2, 1, 0, 1, 1, 1];
+    for action in actions{
+        let mut result = SnakeStepResult { done : false, food_eaten : false, message : SnakeStepMessage::Alive, executed_action : 0 };
+        assert_eq!(unsafe { snake_engine_step(engine, action, &mut result) }, SnakeStatus::Ok);
+        let (done, food_eaten, _) = game.step(action);
+        assert_eq!((result.done, result.food_eaten), (done, food_eaten));
+
+        let mut world = vec![0; 100];
+        assert_eq!(unsafe { snake_engine_get_world(engine, world.as_mut_ptr(), world.len()) }, SnakeStatus::Ok);
+        let expected : Vec<i32> = game.get_world().matrix.into_iter().flatten().collect();
+        assert_eq!(world, expected);
+
+        let mut observation = vec![0.0; 12];
+        assert_eq!(unsafe { snake_engine_observe(engine, SnakeObservation::Features as u32, observation.as_mut_ptr(), 12) }, SnakeStatus::Ok);
+        assert_eq!(observation, game.observe(observation::ObservationType::Features));
+        if done{
+            break;
+        }
+    }
+
+    unsafe {
+        assert_eq!(snake_engine_reset(engine, 42), SnakeStatus::Ok);
+        assert_eq!(snake_engine_destroy(engine), SnakeStatus::Ok);
+    }
+}
+
+#[test]
+fn reports_errors_as_codes(){
+    let mut engine = ptr::null_mut();
+    let hex = CString::new("hex").unwrap();
+    let unknown = CString::new("triangle").unwrap();
+    unsafe {
+        //unknown topology, direction and a snake leaving the world
+        assert_eq!(snake_engine_create(10, 10, 5, 5, 1, unknown.as_ptr(), 0, &mut engine), SnakeStatus::InvalidArgument);
+        assert_eq!(snake_engine_create(10, 10, 5, 5, 4, ptr::null(), 0, &mut engine), SnakeStatus::InvalidArgument);
+        assert_eq!(snake_engine_create(10, 10, 8, 5, 1, ptr::null(), 0, &mut engine), SnakeStatus::InvalidArgument);
+        assert_eq!(snake_engine_create(0, 10, 0, 0, 1, ptr::null(), 0, &mut engine), SnakeStatus::InvalidArgument);
+        assert_eq!(snake_engine_create(10, 10, 5, 5, 1, ptr::null(), 0, ptr::null_mut()), SnakeStatus::NullPointer);
+        assert!(engine.is_null());
+
+        assert_eq!(snake_engine_create(10, 10, 5, 5, 3, hex.as_ptr(), 0, &mut engine), SnakeStatus::Ok);
+    }
+
+    let mut result = SnakeStepResult { done : false, food_eaten : false, message : SnakeStepMessage::Alive, executed_action : 0 };
+    let mut size = 0;
+    let mut small = [0.0f32; 4];
+    unsafe {
+        //the hex grid has 5 actions
+        assert_eq!(snake_engine_step(engine, 5, &mut result), SnakeStatus::InvalidArgument);
+        assert_eq!(snake_engine_step(engine, 4, &mut result), SnakeStatus::Ok);
+        assert_eq!(snake_engine_step(engine, 1, ptr::null_mut()), SnakeStatus::NullPointer);
+        assert_eq!(snake_engine_step(ptr::null_mut(), 1, &mut result), SnakeStatus::NullPointer);
+
+        assert_eq!(snake_engine_observation_size(engine, 1, &mut size), SnakeStatus::Ok);
+        assert_eq!(size, 16);
+        assert_eq!(snake_engine_observation_size(engine, 7, &mut size), SnakeStatus::InvalidArgument);
+        assert_eq!(snake_engine_observe(engine, 1, small.as_mut_ptr(), small.len()), SnakeStatus::BufferTooSmall);
+        assert_eq!(snake_engine_get_world(engine, [0; 4].as_mut_ptr(), 4), SnakeStatus::BufferTooSmall);
+
+        assert_eq!(snake_engine_destroy(engine), SnakeStatus::Ok);
+        assert_eq!(snake_engine_destroy(ptr::null_mut()), SnakeStatus::Ok);
+
+        let message = CStr::from_ptr(snake_status_message(SnakeStatus::BufferTooSmall as u32));
+        assert_eq!(message.to_str().unwrap(), "the output buffer is too small");
+        let message = CStr::from_ptr(snake_status_message(17));
+        assert_eq!(message.to_str().unwrap(), "unknown status");
+    }
+}
+
+#[test]
+fn header_declares_every_function(){
+    let source = include_str!("../src/lib.rs");
+    let header = include_str!("../include/engine_ffi.h");
+
+    let exported : Vec<&str> = source.lines()
+        .filter_map(|line| line.split("extern \"C\" fn ").nth(1))
+        .map(|rest| rest.split('(').next().unwrap())
+        .collect();
+    assert!(exported.len() >= 8);
+    for name in exported{
+        assert!(header.contains(&format!("{name}(")), "{name} is missing in the header, regenerate it with cbindgen");
+    }
+}
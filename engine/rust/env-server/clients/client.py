@@ -0,0 +1,70 @@
+"""reference python client of the env-server protocol, using only the standard library
+
+    python clients/client.py 127.0.0.1 5555
+"""
+import json
+import socket
+import struct
+import sys
+
+
+class EnvClient:
+
+    def __init__(self, sock):
+        self.sock = sock
+
+    @classmethod
+    def tcp(cls, host, port):
+        return cls(socket.create_connection((host, port)))
+
+    @classmethod
+    def unix(cls, path):
+        sock = socket.socket(socket.AF_UNIX, socket.SOCK_STREAM)
+        sock.connect(path)
+        return cls(sock)
+
+    def _recv_exact(self, n):
+        data = b""
+        while len(data) < n:
+            chunk = self.sock.recv(n - len(data))
+            if not chunk:
+                raise ConnectionError("the server closed the connection")
+            data += chunk
+        return data
+
+    def request(self, **request):
+        payload = json.dumps(request).encode()
+        self.sock.sendall(struct.pack(">I", len(payload)) + payload)
+        (length,) = struct.unpack(">I", self._recv_exact(4))
+        response = json.loads(self._recv_exact(length))
+        if not response["ok"]:
+            raise RuntimeError(response["error"])
+        return response
+
+    def create(self, size=(10, 10), start=(5, 5), direction=1, topology="square", seed=0):
+        return self.request(op="create", size=list(size), start=list(start),
+                            direction=direction, topology=topology, seed=seed)["id"]
+
+    def reset(self, id, seed):
+        self.request(op="reset", id=id, seed=seed)
+
+    def step(self, id, action):
+        response = self.request(op="step", id=id, action=action)
+        return response["done"], response["food_eaten"], response["msg"]
+
+    def observe(self, id, type="features"):
+        return self.request(op="observe", id=id, type=type)["observation"]
+
+    def close(self, id):
+        self.request(op="close", id=id)
+
+
+if __name__ == "__main__":
+    client = EnvClient.tcp(sys.argv[1], int(sys.argv[2]))
+    id = client.create(seed=42)
+    done, steps = False, 0
+    while not done:
+        done, food_eaten, msg = client.step(id, 1)
+        steps += 1
+    print(f"episode ended after {steps} steps: {msg}")
+    client.close(id)
@@ -0,0 +1,40 @@
+//! serves snake game engines over TCP or a Unix socket,
+//! see the `env_server` crate for the protocol
+//!
+//! ```text
+//! env-server --tcp 127.0.0.1:5555
+//! env-server --unix /tmp/snake.sock
+//! ```
+
+use std::net::TcpListener;
+use std::process::ExitCode;
+
+const USAGE : &str = "usage: env-server --tcp <address> | --unix <path>";
+
+fn main() -> ExitCode{
+
+    let args : Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.iter().map(|arg| arg.as_str()).collect::<Vec<_>>()[..] {
+        ["--tcp", address] => TcpListener::bind(address).and_then(|listener| {
+            println!("listening on {}", listener.local_addr()?);
+            env_server::serve_tcp(listener)
+        }),
+        #[cfg(unix)]
+        ["--unix", path] => std::os::unix::net::UnixListener::bind(path).and_then(|listener| {
+            println!("listening on {path}");
+            env_server::serve_unix(listener)
+        }),
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("env-server: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,348 @@
+//! Environment server hosting snake game engines behind a socket
+//!
+//! The `env-server` binary listens on a TCP address or a Unix socket and lets
+//! agents written in any language play against the Rust engine, without
+//! linking it. Every connection owns its engines, they are closed with the
+//! connection.
+//!
+//! ```text
+//! env-server --tcp 127.0.0.1:5555
+//! env-server --unix /tmp/snake.sock
+//! ```
+//!
+//! # Protocol
+//!
+//! Requests and responses are JSON objects, each sent as a message of a
+//! 4 byte big endian length followed by that many bytes of UTF-8 JSON.
+//! Messages are limited to [`MAX_MESSAGE_LEN`] bytes. Every request has an
+//! `op` field, every response an `ok` field, and failed requests are answered
+//! with `{"ok": false, "error": "<description>"}` without closing the connection.
+//!
+//! * `create` - creates an engine and resets it with the seed, all fields
+//!   but `op` are optional
+//!   ```text
+//!   {"op": "create", "size": [10, 10], "start": [5, 5], "direction": 1, "topology": "square", "seed": 0}
+//!   {"ok": true, "id": 0}
+//!   ```
+//! * `reset` - resets an engine with a seed
+//!   ```text
+//!   {"op": "reset", "id": 0, "seed": 42}
+//!   {"ok": true}
+//!   ```
+//! * `step` - advances an engine by one relative action, `msg` is one of
+//!   `"alive"`, `"body"`, `"wall"`, `"obstacle"`, `"victory"`, `"timeout"`
+//!   and `"starved"`
+//!   ```text
+//!   {"op": "step", "id": 0, "action": 1}
+//!   {"ok": true, "done": false, "food_eaten": false, "msg": "alive", "score": 0}
+//!   ```
+//! * `observe` - the encoded observation, `type` is `"features"` (the default)
+//!   or `"grid"`
+//!   ```text
+//!   {"op": "observe", "id": 0, "type": "features"}
+//!   {"ok": true, "observation": [0.0, 1.0, ...]}
+//!   ```
+//! * `close` - drops an engine
+//!   ```text
+//!   {"op": "close", "id": 0}
+//!   {"ok": true}
+//!   ```
+//!
+//! [`Client`] is the reference client, `clients/client.py` a python one.
+
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use serde_json::{json, Value};
+use snake_game_rust::snake::*;
+use snake_game_rust::snake::observation::ObservationType;
+use snake_game_rust::snake::snake::Snake;
+use snake_game_rust::snake::data_types::Vec2;
+use snake_game_rust::snake::topology;
+
+/// the largest message accepted, in bytes
+pub const MAX_MESSAGE_LEN : usize = 16 * 1024 * 1024;
+
+/// the largest world accepted by `create`, in cells
+pub const MAX_WORLD_CELLS : usize = 1 << 22;
+
+/// writes a JSON value as a length prefixed message
+pub fn write_message(writer : &mut impl Write, value : &Value) -> io::Result<()>{
+    let bytes = value.to_string().into_bytes();
+    writer.write_all(&(bytes.len() as u32).to_be_bytes())?;
+    writer.write_all(&bytes)?;
+    writer.flush()
+}
+
+/// reads a length prefixed JSON message,
+/// returns `None` if the stream ended before a new message
+pub fn read_message(reader : &mut impl Read) -> io::Result<Option<Value>>{
+    let mut length = [0u8;4];
+    match reader.read_exact(&mut length) {
+        Ok(()) => (),
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err)
+    }
+    let length = u32::from_be_bytes(length) as usize;
+    if length > MAX_MESSAGE_LEN{
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("message of {length} bytes is too long")));
+    }
+    let mut bytes = vec![0u8;length];
+    reader.read_exact(&mut bytes)?;
+    serde_json::from_slice(&bytes)
+        .map(Some)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// the engines of a connection
+#[derive(Default)]
+pub struct Engines{
+    engines : HashMap<u64,GameEngine>,
+    next_id : u64
+}
+
+fn field_usize(request : &Value, name : &str, default : usize) -> Result<usize,String>{
+    match request.get(name) {
+        None => Ok(default),
+        Some(value) => value.as_u64().map(|value| value as usize).ok_or(format!("{name} has to be a non negative integer"))
+    }
+}
+
+fn field_pair(request : &Value, name : &str, default : (usize,usize)) -> Result<(usize,usize),String>{
+    match request.get(name) {
+        None => Ok(default),
+        Some(value) => match value.as_array().map(|pair| pair.iter().map(|v| v.as_u64()).collect::<Vec<_>>()) {
+            Some(pair) if pair.len() == 2 && pair.iter().all(|v| v.is_some()) => Ok((pair[0].unwrap() as usize, pair[1].unwrap() as usize)),
+            _ => Err(format!("{name} has to be a pair of non negative integers"))
+        }
+    }
+}
+
+fn field_id(request : &Value) -> Result<u64,String>{
+    request.get("id").and_then(|id| id.as_u64()).ok_or("id has to be a non negative integer".to_string())
+}
+
+impl Engines {
+
+    fn engine(&mut self, request : &Value) -> Result<&mut GameEngine,String>{
+        let id = field_id(request)?;
+        self.engines.get_mut(&id).ok_or(format!("no engine with id {id}"))
+    }
+
+    fn create(&mut self, request : &Value) -> Result<Value,String>{
+
+        let size = field_pair(request, "size", (10,10))?;
+        if size.0.checked_mul(size.1).is_none_or(|cells| cells > MAX_WORLD_CELLS){
+            return Err(format!("worlds are limited to {MAX_WORLD_CELLS} cells"));
+        }
+        let start = field_pair(request, "start", (size.0/2, size.1/2))?;
+        let direction = field_usize(request, "direction", 1)?;
+        let seed = request.get("seed").map(|seed| seed.as_u64().ok_or("seed has to be a non negative integer")).transpose()?.unwrap_or(0);
+        let name = match request.get("topology") {
+            None => "square",
+            Some(name) => name.as_str().ok_or("topology has to be a string")?
+        };
+        let topology = topology::from_name(name).ok_or(format!("unknown topology {name}"))?;
+        if direction >= topology.num_directions(){
+            return Err(format!("direction has to be smaller than {}", topology.num_directions()));
+        }
+
+        //the engine panics on snakes outside of the world, so the starting snake is validated first
+        let head = Vec2{ x : start.0.min(i32::MAX as usize) as i32, y : start.1.min(i32::MAX as usize) as i32 };
+        let body = Snake::new(head, direction, topology.as_ref()).snake_body;
+        GameEngine::from_parts_with_topology(size, body, direction, None, seed, topology.clone())
+            .map_err(|err| err.to_string())?;
+
+        let mut engine = GameEngine::with_topology(size, start, direction, topology);
+        engine.reset(seed);
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.engines.insert(id, engine);
+        Ok(json!({ "ok" : true, "id" : id }))
+    }
+
+    /// answers a single request
+    pub fn handle(&mut self, request : &Value) -> Value{
+
+        let op = request.get("op").and_then(|op| op.as_str()).unwrap_or("");
+        let response = match op {
+            "create" => self.create(request),
+            "reset" => request.get("seed").and_then(|seed| seed.as_u64())
+                .ok_or("seed has to be a non negative integer".to_string())
+                .and_then(|seed| {
+                    self.engine(request)?.reset(seed);
+                    Ok(json!({ "ok" : true }))
+                }),
+            "step" => self.engine(request).and_then(|engine| {
+                let action = field_usize(request, "action", usize::MAX)?;
+                if action >= engine.topology.num_actions(){
+                    return Err(format!("action has to be smaller than {}", engine.topology.num_actions()));
+                }
+                let (done, food_eaten, msg) = engine.step(action);
+                Ok(json!({ "ok" : true, "done" : done, "food_eaten" : food_eaten, "msg" : msg, "score" : engine.get_score() }))
+            }),
+            "observe" => self.engine(request).and_then(|engine| {
+                let observation_type = match request.get("type").and_then(|name| name.as_str()).unwrap_or("features") {
+                    "features" => ObservationType::Features,
+                    "grid" => ObservationType::Grid,
+                    name => return Err(format!("unknown observation type {name}"))
+                };
+                Ok(json!({ "ok" : true, "observation" : engine.observe(observation_type) }))
+            }),
+            "close" => field_id(request).and_then(|id| match self.engines.remove(&id) {
+                Some(_) => Ok(json!({ "ok" : true })),
+                None => Err(format!("no engine with id {id}"))
+            }),
+            op => Err(format!("unknown op {op:?}"))
+        };
+        response.unwrap_or_else(|error| json!({ "ok" : false, "error" : error }))
+    }
+
+    /// the number of open engines
+    pub fn len(&self) -> usize{
+        self.engines.len()
+    }
+
+    pub fn is_empty(&self) -> bool{
+        self.engines.is_empty()
+    }
+}
+
+/// answers the requests of a connection until it is closed
+///
+/// Malformed messages end the connection, as the stream can not be
+/// resynchronized after them.
+pub fn serve_connection<S : Read + Write>(mut stream : S) -> io::Result<()>{
+    let mut engines = Engines::default();
+    while let Some(request) = read_message(&mut stream)?{
+        let response = engines.handle(&request);
+        write_message(&mut stream, &response)?;
+    }
+    Ok(())
+}
+
+/// accepts connections forever, serving each on its own thread
+pub fn serve_tcp(listener : TcpListener) -> io::Result<()>{
+    for stream in listener.incoming(){
+        let stream = stream?;
+        //requests are small and answered one by one, so they are sent right away
+        stream.set_nodelay(true)?;
+        thread::spawn(move || {
+            if let Err(err) = serve_connection(stream){
+                eprintln!("connection closed: {err}");
+            }
+        });
+    }
+    Ok(())
+}
+
+/// accepts connections on a Unix socket forever, serving each on its own thread
+#[cfg(unix)]
+pub fn serve_unix(listener : std::os::unix::net::UnixListener) -> io::Result<()>{
+    for stream in listener.incoming(){
+        let stream = stream?;
+        thread::spawn(move || {
+            if let Err(err) = serve_connection(stream){
+                eprintln!("connection closed: {err}");
+            }
+        });
+    }
+    Ok(())
+}
+
+/// the reference client of the protocol
+///
+/// Errors reported by the server are returned as `io::ErrorKind::Other`.
+///
+/// # Examples
+///
+/// ```
+/// use std::net::TcpListener;
+/// use env_server::{serve_tcp, Client};
+///
+/// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+/// let address = listener.local_addr().unwrap();
+/// std::thread::spawn(move || serve_tcp(listener));
+///
+/// let mut client = Client::connect_tcp(address).unwrap();
+/// let id = client.create((10,10), (5,5), 1, "square", 42).unwrap();
+/// let (done, _, msg) = client.step(id, 1).unwrap();
+/// assert!(!done);
+/// assert_eq!(msg, "alive");
+/// assert_eq!(client.observe(id, "features").unwrap().len(), 12);
+/// client.close(id).unwrap();
+/// ```
+pub struct Client<S : Read + Write>{
+    stream : S
+}
+
+impl Client<TcpStream> {
+
+    pub fn connect_tcp(address : impl std::net::ToSocketAddrs) -> io::Result<Client<TcpStream>>{
+        let stream = TcpStream::connect(address)?;
+        stream.set_nodelay(true)?;
+        Ok(Client { stream })
+    }
+}
+
+#[cfg(unix)]
+impl Client<std::os::unix::net::UnixStream> {
+
+    pub fn connect_unix(path : impl AsRef<std::path::Path>) -> io::Result<Client<std::os::unix::net::UnixStream>>{
+        Ok(Client { stream : std::os::unix::net::UnixStream::connect(path)? })
+    }
+}
+
+impl<S : Read + Write> Client<S> {
+
+    /// sends a request and returns the response, server errors become `Err`
+    pub fn request(&mut self, request : &Value) -> io::Result<Value>{
+        write_message(&mut self.stream, request)?;
+        let response = read_message(&mut self.stream)?
+            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "the server closed the connection"))?;
+        match response["ok"].as_bool() {
+            Some(true) => Ok(response),
+            _ => Err(io::Error::other(response["error"].as_str().unwrap_or("invalid response").to_string()))
+        }
+    }
+
+    /// creates an engine and returns its id
+    pub fn create(&mut self, size : (usize,usize), start : (usize,usize), direction : usize, topology : &str, seed : u64) -> io::Result<u64>{
+        let response = self.request(&json!({
+            "op" : "create",
+            "size" : [size.0, size.1],
+            "start" : [start.0, start.1],
+            "direction" : direction,
+            "topology" : topology,
+            "seed" : seed
+        }))?;
+        Ok(response["id"].as_u64().unwrap_or_default())
+    }
+
+    pub fn reset(&mut self, id : u64, seed : u64) -> io::Result<()>{
+        self.request(&json!({ "op" : "reset", "id" : id, "seed" : seed })).map(|_| ())
+    }
+
+    /// advances the engine, returns `(done, food_eaten, msg)` like `GameEngine::step`
+    pub fn step(&mut self, id : u64, action : usize) -> io::Result<(bool,bool,String)>{
+        let response = self.request(&json!({ "op" : "step", "id" : id, "action" : action }))?;
+        Ok((
+            response["done"].as_bool().unwrap_or_default(),
+            response["food_eaten"].as_bool().unwrap_or_default(),
+            response["msg"].as_str().unwrap_or_default().to_string()
+        ))
+    }
+
+    pub fn observe(&mut self, id : u64, observation_type : &str) -> io::Result<Vec<f32>>{
+        let response = self.request(&json!({ "op" : "observe", "id" : id, "type" : observation_type }))?;
+        Ok(response["observation"].as_array()
+            .map(|values| values.iter().map(|value| value.as_f64().unwrap_or_default() as f32).collect())
+            .unwrap_or_default())
+    }
+
+    pub fn close(&mut self, id : u64) -> io::Result<()>{
+        self.request(&json!({ "op" : "close", "id" : id })).map(|_| ())
+    }
+}
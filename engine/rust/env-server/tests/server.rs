@@ -0,0 +1,106 @@
+//! Tests of the server and the reference client on localhost
+
+use std::io::Write;
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use serde_json::json;
+use env_server::*;
+use snake_game_rust::snake::*;
+use snake_game_rust::snake::observation::ObservationType;
+
+fn start_server() -> std::net::SocketAddr{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || serve_tcp(listener));
+    address
+}
+
+#[test]
+fn plays_the_same_game_as_the_engine(){
+    let mut client = Client::connect_tcp(start_server()).unwrap();
+    let id = client.create((10,10), (5,5), 1, "square", 42).unwrap();
+
+    let mut game = GameEngine::new((10,10), (5,5), 1);
+    game.reset(42);
+
+    for episode in 0..3{
+        client.reset(id, episode).unwrap();
+        game.reset(episode);
+        for step in 0..100{
+            let action = [1, 1, 0, 1, 2, 2, 1][step % 7];
+            let outcome = client.step(id, action).unwrap();
+            assert_eq!(outcome, game.step(action));
+            assert_eq!(client.observe(id, "features").unwrap(), game.observe(ObservationType::Features));
+            if outcome.0{
+                break;
+            }
+        }
+    }
+    assert_eq!(client.observe(id, "grid").unwrap(), game.observe(ObservationType::Grid));
+    client.close(id).unwrap();
+}
+
+#[test]
+fn hosts_many_engines_and_connections(){
+    let address = start_server();
+    let handles : Vec<_> = (0..4).map(|seed| thread::spawn(move || {
+        let mut client = Client::connect_tcp(address).unwrap();
+        let ids : Vec<u64> = (0..8).map(|_| client.create((10,10), (5,5), 1, "square", seed).unwrap()).collect();
+        let worlds : Vec<Vec<f32>> = ids.iter().map(|id| client.observe(*id, "grid").unwrap()).collect();
+        //engines with the same seed start the same
+        assert!(worlds.windows(2).all(|pair| pair[0] == pair[1]));
+        for id in ids{
+            client.step(id, 0).unwrap();
+            client.close(id).unwrap();
+        }
+    })).collect();
+    for handle in handles{
+        handle.join().unwrap();
+    }
+}
+
+#[test]
+fn reports_errors_without_closing(){
+    let mut client = Client::connect_tcp(start_server()).unwrap();
+
+    let errors = [
+        json!({ "op" : "fly" }),
+        json!({ "op" : "step", "id" : 3, "action" : 1 }),
+        json!({ "op" : "create", "topology" : "triangle" }),
+        json!({ "op" : "create", "size" : [10, 10], "start" : [9, 5], "direction" : 1 }),
+        json!({ "op" : "create", "size" : [100000, 100000] }),
+        json!({ "op" : "reset", "id" : 0 })
+    ];
+    for request in &errors{
+        assert!(client.request(request).is_err(), "{request}");
+    }
+
+    let id = client.create((5,5), (2,2), 3, "hex", 0).unwrap();
+    assert!(client.step(id, 5).is_err());
+    assert!(client.observe(id, "pixels").is_err());
+    assert_eq!(client.observe(id, "features").unwrap().len(), 16);
+    client.close(id).unwrap();
+    assert!(client.close(id).is_err());
+}
+
+#[test]
+fn rejects_oversized_messages(){
+    let mut stream = TcpStream::connect(start_server()).unwrap();
+    stream.write_all(&(MAX_MESSAGE_LEN as u32 + 1).to_be_bytes()).unwrap();
+    //the server closes the connection instead of allocating the message
+    assert!(read_message(&mut stream).map(|message| message.is_none()).unwrap_or(true));
+}
+
+#[cfg(unix)]
+#[test]
+fn serves_unix_sockets(){
+    let path = std::env::temp_dir().join(format!("snake-env-server-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let listener = std::os::unix::net::UnixListener::bind(&path).unwrap();
+    thread::spawn(move || serve_unix(listener));
+
+    let mut client = Client::connect_unix(&path).unwrap();
+    let id = client.create((10,10), (5,5), 1, "square", 7).unwrap();
+    assert_eq!(client.step(id, 1).unwrap(), (false, false, "alive".to_string()));
+    std::fs::remove_file(&path).unwrap();
+}
@@ -21,7 +21,29 @@
 //! `(your env-name)C:\....\snake-nn\engine\rust\python-wrapper>`
 //! * finally install the package locally with `maturin develop`
 use pyo3::prelude::*;
+use pyo3::exceptions::{PyValueError, PyIOError, PyRuntimeError};
+use pyo3::types::{PyBytes, PyByteArray, PyDict};
 use snake_game_rust::snake::*;
+use snake_game_rust::snake::data_types::Vec2;
+use snake_game_rust::snake::snake::Snake;
+use snake_game_rust::snake::observation::ObservationType;
+use snake_game_rust::snake::visibility::{Visibility, Metric};
+use snake_game_rust::snake::rng::{EngineRng, RNG_VERSIONS, CURRENT_RNG_VERSION};
+use snake_game_rust::snake::food_schedule::FoodPlacement;
+use snake_game_rust::snake::rules::{Rules, TailRule, Reversal, Walls};
+use snake_game_rust::snake::limits::Limits;
+use snake_game_rust::snake::registry;
+use snake_game_rust::snake::scenario::{ScenarioGenerator, BodyMethod};
+use snake_game_rust::snake::cell::Cell;
+use snake_game_rust::snake::render::{self, Renderer, Frame};
+
+fn observation_type_from_name(name : &str) -> PyResult<ObservationType>{
+    match name {
+        "grid" => Ok(ObservationType::Grid),
+        "features" => Ok(ObservationType::Features),
+        _ => Err(PyValueError::new_err(format!("Unknown observation type {name}")))
+    }
+}
 
 
 /// A wrapper around the `GameEngine` struct
@@ -51,11 +73,14 @@ impl EngineWrapper{
     /// * 1 - up        
     /// * 2 - right
     /// * 3 - down
+    /// * `topology` - optional name of the grid topology, `"square"` (default),
+    /// `"hex"` or `"octile"`. The directions and actions of the other topologies 
+    /// are described in the `snake_game_rust::snake::topology` module
     /// 
     /// # Panics
     /// Panics if the either of the input is of incorrect type, either of 
     /// the size dimensions is 0, the snake would be spawned outside the grid or
-    /// the direction is not mapped.
+    /// the direction is not mapped. Raises a `ValueError` for an unknown topology.
     /// 
     /// # Examples 
     /// 
@@ -68,9 +93,47 @@ impl EngineWrapper{
     /// ew = EngineWrapper((10,10),(5,5),0)
     /// ```
     #[new]
-    pub fn py_new(size : (usize,usize), starting_pos : (usize,usize), starting_direction : usize) -> Self{
-        let mut ge = GameEngine::new(size, starting_pos,starting_direction);
-        EngineWrapper{ engine : ge}
+    #[pyo3(signature = (size, starting_pos, starting_direction, topology = "square"))]
+    pub fn py_new(size : (usize,usize), starting_pos : (usize,usize), starting_direction : usize, topology : &str) -> PyResult<Self>{
+        let topology = match topology::from_name(topology) {
+            Some(topology) => topology,
+            None => return Err(PyValueError::new_err(format!("Unknown topology {topology}")))
+        };
+        let ge = GameEngine::with_topology(size, starting_pos, starting_direction, topology);
+        Ok(EngineWrapper{ engine : ge})
+    }
+
+    /// creates an Engine in an explicitly given state, see `GameEngine::from_parts`
+    /// 
+    /// * `body` - a list of `(x, y)` cells of the snake ordered from head to tail
+    /// * `direction` - the direction the head is facing
+    /// * `food_pos` - the position of the food, or `None` to place it at random
+    /// * `seed` - the seed of the random generator
+    /// 
+    /// Raises a `ValueError` if the state is not valid
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// from python_wrapper import EngineWrapper
+    /// 
+    /// ew = EngineWrapper.from_parts((10,10), [(5,5),(5,6),(6,6)], 0, (0,0), 12)
+    /// ```
+    #[staticmethod]
+    #[pyo3(signature = (size, body, direction, food_pos, seed))]
+    pub fn from_parts(
+        size : (usize,usize),
+        body : Vec<(i32,i32)>,
+        direction : usize,
+        food_pos : Option<(i32,i32)>,
+        seed : u64
+    ) -> PyResult<Self>{
+        let body = body.into_iter().map(|(x, y)| Vec2 { x, y }).collect();
+        let food_pos = food_pos.map(|(x, y)| Vec2 { x, y });
+        match GameEngine::from_parts(size, body, direction, food_pos, seed) {
+            Ok(engine) => Ok(EngineWrapper { engine }),
+            Err(err) => Err(PyValueError::new_err(err.to_string()))
+        }
     }
 
     /// take an action as an input, forwards the game by one iteration,
@@ -82,12 +145,13 @@ impl EngineWrapper{
     /// * `msg` - a string, a short message describing the iteration, mostly 
     /// used for learning enviroment 
     /// 
-    /// For now `msg` can take on 4 values:
+    /// For now `msg` can take on 5 values:
     /// 
     /// * `"alive"` - if snake is alive, 
     /// * `"body"` - if the snake has collided with its own body
     /// * `"wall"` - if the snake has collided with on of the walls
-    /// * `"victory"` - if the snake body is taking up the entire world grid
+    /// * `"obstacle"` - if the snake has collided with an obstacle
+    /// * `"victory"` - if the snake body is taking up the entire free part of the world grid
     /// 
     /// # Panics
     /// 
@@ -115,6 +179,22 @@ impl EngineWrapper{
         self.engine.step(action)
     }
 
+    /// sets the probability of repeating the previous action (`sticky_prob`)
+    /// and of executing a random action (`epsilon`) instead of the given one
+    /// 
+    /// # Panics
+    /// 
+    /// Will panic if either of the probabilities is outside of `[0, 1]`
+    pub fn py_set_action_noise(&mut self, sticky_prob : f64, epsilon : f64){
+        self.engine.set_action_noise(action_noise::ActionNoise { sticky_prob, epsilon })
+    }
+
+    /// returns the action executed on the last step, which can differ from
+    /// the given action when action noise is enabled
+    pub fn py_get_executed_action(&self) -> usize{
+        self.engine.last_action
+    }
+
     
     /// resets the world state given a seed 
     /// 
@@ -139,6 +219,94 @@ impl EngineWrapper{
         self.engine.reset(seed)
     }
 
+    /// sets how new food is placed, `"random"` or `"scheduled"`, with the
+    /// schedule every player on the same seed gets the same food as long as
+    /// the cells are free. Call reset afterwards to start from the first food
+    pub fn py_set_food_placement(&mut self, food_placement : &str) -> PyResult<()>{
+        match FoodPlacement::from_name(food_placement) {
+            Some(food_placement) => {
+                self.engine.set_food_placement(food_placement);
+                Ok(())
+            },
+            None => Err(PyValueError::new_err(format!("Unknown food placement {food_placement}, expected random or scheduled")))
+        }
+    }
+
+    /// sets the collision rules, `tail` is `"chase"` or `"block"`,
+    /// `reversal` is `"ignore"` or `"collide"` and `walls` is `"solid"` or `"wrap"`
+    #[pyo3(signature = (tail = "chase", reversal = "ignore", walls = "solid"))]
+    pub fn py_set_rules(&mut self, tail : &str, reversal : &str, walls : &str) -> PyResult<()>{
+        let tail = TailRule::from_name(tail)
+            .ok_or_else(|| PyValueError::new_err(format!("Unknown tail rule {tail}, expected chase or block")))?;
+        let reversal = Reversal::from_name(reversal)
+            .ok_or_else(|| PyValueError::new_err(format!("Unknown reversal rule {reversal}, expected ignore or collide")))?;
+        let walls = Walls::from_name(walls)
+            .ok_or_else(|| PyValueError::new_err(format!("Unknown walls {walls}, expected solid or wrap")))?;
+        self.engine.set_rules(Rules { tail, reversal, walls });
+        Ok(())
+    }
+
+    /// sets the episode limits, the game ends with `"timeout"` after
+    /// `max_steps` steps and with `"starved"` after `max_steps_without_food`
+    /// steps without eating, `None` disables a limit
+    #[pyo3(signature = (max_steps = None, max_steps_without_food = None))]
+    pub fn py_set_limits(&mut self, max_steps : Option<usize>, max_steps_without_food : Option<usize>){
+        self.engine.set_limits(Limits { max_steps, max_steps_without_food });
+    }
+
+    /// the shortest list of actions that takes the head to the food,
+    /// `None` when no actions reach it, see `GameEngine::path_to_food`
+    pub fn py_path_to_food(&self) -> Option<Vec<usize>>{
+        self.engine.path_to_food()
+    }
+
+    /// the number of steps since the snake last ate
+    pub fn py_get_steps_since_food(&self) -> usize{
+        self.engine.get_steps_since_food()
+    }
+
+    /// advances the game by one step in an absolute direction,
+    /// returns the same tuple as step
+    pub fn py_step_direction(&mut self, direction : usize) -> PyResult<(bool,bool,String)>{
+        if direction >= self.engine.topology.num_directions(){
+            return Err(PyValueError::new_err(format!("Unknown direction {direction}")));
+        }
+        let outcome = self.engine.step_direction(direction);
+        Ok((outcome.done, outcome.food_eaten, outcome.msg))
+    }
+
+    /// keeps the states before the last `capacity` steps so they can be
+    /// undone, a capacity of 0 disables the history
+    pub fn py_set_history(&mut self, capacity : usize){
+        self.engine.set_history(capacity)
+    }
+
+    /// returns to the state before the last step, including the random
+    /// generator, returns false if there is no stored state
+    pub fn py_undo(&mut self) -> bool{
+        self.engine.undo()
+    }
+
+    /// steps forward again after an undo, returns false if there is nothing to redo
+    pub fn py_redo(&mut self) -> bool{
+        self.engine.redo()
+    }
+
+    /// returns the version of the random generator, stored with every run
+    pub fn py_get_rng_version(&self) -> u32{
+        self.engine.get_rng_version()
+    }
+
+    /// selects the version of the random generator, set the version a game
+    /// was recorded with before resetting with its seed to replay it
+    pub fn py_set_rng_version(&mut self, version : u32) -> PyResult<()>{
+        if !RNG_VERSIONS.contains(&version){
+            return Err(PyValueError::new_err(format!("Unknown rng version {version}, expected one of {RNG_VERSIONS:?}")));
+        }
+        self.engine.set_rng_version(version);
+        Ok(())
+    }
+
 
     ///generic getter function for the game_world matrix
     /// 
@@ -149,6 +317,33 @@ impl EngineWrapper{
         self.engine.get_world().matrix
     }
 
+    /// returns the code of the cell at `(x, y)`, one of the `CELL_*`
+    /// constants of the module, or None outside of the world
+    pub fn py_cell_at(&self, pos : (i32,i32)) -> Option<i32>{
+        self.engine.cell_at(Vec2{ x : pos.0, y : pos.1 }).map(|cell| cell.code())
+    }
+
+    /// returns True if the snake dies entering `(x, y)`, a cell outside
+    /// of the world, of the snake or an obstacle
+    pub fn py_is_blocked(&self, pos : (i32,i32)) -> bool{
+        self.engine.is_blocked(Vec2{ x : pos.0, y : pos.1 })
+    }
+
+    /// returns the index of the snake part at `(x, y)`, 0 for the head,
+    /// or None if the snake is not there
+    pub fn py_body_index_at(&self, pos : (i32,i32)) -> Option<usize>{
+        self.engine.body_index_at(Vec2{ x : pos.0, y : pos.1 })
+    }
+
+    /// returns the cells changed by the last step as a list of
+    /// `((x, y), old, new)` tuples, so the world can be updated
+    /// without copying it every step
+    pub fn py_get_delta(&self) -> Vec<((i32,i32),i32,i32)>{
+        self.engine.get_delta().iter()
+            .map(|change| ((change.pos.x, change.pos.y), change.old, change.new))
+            .collect()
+    }
+
     ///generic getter function for the position of the snake head
     /// 
     /// In python it returns a list of two coordinates
@@ -167,12 +362,597 @@ impl EngineWrapper{
         vec![food_pos.x,food_pos.y]
     }
 
+    /// enables the fog of war, the snake only sees cells within `radius`
+    /// of its head
+    /// 
+    /// * `metric` - `"manhattan"` or `"euclidean"`
+    /// * `line_of_sight` - if true the body and obstacles block the view
+    /// 
+    /// A radius of `None` disables the fog of war.
+    #[pyo3(signature = (radius, metric = "manhattan", line_of_sight = false))]
+    pub fn py_set_visibility(&mut self, radius : Option<usize>, metric : &str, line_of_sight : bool) -> PyResult<()>{
+        let metric = match metric {
+            "manhattan" => Metric::Manhattan,
+            "euclidean" => Metric::Euclidean,
+            _ => return Err(PyValueError::new_err(format!("Unknown metric {metric}")))
+        };
+        self.engine.set_visibility(radius.map(|radius| Visibility { radius, metric, line_of_sight }));
+        Ok(())
+    }
+
+    /// returns the world as seen by the snake, hidden cells are -1 and
+    /// stale cells hold their last seen value
+    pub fn py_get_masked_world(&self) -> Vec<Vec<i32>>{
+        self.engine.get_masked_world().matrix
+    }
+
+    /// returns the visibility mask, 0 - hidden, 1 - stale, 2 - visible
+    pub fn py_get_visibility_mask(&self) -> Vec<Vec<i32>>{
+        self.engine.get_visibility_mask().matrix
+    }
+
+    /// returns the encoded observation, `observation_type` is either
+    /// `"grid"` or `"features"`. With `masked` the observation is built
+    /// from the world as seen through the fog of war
+    #[pyo3(signature = (observation_type = "features", masked = false))]
+    pub fn py_observe(&self, observation_type : &str, masked : bool) -> PyResult<Vec<f32>>{
+        let observation_type = observation_type_from_name(observation_type)?;
+        Ok(match masked {
+            true => self.engine.observe_masked(observation_type),
+            false => self.engine.observe(observation_type)
+        })
+    }
+
+    /// draws the world as an RGB image of `cell_size` pixels per cell,
+    /// returns the pixels as bytes and the `(height, width, 3)` shape, so
+    /// `np.frombuffer(pixels, np.uint8).reshape(shape)` gives an `rgb_array`.
+    /// With `masked` the world is drawn as seen through the fog of war
+    #[pyo3(signature = (cell_size = 16, masked = false))]
+    pub fn py_render<'py>(&self, py : Python<'py>, cell_size : usize, masked : bool) -> PyResult<(&'py PyBytes, (usize,usize,usize))>{
+        let frame = render_world(self, cell_size, masked)?;
+        Ok((PyBytes::new(py, &frame.pixels), (frame.height, frame.width, 3)))
+    }
+
+    /// saves the world drawn like `py_render` as a PNG image
+    #[pyo3(signature = (path, cell_size = 16, masked = false))]
+    pub fn py_save_png(&self, path : &str, cell_size : usize, masked : bool) -> PyResult<()>{
+        let frame = render_world(self, cell_size, masked)?;
+        frame.save_png(path).map_err(|err| PyIOError::new_err(err.to_string()))
+    }
+
+    /// returns the Zobrist hash of the current state
+    pub fn py_get_hash(&self) -> u64{
+        self.engine.get_hash()
+    }
+
+    /// returns a key of the current state that is invariant under the
+    /// symmetries of the world grid
+    pub fn py_canonical_key(&self) -> u64{
+        self.engine.canonical_key()
+    }
+
+    ///returns the neighbours of a cell that lie inside the world grid,
+    ///according to the topology of the world
+    /// 
+    /// In python it returns a list of lists of two coordinates
+    pub fn py_get_neighbours(&self, pos : (i32,i32)) -> Vec<Vec<i32>>{
+        self.engine.neighbours(Vec2 { x: pos.0, y: pos.1 })
+            .into_iter()
+            .map(|cell| vec![cell.x, cell.y])
+            .collect()
+    }
+
+
+}
+fn render_world(wrapper : &EngineWrapper, cell_size : usize, masked : bool) -> PyResult<Frame>{
+    if cell_size == 0{
+        return Err(PyValueError::new_err("The cell size has to be positive"));
+    }
+    let world = match masked {
+        true => wrapper.engine.get_masked_world(),
+        false => wrapper.engine.get_world()
+    };
+    Ok(Renderer::new(cell_size).render(&world))
+}
+
+/// the observations of `SnakeGymEnv`, the encodings of `ObservationType`
+/// and the window of `GameEngine::observe_viewport`
+#[derive(Clone,Copy,PartialEq,Eq,Debug)]
+enum GymObservation{
+    Encoded(ObservationType),
+    Viewport(usize)
+}
+
+impl GymObservation {
+
+    fn from_name(name : &str, viewport_radius : usize) -> PyResult<GymObservation>{
+        match name {
+            "viewport" => Ok(GymObservation::Viewport(viewport_radius)),
+            _ => observation_type_from_name(name)
+                .map(GymObservation::Encoded)
+                .map_err(|_| PyValueError::new_err(format!("Unknown observation type {name}, expected grid, features or viewport")))
+        }
+    }
+
+    fn shape(&self, engine : &GameEngine) -> Vec<usize>{
+        match self {
+            GymObservation::Encoded(ObservationType::Grid) => vec![engine.world_size.0, engine.world_size.1],
+            GymObservation::Encoded(ObservationType::Features) => vec![ObservationType::Features.size(engine.world_size, engine.topology.as_ref())],
+            GymObservation::Viewport(radius) => vec![2*radius + 1, 2*radius + 1]
+        }
+    }
+
+    /// the bounds of every value, the cell codes or the 0/1 flags of the features
+    fn bounds(&self) -> (f32,f32){
+        match self {
+            GymObservation::Encoded(ObservationType::Features) => (0.0, 1.0),
+            _ => (0.0, Cell::ALL[Cell::ALL.len() - 1].code() as f32)
+        }
+    }
+
+    fn encode(&self, engine : &GameEngine) -> Vec<f32>{
+        match self {
+            GymObservation::Encoded(observation_type) => engine.observe(*observation_type),
+            GymObservation::Viewport(radius) => engine.observe_viewport(*radius)
+        }
+    }
+}
+
+/// the rewards of `SnakeGymEnv`
+#[derive(Clone,Copy,PartialEq,Debug)]
+struct Rewards{
+    food : f64,
+    death : f64,
+    step : f64
+}
+
+impl Rewards {
+
+    /// the reward, terminated and truncated of a step, reaching the step
+    /// limit truncates the episode and every other end terminates it
+    ///
+    /// The step eating the last food wins the game, the engine reports it
+    /// as a victory without food eaten and it pays the food reward.
+    fn outcome(&self, done : bool, food_eaten : bool, msg : &str) -> (f64,bool,bool){
+        let truncated = msg == "timeout";
+        let terminated = done && !truncated;
+        let reward = match msg {
+            _ if food_eaten || msg == "victory" => self.food,
+            "body" | "wall" | "obstacle" | "starved" => self.death,
+            _ => self.step
+        };
+        (reward, terminated, truncated)
+    }
+}
+
+/// creates the engine of `SnakeGymEnv`, the snake starts in the middle of
+/// the world by default
+///
+/// The engine panics on snakes outside of the world, so the starting snake
+/// is validated first like `snake_engine_create` of the C interface does.
+fn gym_engine(
+    size : (usize,usize),
+    starting_pos : Option<(usize,usize)>,
+    direction : usize,
+    topology : &str
+) -> Result<GameEngine,String>{
+    let topology = topology::from_name(topology).ok_or(format!("Unknown topology {topology}"))?;
+    let start = starting_pos.unwrap_or((size.0/2, size.1/2));
+    if direction >= topology.num_directions(){
+        return Err(format!("The starting direction has to be smaller than {}", topology.num_directions()));
+    }
+    if start.0 > i32::MAX as usize || start.1 > i32::MAX as usize{
+        return Err(format!("The starting position {start:?} is outside of the world"));
+    }
+
+    let head = Vec2{ x : start.0 as i32, y : start.1 as i32 };
+    let body = Snake::new(head, direction, topology.as_ref()).snake_body;
+    GameEngine::from_parts_with_topology(size, body, direction, None, 0, topology.clone())
+        .map_err(|err| err.to_string())?;
+
+    Ok(GameEngine::with_topology(size, start, direction, topology))
+}
+
+/// A Gymnasium environment around the `GameEngine` struct
+///
+/// The class implements the `gymnasium.Env` protocol, `reset` returns
+/// `(observation, info)` and `step` returns `(observation, reward, terminated,
+/// truncated, info)`. The step, the reward and the observation are computed in
+/// Rust, python only receives the finished numpy arrays. Requires the
+/// `gymnasium` and `numpy` packages at runtime.
+///
+/// * `observation_type` - `"features"` (default), `"grid"` or `"viewport"`,
+///   the cells in a square of `2*viewport_radius + 1` cells around the head.
+///   The features are the `ObservationType::Features` of the engine, not the
+///   observation of `snake_env_12in_turn.py`
+/// * `reward_food`, `reward_death` and `reward_step` - the reward for eating,
+///   for ending the game in a collision or starving and for every other step
+/// * `max_steps` and `max_steps_without_food` - the limits of `py_set_limits`,
+///   reaching `max_steps` truncates the episode, starving terminates it
+/// * `render_mode` - `None` or `"rgb_array"`
+///
+/// Raises a `ValueError` if the starting snake does not fit in the world.
+///
+/// # Examples
+///
+/// ```
+/// from python_wrapper import SnakeGymEnv
+///
+/// env = SnakeGymEnv((10,10), observation_type = "viewport", viewport_radius = 3)
+/// obs, info = env.reset(seed = 42)
+///
+/// terminated = truncated = False
+/// while not (terminated or truncated):
+///     obs, reward, terminated, truncated, info = env.step(env.action_space.sample())
+/// ```
+#[pyclass]
+pub struct SnakeGymEnv{
+    engine : GameEngine,
+    observation : GymObservation,
+    rewards : Rewards,
+    //draws the seeds of the resets without a seed
+    seeds : EngineRng,
+    done : bool,
+    numpy : PyObject,
+    #[pyo3(get)]
+    action_space : PyObject,
+    #[pyo3(get)]
+    observation_space : PyObject,
+    #[pyo3(get)]
+    metadata : PyObject,
+    #[pyo3(get)]
+    render_mode : Option<String>
+}
+
+impl SnakeGymEnv {
+
+    #[allow(clippy::too_many_arguments)]
+    fn build(
+        py : Python,
+        engine : GameEngine,
+        observation_type : &str,
+        viewport_radius : usize,
+        rewards : Rewards,
+        render_mode : Option<String>
+    ) -> PyResult<SnakeGymEnv>{
+        if render_mode.as_deref().is_some_and(|mode| mode != "rgb_array"){
+            return Err(PyValueError::new_err(format!("Unknown render mode {}, expected rgb_array", render_mode.unwrap())));
+        }
+        let observation = GymObservation::from_name(observation_type, viewport_radius)?;
+        let numpy = py.import("numpy")?;
+        let spaces = py.import("gymnasium.spaces")?;
+
+        let (low, high) = observation.bounds();
+        let action_space = spaces.getattr("Discrete")?.call1((engine.topology.num_actions(),))?;
+        let observation_space = spaces.getattr("Box")?
+            .call1((low, high, observation.shape(&engine), numpy.getattr("float32")?))?;
+        let metadata = PyDict::new(py);
+        metadata.set_item("render_modes", vec!["rgb_array"])?;
+
+        Ok(SnakeGymEnv {
+            engine,
+            observation,
+            rewards,
+            seeds : EngineRng::from_entropy(CURRENT_RNG_VERSION),
+            done : false,
+            numpy : numpy.into(),
+            action_space : action_space.into(),
+            observation_space : observation_space.into(),
+            metadata : metadata.into(),
+            render_mode
+        })
+    }
+
+    /// the current observation as a float32 numpy array
+    fn observe(&self, py : Python) -> PyResult<PyObject>{
+        let obs = self.observation.encode(&self.engine);
+        let bytes : Vec<u8> = obs.iter().flat_map(|value| value.to_ne_bytes()).collect();
+        let array = self.numpy.as_ref(py)
+            .call_method1("frombuffer", (PyByteArray::new(py, &bytes), self.numpy.as_ref(py).getattr("float32")?))?
+            .call_method1("reshape", (self.observation.shape(&self.engine),))?;
+        Ok(array.into())
+    }
+}
+
+#[pymethods]
+impl SnakeGymEnv{
+
+    /// creates the environment on a new engine, see `EngineWrapper.__init__`,
+    /// the snake starts in the middle of the world by default
+    #[new]
+    #[pyo3(signature = (
+        size,
+        starting_pos = None,
+        starting_direction = 1,
+        topology = "square",
+        observation_type = "features",
+        viewport_radius = 5,
+        reward_food = 1.0,
+        reward_death = -1.0,
+        reward_step = 0.0,
+        max_steps = None,
+        max_steps_without_food = None,
+        render_mode = None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn py_new(
+        py : Python,
+        size : (usize,usize),
+        starting_pos : Option<(usize,usize)>,
+        starting_direction : usize,
+        topology : &str,
+        observation_type : &str,
+        viewport_radius : usize,
+        reward_food : f64,
+        reward_death : f64,
+        reward_step : f64,
+        max_steps : Option<usize>,
+        max_steps_without_food : Option<usize>,
+        render_mode : Option<String>
+    ) -> PyResult<Self>{
+        let mut engine = gym_engine(size, starting_pos, starting_direction, topology).map_err(PyValueError::new_err)?;
+        engine.set_limits(Limits { max_steps, max_steps_without_food });
+        let rewards = Rewards { food : reward_food, death : reward_death, step : reward_step };
+        SnakeGymEnv::build(py, engine, observation_type, viewport_radius, rewards, render_mode)
+    }
+
+    /// creates the environment on the registered environment with the given
+    /// name, like `"classic-10x10-v0"`, keeping its limits
+    #[staticmethod]
+    #[pyo3(signature = (
+        name,
+        observation_type = "features",
+        viewport_radius = 5,
+        reward_food = 1.0,
+        reward_death = -1.0,
+        reward_step = 0.0,
+        render_mode = None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn make(
+        py : Python,
+        name : &str,
+        observation_type : &str,
+        viewport_radius : usize,
+        reward_food : f64,
+        reward_death : f64,
+        reward_step : f64,
+        render_mode : Option<String>
+    ) -> PyResult<Self>{
+        let engine = registry::make(name).map_err(|err| PyValueError::new_err(err.to_string()))?;
+        let rewards = Rewards { food : reward_food, death : reward_death, step : reward_step };
+        SnakeGymEnv::build(py, engine, observation_type, viewport_radius, rewards, render_mode)
+    }
+
+    /// starts a new episode, returns `(observation, info)`
+    ///
+    /// A seed makes the episode and every following episode reset without a
+    /// seed reproducible. `options` is accepted for the protocol and ignored
+    #[pyo3(signature = (seed = None, options = None))]
+    pub fn reset(&mut self, py : Python, seed : Option<u64>, options : Option<&PyDict>) -> PyResult<(PyObject,PyObject)>{
+        let _ = options;
+        let seed = match seed {
+            Some(seed) => {
+                self.seeds = EngineRng::new(CURRENT_RNG_VERSION, seed);
+                seed
+            },
+            None => self.seeds.u64()
+        };
+        self.engine.reset(seed);
+        self.done = false;
+
+        let info = PyDict::new(py);
+        info.set_item("seed", seed)?;
+        info.set_item("score", self.engine.get_score())?;
+        Ok((self.observe(py)?, info.into()))
+    }
+
+    /// advances the game by one step, returns `(observation, reward,
+    /// terminated, truncated, info)`
+    ///
+    /// Reaching `max_steps` truncates the episode, every other end of the game
+    /// terminates it. The info holds the message of `EngineWrapper.py_step`,
+    /// whether food was eaten, the score, the steps and the executed action.
+    /// Raises a `ValueError` for an unknown action and a `RuntimeError` when
+    /// the episode has ended and was not reset
+    pub fn step(&mut self, py : Python, action : usize) -> PyResult<(PyObject,f64,bool,bool,PyObject)>{
+        if action >= self.engine.topology.num_actions(){
+            return Err(PyValueError::new_err(format!("Unknown action {action}")));
+        }
+        if self.done{
+            return Err(PyRuntimeError::new_err("The episode has ended, call reset before stepping"));
+        }
+        let (done, food_eaten, msg) = self.engine.step(action);
+        self.done = done;
+
+        let (reward, terminated, truncated) = self.rewards.outcome(done, food_eaten, &msg);
+
+        let info = PyDict::new(py);
+        info.set_item("msg", &msg)?;
+        info.set_item("food_eaten", food_eaten)?;
+        info.set_item("score", self.engine.get_score())?;
+        info.set_item("steps", self.engine.steps)?;
+        info.set_item("executed_action", self.engine.last_action)?;
+        Ok((self.observe(py)?, reward, terminated, truncated, info.into()))
+    }
+
+    /// the world as a `(height, width, 3)` uint8 numpy array with the
+    /// `"rgb_array"` render mode, `None` without a render mode
+    #[pyo3(signature = (cell_size = 16))]
+    pub fn render(&self, py : Python, cell_size : usize) -> PyResult<PyObject>{
+        if self.render_mode.is_none(){
+            return Ok(py.None());
+        }
+        if cell_size == 0{
+            return Err(PyValueError::new_err("The cell size has to be positive"));
+        }
+        let frame = Renderer::new(cell_size).render(&self.engine.get_world());
+        let numpy = self.numpy.as_ref(py);
+        let array = numpy
+            .call_method1("frombuffer", (PyByteArray::new(py, &frame.pixels), numpy.getattr("uint8")?))?
+            .call_method1("reshape", ((frame.height, frame.width, 3),))?;
+        Ok(array.into())
+    }
 
+    /// releases nothing, the engine holds no outside resources
+    pub fn close(&mut self){}
+
+    /// the environment itself, there are no wrappers around it
+    #[getter]
+    pub fn unwrapped(slf : PyRef<Self>) -> PyRef<Self>{
+        slf
+    }
+}
+
+/// saves frames returned by `EngineWrapper.py_render` as an endlessly
+/// looping animated GIF, showing every frame for `delay_ms` milliseconds
+#[pyfunction]
+#[pyo3(signature = (path, frames, shape, delay_ms = 100))]
+fn save_gif(path : &str, frames : Vec<&PyBytes>, shape : (usize,usize,usize), delay_ms : u32) -> PyResult<()>{
+    let (height, width, _) = shape;
+    let frames : Vec<Frame> = frames.into_iter()
+        .map(|pixels| Frame { width, height, pixels : pixels.as_bytes().to_vec() })
+        .collect();
+    if frames.iter().any(|frame| frame.pixels.len() != 3*width*height){
+        return Err(PyValueError::new_err(format!("Every frame needs {} bytes for the shape {shape:?}", 3*width*height)));
+    }
+    render::save_gif(&frames, &Renderer::default().colors(), delay_ms, path)
+        .map_err(|err| PyIOError::new_err(err.to_string()))
+}
+
+/// builds the registered environment with the given name, like
+/// `"classic-10x10-v0"`, reset it with a seed before playing
+#[pyfunction]
+fn make(name : &str) -> PyResult<EngineWrapper>{
+    registry::make(name)
+        .map(|engine| EngineWrapper { engine })
+        .map_err(|err| PyValueError::new_err(err.to_string()))
+}
+
+/// generates a random mid-game state with a body of `length` cells,
+/// see `snake_game_rust::snake::scenario`
+///
+/// * `method` - `"random_walk"` or `"solver"`, how the body is built
+/// * `solvable_only` - only return states where the food can be reached
+///
+/// Returns the engine and the fewest steps to the food, or `None` when
+/// the food cannot be reached. Raises a `ValueError` when no state is found
+#[pyfunction]
+#[pyo3(signature = (size, length, seed, method = "random_walk", topology = "square", solvable_only = false))]
+fn generate_scenario(size : (usize,usize), length : usize, seed : u64, method : &str, topology : &str, solvable_only : bool) -> PyResult<(EngineWrapper,Option<usize>)>{
+    let method = BodyMethod::from_name(method)
+        .ok_or_else(|| PyValueError::new_err(format!("Unknown method {method}, expected random_walk or solver")))?;
+    let topology = topology::from_name(topology)
+        .ok_or_else(|| PyValueError::new_err(format!("Unknown topology {topology}")))?;
+    let scenario = ScenarioGenerator::new(size, length)
+        .with_method(method)
+        .with_topology(topology)
+        .with_solvable_only(solvable_only)
+        .with_steps_to_food(true)
+        .generate(seed)
+        .map_err(|err| PyValueError::new_err(err.to_string()))?;
+    Ok((EngineWrapper { engine : scenario.engine() }, scenario.steps_to_food))
+}
+
+/// the names of the registered environments
+#[pyfunction]
+fn registered_envs() -> Vec<&'static str>{
+    registry::names()
 }
 
 /// A Python module implemented in Rust.
 #[pymodule]
 fn python_wrapper(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<EngineWrapper>()?;
+    m.add_class::<SnakeGymEnv>()?;
+    m.add_function(wrap_pyfunction!(save_gif, m)?)?;
+    m.add_function(wrap_pyfunction!(make, m)?)?;
+    m.add_function(wrap_pyfunction!(registered_envs, m)?)?;
+    m.add_function(wrap_pyfunction!(generate_scenario, m)?)?;
+    //the codes of the world cells, `CELL_EMPTY`, `CELL_HEAD`, `CELL_BODY`, ...
+    for cell in Cell::ALL{
+        m.add(&format!("CELL_{}", cell.name().to_uppercase()), cell.code())?;
+    }
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REWARDS : Rewards = Rewards { food : 1.0, death : -1.0, step : -0.01 };
+
+    #[test]
+    fn outcome(){
+        assert_eq!(REWARDS.outcome(false, false, "alive"), (-0.01, false, false));
+        assert_eq!(REWARDS.outcome(false, true, "alive"), (1.0, false, false));
+        for msg in ["body", "wall", "obstacle", "starved"]{
+            assert_eq!(REWARDS.outcome(true, false, msg), (-1.0, true, false), "{msg}");
+        }
+        //the step limit truncates the episode instead of ending it
+        assert_eq!(REWARDS.outcome(true, false, "timeout"), (-0.01, false, true));
+    }
+
+    #[test]
+    fn victory(){
+        //the snake fills the 2x3 world, eating on every step
+        let mut engine = gym_engine((2,3), Some((1,0)), 0, "square").unwrap();
+        engine.reset(5);
+
+        let mut outcomes = vec![];
+        let mut last = String::new();
+        for action in [2, 2, 1]{
+            let (done, food_eaten, msg) = engine.step(action);
+            outcomes.push(REWARDS.outcome(done, food_eaten, &msg));
+            last = msg;
+        }
+        assert_eq!(last, "victory");
+        assert_eq!(outcomes, vec![(1.0, false, false), (1.0, false, false), (1.0, true, false)]);
+    }
+
+    #[test]
+    fn timeout_truncates(){
+        //the food out of reach of the three steps
+        let body = vec![Vec2 { x : 5, y : 5 }, Vec2 { x : 6, y : 5 }];
+        let mut engine = GameEngine::from_parts((10,10), body, 1, Some(Vec2 { x : 9, y : 9 }), 42).unwrap();
+        engine.set_limits(Limits { max_steps : Some(3), max_steps_without_food : None });
+
+        let mut outcomes = vec![];
+        for action in [0, 2, 0]{
+            let (done, food_eaten, msg) = engine.step(action);
+            outcomes.push(REWARDS.outcome(done, food_eaten, &msg));
+        }
+        assert_eq!(outcomes, vec![(-0.01, false, false), (-0.01, false, false), (-0.01, false, true)]);
+    }
+
+    #[test]
+    fn invalid_engines(){
+        let engine = gym_engine((10,12), None, 0, "hex").unwrap();
+        assert_eq!(engine.get_world().size, (10,12));
+
+        assert!(gym_engine((0,0), None, 1, "square").is_err());
+        assert!(gym_engine((10,10), Some((9,5)), 1, "square").is_err());
+        assert!(gym_engine((10,10), Some((5,usize::MAX)), 1, "square").is_err());
+        assert!(gym_engine((10,10), None, 4, "square").is_err());
+        assert!(gym_engine((10,10), None, 1, "triangle").is_err());
+    }
+
+    #[test]
+    fn observation_shapes(){
+        let mut engine = gym_engine((8,10), None, 1, "square").unwrap();
+        engine.reset(7);
+
+        let observations = [
+            (GymObservation::from_name("grid", 5).unwrap(), vec![8, 10], 4.0),
+            (GymObservation::from_name("features", 5).unwrap(), vec![12], 1.0),
+            (GymObservation::from_name("viewport", 2).unwrap(), vec![5, 5], 4.0)
+        ];
+        for (observation, shape, high) in observations{
+            assert_eq!(observation.shape(&engine), shape);
+            assert_eq!(observation.bounds(), (0.0, high));
+            let obs = observation.encode(&engine);
+            assert_eq!(obs.len(), shape.iter().product::<usize>());
+            assert!(obs.iter().all(|value| (0.0..=high).contains(value)), "{observation:?}");
+        }
+        assert!(GymObservation::from_name("pixels", 5).is_err());
+    }
+}
@@ -0,0 +1,101 @@
+//! Criterion benchmarks of the engine on square boards of different sizes
+//!
+//! Run with `cargo bench`, a single group with `cargo bench -- step`.
+//! The steps are replayed from a greedy game recorded on the same seed,
+//! so every size measures the same kind of long, food eating episode.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use snake_game_rust::snake::GameEngine;
+use snake_game_rust::snake::observation::ObservationType;
+use snake_game_rust::snake::policy::{GreedyPolicy, RandomPolicy};
+
+const SIZES : [usize;3] = [10, 20, 50];
+const SEED : u64 = 42;
+
+fn engine(size : usize) -> GameEngine{
+    let mut game = GameEngine::new((size,size), (size/2,size/2), 1);
+    game.reset(SEED);
+    game
+}
+
+/// an engine a few hundred steps into a greedy game, with a longer snake
+fn mid_game(size : usize) -> GameEngine{
+    let mut game = engine(size);
+    game.play(&mut GreedyPolicy, 4*size);
+    game
+}
+
+fn step(c : &mut Criterion){
+    let mut group = c.benchmark_group("step");
+    for size in SIZES{
+        let actions = engine(size).play(&mut GreedyPolicy, 10_000);
+        group.throughput(Throughput::Elements(actions.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &actions, |b, actions| {
+            b.iter_batched_ref(|| engine(size), |game| {
+                for action in actions{
+                    game.step(*action);
+                }
+            }, BatchSize::SmallInput)
+        });
+    }
+    group.finish();
+}
+
+fn reset(c : &mut Criterion){
+    let mut group = c.benchmark_group("reset");
+    for size in SIZES{
+        let mut game = engine(size);
+        let mut seed = 0;
+        group.bench_function(BenchmarkId::from_parameter(size), |b| b.iter(|| {
+            seed += 1;
+            game.reset(seed);
+        }));
+    }
+    group.finish();
+}
+
+fn get_world(c : &mut Criterion){
+    let mut group = c.benchmark_group("get_world");
+    for size in SIZES{
+        let game = mid_game(size);
+        group.bench_function(BenchmarkId::from_parameter(size), |b| b.iter(|| game.get_world()));
+    }
+    group.finish();
+}
+
+fn get_flattened_world(c : &mut Criterion){
+    let mut group = c.benchmark_group("get_flattened_world");
+    for size in SIZES{
+        let game = mid_game(size);
+        group.bench_function(BenchmarkId::from_parameter(size), |b| b.iter(|| game.get_flattened_world()));
+    }
+    group.finish();
+}
+
+fn observe(c : &mut Criterion){
+    let mut group = c.benchmark_group("observe");
+    for size in SIZES{
+        let game = mid_game(size);
+        group.bench_function(BenchmarkId::new("grid", size), |b| b.iter(|| game.observe(ObservationType::Grid)));
+        group.bench_function(BenchmarkId::new("features", size), |b| b.iter(|| game.observe(ObservationType::Features)));
+        group.bench_function(BenchmarkId::new("viewport", size), |b| b.iter(|| game.observe_viewport(5)));
+    }
+    group.finish();
+}
+
+fn random_episode(c : &mut Criterion){
+    let mut group = c.benchmark_group("random_episode");
+    for size in SIZES{
+        let mut game = engine(size);
+        let mut seed = 0;
+        group.bench_function(BenchmarkId::from_parameter(size), |b| b.iter(|| {
+            seed += 1;
+            game.reset(seed);
+            game.play(&mut RandomPolicy::new(seed), 10*size*size).len()
+        }));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, step, reset, get_world, get_flattened_world, observe, random_episode);
+criterion_main!(benches);
@@ -0,0 +1,179 @@
+//! Measures the steps per second of the engines under a random policy
+//!
+//! Every thread plays its own engine, resetting it with the next seed when an
+//! episode ends, so the numbers include the resets like a training loop does.
+//! The results are printed as JSON, or written to the `--output` file, to be
+//! compared between changes of the engine.
+//!
+//! ```text
+//! cargo run --release --bin bench -- --sizes 10,20,50 --steps 1000000 --output before.json
+//! ```
+
+use std::process::ExitCode;
+use std::thread;
+use std::time::Instant;
+use snake_game_rust::snake::GameEngine;
+use snake_game_rust::snake::env::SnakeEnv;
+use snake_game_rust::snake::sparse::SparseEngine;
+use snake_game_rust::snake::rng::{EngineRng, CURRENT_RNG_VERSION};
+
+const USAGE : &str = "usage: bench [--sizes <size>,...] [--engines dense|sparse,...] [--steps <steps per thread>]
+             [--threads <threads>] [--seed <seed>] [--output <file>]";
+
+struct Options{
+    sizes : Vec<usize>,
+    engines : Vec<String>,
+    steps : usize,
+    threads : usize,
+    seed : u64,
+    output : Option<String>
+}
+
+/// a measured run, all threads together
+struct Run{
+    engine : String,
+    size : usize,
+    threads : usize,
+    steps : usize,
+    episodes : usize,
+    seconds : f64
+}
+
+impl Run {
+    fn to_json(&self) -> String{
+        format!(
+            "{{\"engine\": \"{}\", \"size\": {}, \"threads\": {}, \"steps\": {}, \"episodes\": {}, \"seconds\": {:.6}, \"steps_per_sec\": {:.1}}}",
+            self.engine, self.size, self.threads, self.steps, self.episodes, self.seconds, self.steps as f64 / self.seconds
+        )
+    }
+}
+
+fn parse_list<T : std::str::FromStr>(value : &str) -> Result<Vec<T>,String>{
+    value.split(',').map(|item| item.parse().map_err(|_| format!("invalid value {item}"))).collect()
+}
+
+fn parse_options(args : &[String]) -> Result<Options,String>{
+
+    let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+    let mut options = Options {
+        sizes : vec![10, 20, 50],
+        engines : vec!["dense".to_string(), "sparse".to_string()],
+        steps : 200_000,
+        threads,
+        seed : 0,
+        output : None
+    };
+    let mut args = args.iter();
+
+    while let Some(flag) = args.next(){
+        let value = args.next().ok_or(format!("{flag} needs a value"))?;
+        match flag.as_str() {
+            "--sizes" => {
+                options.sizes = parse_list(value)?;
+                if options.sizes.iter().any(|size| *size < 4){
+                    return Err(format!("invalid sizes {value}, expected at least 4"));
+                }
+            },
+            "--engines" => {
+                options.engines = parse_list(value)?;
+                if let Some(engine) = options.engines.iter().find(|engine| !matches!(engine.as_str(), "dense" | "sparse")){
+                    return Err(format!("unknown engine {engine}"));
+                }
+            },
+            "--steps" => options.steps = value.parse().map_err(|_| format!("invalid steps {value}"))?,
+            "--threads" => options.threads = value.parse().ok().filter(|threads| *threads > 0).ok_or(format!("invalid threads {value}"))?,
+            "--seed" => options.seed = value.parse().map_err(|_| format!("invalid seed {value}"))?,
+            "--output" => options.output = Some(value.clone()),
+            _ => return Err(format!("unknown option {flag}"))
+        }
+    }
+    Ok(options)
+}
+
+/// plays uniformly random actions for the given number of steps,
+/// returns the number of episodes started
+fn play(env : &mut impl SnakeEnv, steps : usize, seed : u64) -> usize{
+    let mut rng = EngineRng::new(CURRENT_RNG_VERSION, seed);
+    let mut episodes = 1;
+    env.reset(seed);
+    for _ in 0..steps{
+        if env.step(rng.usize(0..env.num_actions())).0{
+            env.reset(seed + episodes as u64);
+            episodes += 1;
+        }
+    }
+    episodes
+}
+
+fn play_engine(engine : &str, size : usize, steps : usize, seed : u64) -> usize{
+    let starting_pos = (size/2, size/2);
+    match engine {
+        "sparse" => play(&mut SparseEngine::new((size,size), starting_pos, 1), steps, seed),
+        _ => play(&mut GameEngine::new((size,size), starting_pos, 1), steps, seed)
+    }
+}
+
+fn measure(engine : &str, size : usize, threads : usize, options : &Options) -> Run{
+    let start = Instant::now();
+    let episodes : usize = thread::scope(|scope| {
+        let handles : Vec<_> = (0..threads)
+            .map(|thread| {
+                //far apart seeds, the threads never play the same episodes
+                let seed = options.seed + (thread as u64) * (1 << 32);
+                scope.spawn(move || play_engine(engine, size, options.steps, seed))
+            })
+            .collect();
+        handles.into_iter().map(|handle| handle.join().unwrap()).sum()
+    });
+    Run {
+        engine : engine.to_string(),
+        size,
+        threads,
+        steps : threads*options.steps,
+        episodes,
+        seconds : start.elapsed().as_secs_f64()
+    }
+}
+
+fn main() -> ExitCode{
+
+    let args : Vec<String> = std::env::args().skip(1).collect();
+    let options = match parse_options(&args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("bench: {err}\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut runs = vec![];
+    for engine in &options.engines{
+        for size in &options.sizes{
+            runs.push(measure(engine, *size, 1, &options));
+            if options.threads > 1{
+                runs.push(measure(engine, *size, options.threads, &options));
+            }
+            let run = &runs[runs.len() - 1];
+            eprintln!("{engine} {size}x{size}: {:.0} steps/s with {} threads", run.steps as f64 / run.seconds, run.threads);
+        }
+    }
+
+    let results : Vec<String> = runs.iter().map(|run| format!("    {}", run.to_json())).collect();
+    let json = format!(
+        "{{\n  \"version\": \"{}\",\n  \"profile\": \"{}\",\n  \"steps_per_thread\": {},\n  \"seed\": {},\n  \"results\": [\n{}\n  ]\n}}\n",
+        env!("CARGO_PKG_VERSION"),
+        if cfg!(debug_assertions) { "debug" } else { "release" },
+        options.steps,
+        options.seed,
+        results.join(",\n")
+    );
+
+    match &options.output {
+        Some(path) => if let Err(err) = std::fs::write(path, json) {
+            eprintln!("bench: {path}: {err}");
+            return ExitCode::FAILURE;
+        },
+        None => print!("{json}")
+    }
+    ExitCode::SUCCESS
+}
@@ -10,7 +10,7 @@
 //! 
 //! For rust projects simply add the following line to the cargo.toml file 
 //! in the dependencies section
-//! ```
+//! ```toml
 //! snake-game-rust = {path = "../snake-game-rust"}
 //! ```
 //! this path is taken as an example and it should be changed depending on the 
@@ -42,6 +42,8 @@
 //! Then call step with either 0,1 or 2 as the action to forward 
 //! the game by one iteration.
 //! ```
+//! # use snake_game_rust::snake::*;
+//! # let mut game = GameEngine::new((10,10), (5,5), 1);
 //! game.step(1);
 //! ```
 //! The actions are mapped as following:
@@ -56,27 +58,124 @@
 //! Use the reset method to reset the game to the starting state
 //! based on the provided seed
 //! ```
+//! # use snake_game_rust::snake::*;
+//! # let mut game = GameEngine::new((10,10), (5,5), 1);
+//! # let seed = 12;
 //! game.reset(seed);
 //! ```
 //! 
 //! To view the game state call get_world
 //! ```
+//! # use snake_game_rust::snake::*;
+//! # let game = GameEngine::new((10,10), (5,5), 1);
 //! game.get_world();
 //! ```
 //! 
-//! This return the current world Matrix
+//! This return the current world Matrix, its cells hold the codes of
+//! [`snake::cell::Cell`], 0 for empty cells, 1 for the head, 2 for the body,
+//! 3 for the food and 4 for obstacles. The cells are also queried directly
+//! with [`snake::GameEngine::cell_at`]
 //! 
 //! Additional get method are present for food position and head position
+//! 
+//! Games can be recorded and replayed bit for bit with the [`snake::replay`] module,
+//! the conformance suite in `tests/conformance.rs` checks the engine against
+//! the golden replays in `tests/golden`. The engine uses its own versioned
+//! random generators from the [`snake::rng`] module, the version is stored
+//! with every run so that old seeds keep producing the same games
+//! 
+//! Hexagonal and 8-connected grids are available through 
+//! [`snake::GameEngine::with_topology`] and the [`snake::topology`] module
+//! 
+//! Observations for learning agents are encoded with [`snake::GameEngine::observe`],
+//! a partially observable version of the game is enabled with 
+//! [`snake::GameEngine::set_visibility`]
+//! 
+//! Optional world dynamics, like wandering food or obstacles spawning
+//! during the episode, are configured with [`snake::GameEngine::set_dynamics`]
+//! 
+//! With [`snake::GameEngine::set_food_placement`] the food follows a fixed
+//! schedule of the seed, so every player on the same seed gets the same food
+//! 
+//! Steps can be undone and redone with [`snake::GameEngine::undo`] after
+//! enabling a bounded history with [`snake::GameEngine::set_history`]
+//! 
+//! The collision rules for chasing the tail and moving straight back are set
+//! with [`snake::GameEngine::set_rules`], see [`snake::rules`]
+//! 
+//! Games are drawn without a display by the [`snake::render`] module, frames
+//! and whole episodes are saved as PNG and GIF files with the `render` feature
+//! 
+//! Named and versioned environments, like `"classic-10x10-v0"`, are built with
+//! [`snake::registry::make`], with the step limits of [`snake::limits`],
+//! wrapping walls and obstacle layouts
+//! 
+//! The consistency of the engine state is checked with [`snake::GameEngine::validate`],
+//! the `debug-invariants` feature checks it after every step
+//! 
+//! Random mid-game states with long snakes are built by the [`snake::scenario`]
+//! module
+//! 
+//! A 3D variant of the game on a voxel grid lives in [`snake::voxel`], both
+//! engines implement the [`snake::env::SnakeEnv`] trait
+//! 
+//! Boards too large for a dense world, up to 1000×1000 and beyond, are played
+//! with the [`snake::sparse`] engine at a constant cost per step, observing a
+//! window around the head
+//! 
+//! The speed of the engine is measured by the criterion benchmarks of
+//! `cargo bench` and by the `bench` binary, which writes the steps per second
+//! of a random policy as JSON to compare versions of the engine
+//! 
+//! Agents implement the [`snake::policy::Policy`] trait and play episodes with
+//! [`snake::GameEngine::play`]
 
 
 ///snake module
 pub mod snake{
     pub mod data_types;
     pub mod snake;
-
-    use fastrand::{self};
+    pub mod dynamics;
+    pub mod topology;
+    pub mod action_noise;
+    pub mod observation;
+    pub mod visibility;
+    pub mod zobrist;
+    pub mod symmetry;
+    pub mod errors;
+    pub mod replay;
+    pub mod rng;
+    pub mod food_schedule;
+    pub mod delta;
+    pub mod rules;
+    pub mod history;
+    pub mod cell;
+    pub mod render;
+    pub mod policy;
+    pub mod limits;
+    pub mod layout;
+    pub mod registry;
+    pub mod invariants;
+    pub mod scenario;
+    pub mod env;
+    pub mod voxel;
+    pub mod sparse;
+
+    use std::sync::Arc;
     use data_types::*;
     use snake::Snake;
+    use dynamics::Dynamics;
+    use topology::{Topology, SquareGrid};
+    use action_noise::ActionNoise;
+    use visibility::FogOfWar;
+    use zobrist::ZobristHash;
+    use errors::EngineError;
+    use rng::{EngineRng, CURRENT_RNG_VERSION};
+    use food_schedule::FoodPlacement;
+    use rules::Rules;
+    use limits::Limits;
+    use history::History;
+    use cell::Cell;
 
     
 
@@ -84,13 +183,33 @@ pub mod snake{
     pub struct GameEngine{
         pub game_world : Matrix,
         pub world_size : (usize,usize),
-        pub rng_generator : fastrand::Rng,
+        pub rng_generator : EngineRng,
+        pub rng_version : u32,
         pub seed : u64,
         pub snake : Snake,
         pub starting_direction : usize,
         pub food_pos : Vec2,
+        pub food_placement : FoodPlacement,
+        pub food_count : usize,
         pub starting_pos : (usize,usize),
-        pub free_space : Vec<Vec2>
+        pub starting_body : Option<Vec<Vec2>>,
+        pub free_space : Vec<Vec2>,
+        pub obstacles : Vec<Vec2>,
+        pub dynamics : Dynamics,
+        pub rules : Rules,
+        pub rules_version : u32,
+        pub limits : Limits,
+        pub steps : usize,
+        pub steps_since_food : usize,
+        pub obstacle_layout : Vec<Vec2>,
+        pub topology : Arc<dyn Topology>,
+        pub action_noise : ActionNoise,
+        pub last_action : usize,
+        pub fog : Option<FogOfWar>,
+        pub zobrist : ZobristHash,
+        pub delta : Vec<CellChange>,
+        pub history : History,
+        pub invariant_reports : Vec<invariants::InvariantReport>
         /*
                     1-up
             0-left       2-right
@@ -128,19 +247,50 @@ pub mod snake{
             starting_pos : (usize,usize),
             starting_direction : usize,
         ) -> GameEngine{
+            GameEngine::with_topology(world_size, starting_pos, starting_direction, Arc::new(SquareGrid))
+        }
+
+        /// same as [`GameEngine::new`], but the world grid uses the given
+        /// topology instead of the square grid with 4 neighbours
+        /// 
+        /// The direction and the actions accepted by `step` are then
+        /// interpreted by the topology, see the [`topology`] module.
+        /// 
+        /// # Panics
+        /// 
+        /// Will panic if one of the world dimensions is 0 or if the direction 
+        /// does not exist in the topology.
+        /// 
+        /// # Examples
+        /// 
+        /// ```
+        /// use std::sync::Arc;
+        /// use snake_game_rust::snake::*;
+        /// use snake_game_rust::snake::topology::HexGrid;
+        /// 
+        /// //snake facing right on a hexagonal grid
+        /// let mut ge = GameEngine::with_topology((10,10), (5,5), 3, Arc::new(HexGrid));
+        /// 
+        /// //turn 60° right, the head moves down right
+        /// ge.step(3);
+        /// assert!(*ge.get_snake_head() == data_types::Vec2{ x : 6, y : 6 });
+        /// ```
+        pub fn with_topology(
+            world_size : (usize,usize), 
+            starting_pos : (usize,usize),
+            starting_direction : usize,
+            topology : Arc<dyn Topology>
+        ) -> GameEngine{
 
             if world_size.0 == 0 || world_size.1 == 0{
                 panic!("World size cannot be zero!")
             };
 
-            if starting_direction > 3 {
-                panic!("Direction has to be either
-                    1 - up
-                    2 - down
-                    0 - left 
-                    3 - right
-                    and not {starting_direction}
-                ")
+            if starting_direction >= topology.num_directions() {
+                panic!("Direction has to be smaller than {} on a {} grid and not {starting_direction}",
+                    topology.num_directions(),
+                    topology.name()
+                )
             }
 
             let x  = starting_pos.0 as i32;
@@ -148,7 +298,133 @@ pub mod snake{
 
             let snake_head= Vec2{x : x, y : y};
 
-            let snake  = Snake::new(snake_head, starting_direction);
+            let snake  = Snake::new(snake_head, starting_direction, topology.as_ref());
+
+            let mut ge = GameEngine::assemble(world_size, snake, starting_pos, topology, EngineRng::from_entropy(CURRENT_RNG_VERSION), 0);
+
+            ge.spawn_food();
+            ge.rehash();
+            
+            return ge;
+
+        }
+
+        /// returns a new instance of gameEngine struct in an explicitly given state,
+        /// used for tests, debugging and starting from hard positions
+        /// 
+        /// * `body` - the cells of the snake ordered from head to tail
+        /// * `direction` - the direction the head is facing
+        /// * `food_pos` - the position of the food, if `None` it is placed at random
+        /// * `seed` - the seed of the random generator, of the current rng version
+        /// 
+        /// The state is validated, the body has to be contiguous and inside the world,
+        /// and the food cannot lie on the body. Calling reset afterwards returns to
+        /// this body, with the food placed according to the seed. Another rng
+        /// version is selected with [`GameEngine::set_rng_version`], which seeds
+        /// the generator again with the same seed.
+        /// 
+        /// # Examples
+        /// 
+        /// ```
+        /// use snake_game_rust::snake::*;
+        /// use snake_game_rust::snake::data_types::Vec2;
+        /// use snake_game_rust::snake::errors::EngineError;
+        /// 
+        /// //a U-shaped snake next to the top wall, facing left
+        /// let body = vec![
+        ///     Vec2{ x : 1, y : 0 },
+        ///     Vec2{ x : 1, y : 1 },
+        ///     Vec2{ x : 0, y : 1 },
+        ///     Vec2{ x : 0, y : 2 },
+        ///     Vec2{ x : 1, y : 2 },
+        ///     Vec2{ x : 2, y : 2 },
+        /// ];
+        /// let mut game = GameEngine::from_parts((5,5), body, 0, Some(Vec2{ x : 4, y : 4 }), 7).unwrap();
+        /// 
+        /// let (done, _, msg) = game.step(1);
+        /// assert!(done);
+        /// assert_eq!(msg, "wall");
+        /// 
+        /// let broken = vec![Vec2{ x : 1, y : 0 }, Vec2{ x : 3, y : 0 }];
+        /// assert_eq!(GameEngine::from_parts((5,5), broken, 0, None, 7).err(), Some(EngineError::NotContiguous(1)));
+        /// ```
+        pub fn from_parts(
+            world_size : (usize,usize),
+            body : Vec<Vec2>,
+            direction : usize,
+            food_pos : Option<Vec2>,
+            seed : u64
+        ) -> Result<GameEngine,EngineError>{
+            GameEngine::from_parts_with_topology(world_size, body, direction, food_pos, seed, Arc::new(SquareGrid))
+        }
+
+        /// same as [`GameEngine::from_parts`] for a world with the given topology
+        pub fn from_parts_with_topology(
+            world_size : (usize,usize),
+            body : Vec<Vec2>,
+            direction : usize,
+            food_pos : Option<Vec2>,
+            seed : u64,
+            topology : Arc<dyn Topology>
+        ) -> Result<GameEngine,EngineError>{
+
+            if world_size.0 == 0 || world_size.1 == 0{
+                return Err(EngineError::ZeroWorldSize);
+            }
+            if direction >= topology.num_directions(){
+                return Err(EngineError::InvalidDirection(direction));
+            }
+            if body.is_empty(){
+                return Err(EngineError::EmptyBody);
+            }
+
+            let in_bounds = |cell : &Vec2| cell.x >= 0 && cell.y >= 0
+                && (cell.x as usize) < world_size.0 && (cell.y as usize) < world_size.1;
+
+            for (i, cell) in body.iter().enumerate(){
+                if !in_bounds(cell){
+                    return Err(EngineError::OutOfBounds(*cell));
+                }
+                if body[..i].contains(cell){
+                    return Err(EngineError::Overlap(*cell));
+                }
+                if i > 0 && !(0..topology.num_directions()).any(|d| topology.advance(*cell, d) == body[i-1]){
+                    return Err(EngineError::NotContiguous(i));
+                }
+            }
+            if body.len() > 1 && topology.advance(body[0], direction) == body[1]{
+                return Err(EngineError::FacingNeck);
+            }
+            if let Some(food_pos) = food_pos{
+                if !in_bounds(&food_pos){
+                    return Err(EngineError::OutOfBounds(food_pos));
+                }
+                if body.contains(&food_pos){
+                    return Err(EngineError::Overlap(food_pos));
+                }
+            }
+
+            let starting_pos = (body[0].x as usize, body[0].y as usize);
+            let snake = Snake::from_body(body.clone(), direction);
+
+            let mut ge = GameEngine::assemble(world_size, snake, starting_pos, topology, EngineRng::new(CURRENT_RNG_VERSION, seed), seed);
+            ge.starting_body = Some(body);
+
+            match food_pos {
+                Some(food_pos) => {
+                    ge.food_pos = food_pos;
+                    ge.game_world[(food_pos.x, food_pos.y)] = Cell::Food.code();
+                },
+                None => ge.spawn_food()
+            }
+            ge.rehash();
+            ge.debug_validate("from_parts");
+
+            Ok(ge)
+        }
+
+        /// the cells of the world not taken by the snake body
+        fn free_cells(world_size : (usize,usize), snake : &Snake) -> Vec<Vec2>{
 
             let mut free_space : Vec<Vec2> = Vec::with_capacity(world_size.0*world_size.1);
 
@@ -160,24 +436,56 @@ pub mod snake{
                     }
                 }
             }
+            free_space
+        }
+
+        /// builds the engine around a snake and draws it, without placing the food
+        fn assemble(
+            world_size : (usize,usize),
+            snake : Snake,
+            starting_pos : (usize,usize),
+            topology : Arc<dyn Topology>,
+            rng_generator : EngineRng,
+            seed : u64
+        ) -> GameEngine{
+
+            let free_space = GameEngine::free_cells(world_size, &snake);
+            let zobrist = ZobristHash::new(world_size, topology.num_directions());
 
             let mut ge = GameEngine { 
                 game_world: Matrix::zeros(world_size), 
-                world_size : world_size,
-                rng_generator : fastrand::Rng::new(),
-                seed : 0,
-                snake: snake,
-                starting_direction : starting_direction,
+                world_size,
+                rng_version : rng_generator.version(),
+                rng_generator,
+                seed,
+                starting_direction : snake.direction,
+                snake,
                 food_pos : Vec2 { x: 0, y: 0 },
-                starting_pos : starting_pos,
-                free_space : free_space
+                food_placement : FoodPlacement::default(),
+                food_count : 0,
+                starting_pos,
+                starting_body : None,
+                free_space,
+                obstacles : vec![],
+                dynamics : Dynamics::default(),
+                rules : Rules::default(),
+                rules_version : rules::CURRENT_RULES_VERSION,
+                limits : Limits::default(),
+                steps_since_food : 0,
+                obstacle_layout : vec![],
+                steps : 0,
+                last_action : topology.forward_action(),
+                topology,
+                action_noise : ActionNoise::default(),
+                fog : None,
+                zobrist,
+                delta : vec![],
+                history : History::default(),
+                invariant_reports : vec![]
             };
 
             ge.draw_world();
-            ge.spawn_food();
-            
-            return ge;
-
+            ge
         }
 
         /// Forwards the game by one iteration and returns infomation about it
@@ -187,18 +495,23 @@ pub mod snake{
         /// * 1 - go forward
         /// * 2 - turn right
         /// 
+        /// Engines created with [`GameEngine::with_topology`] accept the 
+        /// actions of their topology instead.
+        /// 
         /// Returns a tuple `(done, food_eaten, msg)`
         /// * `done` - a boolean, true if the game has ended in some way
         /// * `food_eaten` - a boolean, true if food has been eaten this iteration
         /// * `msg` - a string, a short message describing the iteration, mostly 
         /// used for learning enviroment 
         /// 
-        /// For now `msg` can take on 4 values:
+        /// For now `msg` can take on 5 values:
         /// 
         /// * `"alive"` - if snake is alive, 
         /// * `"body"` - if the snake has collided with its own body
         /// * `"wall"` - if the snake has collided with on of the walls
-        /// * `"victory"` - if the snake body is taking up the entire world grid
+        /// * `"obstacle"` - if the snake has collided with an obstacle, see [`dynamics`]
+        /// * `"victory"` - if the snake body is taking up the entire free part of the world grid,
+        ///   the winning step reports no food eaten
         /// 
         /// # Panics
         /// 
@@ -211,7 +524,7 @@ pub mod snake{
         /// 
         /// let mut game = GameEngine::new((10,10), (5,5), 1);
         /// 
-        /// (done, food_eaten, msg) = game.step(1);
+        /// let (done, food_eaten, msg) = game.step(1);
         /// 
         /// if done {
         ///     //some code to run after the game ends
@@ -219,8 +532,51 @@ pub mod snake{
         /// ``` 
         pub fn step(&mut self, action : usize) -> (bool,bool,String){
 
-            
-            self.move_snake(action);
+            let outcome = self.step_outcome(action);
+            (outcome.done, outcome.food_eaten, outcome.msg)
+        }
+
+        /// Same as [`GameEngine::step`], but returns the full [`StepOutcome`],
+        /// which also holds the action that was actually executed after 
+        /// applying the action noise, see [`action_noise`]
+        /// 
+        /// # Panics
+        /// 
+        /// Will panic if supplied with an action that is not mapped
+        /// 
+        /// # Examples
+        /// 
+        /// ```
+        /// use snake_game_rust::snake::*;
+        /// 
+        /// let mut game = GameEngine::new((10,10), (5,5), 1);
+        /// 
+        /// let outcome = game.step_outcome(2);
+        /// 
+        /// assert!(!outcome.done);
+        /// assert_eq!(outcome.msg, "alive");
+        /// assert_eq!(outcome.executed_action, 2);
+        /// ``` 
+        pub fn step_outcome(&mut self, action : usize) -> StepOutcome{
+
+            if action >= self.topology.num_actions() {
+                panic!("Expected action to be smaller than {}, got {action} instead", self.topology.num_actions());
+            }
+
+            self.push_history();
+            let action = self.noisy_action(action);
+            self.last_action = action;
+
+            let direction = self.topology.turn(self.snake.direction, action);
+            self.advance(direction)
+        }
+
+        /// moves the snake in the direction and updates the world
+        fn advance(&mut self, direction : usize) -> StepOutcome{
+
+            self.delta.clear();
+
+            self.move_snake(direction);
             let (done,msg) = self.game_over();
             
             let food_eaten = if !done{
@@ -229,35 +585,40 @@ pub mod snake{
                 false
             };
 
-            if self.game_world.matrix.iter().map(|x| x.iter().any(|x| *x==3)).any(|x| x) == false {
+            if !done {
+                self.steps += 1;
+                self.steps_since_food = if food_eaten { 0 } else { self.steps_since_food + 1 };
+                self.apply_dynamics();
+                self.update_fog();
+            }
+            let (done, msg) = match self.reached_limit() {
+                Some(limit) if !done => (true, limit.to_string()),
+                _ => (done, msg)
+            };
+
+            //the legacy rules can spawn the food under the head, which then
+            //leaves the world without food, see the invariants
+            if !self.free_space.is_empty() && !self.game_world.matrix.iter().any(|x| x.contains(&Cell::Food.code())) {
                 self.spawn_food();
-                println!("This should not happen");
             }
-            (done, food_eaten, msg)
+            //the state after a collision or a victory is left as it was
+            if !done || msg == "timeout" || msg == "starved"{
+                self.debug_validate("a step");
+            }
+            StepOutcome { done, food_eaten, msg, executed_action : self.last_action, delta : self.delta.clone() }
 
         }
 
-        fn move_snake(&mut self, action : usize){
+        fn move_snake(&mut self, direction : usize){
 
-            if action > 2 {
-                panic!("Expected action to be 0,1 or 2, got {action} instead");
-            }
+            let old_head = self.snake.snake_head;
+            let old_direction = self.snake.direction;
 
-            self.snake.direction = (self.snake.direction + (action + 3)%4)%4;
+            self.snake.direction = direction;
 
-            let delta_x = match self.snake.direction {
-                1 => -1,
-                3 => 1,
-                _ => 0
-            };
-            let delta_y = match self.snake.direction {
-                0 => -1,
-                2 => 1,
-                _ => 0
-                
-            };
+            self.snake.snake_head = self.next_cell(self.snake.snake_head, self.snake.direction);
 
-            self.snake.snake_head += Vec2{x : delta_x, y : delta_y};
+            self.zobrist.move_head(old_head, self.snake.snake_head, old_direction, self.snake.direction);
 
             self.snake.snake_body.insert(0,self.snake.snake_head.clone());
 
@@ -265,28 +626,31 @@ pub mod snake{
         }
 
         fn snake_updates(&mut self) -> bool{
-            
-            let snake_head_pos_x = self.snake.snake_head.x as usize;
-            let snake_head_pos_y = self.snake.snake_head.y as usize;
 
             self.free_space.retain(|cell| *cell != self.snake.snake_head);
             
 
-            let snake_neck = &self.snake.snake_body[1];
-            self.game_world[(snake_neck.x, snake_neck.y)] = 2;
+            let snake_neck = self.snake.snake_body[1];
+            self.set_cell(snake_neck, Cell::Body);
             if self.snake.snake_head  == self.food_pos{
             
                 self.spawn_food();
-                self.game_world[(snake_head_pos_x,snake_head_pos_y)] = 1;
+                self.set_cell(self.snake.snake_head, Cell::Head);
 
                 return true;
             }   
             
-            let snake_tail = self.snake.snake_body.last().unwrap();
+            let snake_tail = *self.snake.snake_body.last().unwrap();
+            let snake_tail_link = self.link_direction(snake_tail, self.snake.snake_body[self.snake.snake_body.len()-2]);
+            self.zobrist.remove_tail(snake_tail, snake_tail_link);
             
-            self.game_world[(snake_tail.x,snake_tail.y)] = 0;
-            self.free_space.push(snake_tail.clone());           
-            self.game_world[(snake_head_pos_x,snake_head_pos_y)] = 1;
+            self.set_cell(snake_tail, Cell::Empty);
+            //the head chasing the tail moves into the cell the tail leaves, the
+            //legacy rules list it as free anyway to replay old games bit for bit
+            if snake_tail != self.snake.snake_head || self.rules_version == rules::LEGACY_RULES_VERSION{
+                self.free_space.push(snake_tail);
+            }
+            self.set_cell(self.snake.snake_head, Cell::Head);
             self.snake.snake_body.pop();
 
             return false;
@@ -299,11 +663,22 @@ pub mod snake{
             // time complexity changes to O(1) memory to O(n)
             //only need tor update a maximum of twice per step
             let upper_bound = self.free_space.len();
-            let new_food_index = self.rng_generator.usize(0..upper_bound);
-            let new_food_pos = self.free_space[new_food_index];
+            if upper_bound == 0 {
+                self.zobrist.set_food(None);
+                return;
+            }
+            let new_food_pos = match self.food_placement {
+                FoodPlacement::Random => {
+                    let new_food_index = self.rng_generator.usize(0..upper_bound);
+                    self.free_space[new_food_index]
+                },
+                FoodPlacement::Scheduled => self.scheduled_food_pos()
+            };
+            self.food_count += 1;
 
             self.food_pos = new_food_pos;
-            self.game_world[(self.food_pos.x,self.food_pos.y)] = 3;
+            self.set_cell(self.food_pos, Cell::Food);
+            self.zobrist.set_food(Some(self.food_pos));
         }
 
 
@@ -311,13 +686,12 @@ pub mod snake{
 
             let on_self_collision = "body".to_string();
             let on_wall_collision = "wall".to_string();
+            let on_obstacle_collision = "obstacle".to_string();
             let on_game_completed = "victory".to_string();
             let stil_playing = "alive".to_string();
 
-            for i in 1..(self.snake.snake_body.len()-1){
-                if self.snake.snake_body[i] == self.snake.snake_head{
-                    return (true,on_self_collision);
-                }
+            if self.hits_body(){
+                return (true,on_self_collision);
             }
             if self.snake.snake_head.x < 0 || self.snake.snake_head.x >= self.world_size.0.try_into().unwrap() {
                 return (true,on_wall_collision);
@@ -325,7 +699,14 @@ pub mod snake{
             if self.snake.snake_head.y < 0 || self.snake.snake_head.y >= self.world_size.1.try_into().unwrap() {
                 return (true,on_wall_collision);
             }
-            if self.snake.snake_body.len() == self.world_size.0*self.world_size.1{
+            if self.obstacles.contains(&self.snake.snake_head) {
+                return (true,on_obstacle_collision);
+            }
+            //the head is inserted but the tail not yet moved, a full world is
+            //only won by eating, chasing the tail keeps the length. The legacy
+            //rules also count the tail chase as a victory
+            let eats = self.snake.snake_head == self.food_pos || self.rules_version == rules::LEGACY_RULES_VERSION;
+            if eats && self.snake.snake_body.len() == self.world_size.0*self.world_size.1 - self.obstacles.len(){
                 return (true,on_game_completed);
             }
             return (false,stil_playing);
@@ -337,12 +718,12 @@ pub mod snake{
 
             for i in 0..self.snake.snake_body.len(){
 
-                let color = match i {
-                    0 => 1,
-                    _ => 2
+                let cell = match i {
+                    0 => Cell::Head,
+                    _ => Cell::Body
                 };
                 let snake_body_part = self.snake.snake_body[i];
-                self.game_world[(snake_body_part.x, snake_body_part.y)] = color;
+                self.game_world[(snake_body_part.x, snake_body_part.y)] = cell.code();
             }
         }
 
@@ -368,40 +749,54 @@ pub mod snake{
         pub fn reset(&mut self, seed : u64){
 
             self.seed = seed;
+            self.steps = 0;
+            self.steps_since_food = 0;
+            self.food_count = 0;
+            self.obstacles.clear();
+            self.last_action = self.topology.forward_action();
 
-            self.rng_generator.seed(seed);
-            
-            let x  = self.starting_pos.0 as i32;
-            let y  = self.starting_pos.1 as i32;
+            self.rng_generator = EngineRng::new(self.rng_version, seed);
             
-            let snake_head = Vec2{x : x, y : y};
-
-            self.snake = Snake::new(snake_head, self.starting_direction);
-            let mut free_space : Vec<Vec2> = Vec::with_capacity(self.world_size.0*self.world_size.1);
+            self.snake = self.starting_snake();
+            self.free_space = GameEngine::free_cells(self.world_size, &self.snake);
+            self.game_world = Matrix::zeros(self.world_size);
+            self.draw_world();
+            self.place_obstacle_layout();
+            self.spawn_food();
+            self.rehash();
+            self.reset_fog();
+            self.delta.clear();
+            self.clear_history();
+            self.invariant_reports.clear();
+            self.debug_validate("a reset");
+        }
 
-            for x in 0..self.world_size.0{
-                for y in 0..self.world_size.1{
-                    let pair_of_coordinates = Vec2{ x : x.try_into().unwrap(), y : y.try_into().unwrap() };
-                    if !&self.snake.snake_body.contains(&pair_of_coordinates){
-                        free_space.push(pair_of_coordinates);
-                    }
-                }
+        /// the snake every episode starts with
+        fn starting_snake(&self) -> Snake{
+            let snake_head = Vec2{ x : self.starting_pos.0 as i32, y : self.starting_pos.1 as i32 };
+            match &self.starting_body {
+                Some(body) => Snake::from_body(body.clone(), self.starting_direction),
+                None => Snake::new(snake_head, self.starting_direction, self.topology.as_ref())
             }
-            self.free_space = free_space;
-            self.game_world = Matrix::zeros(self.world_size);
-            let mut free_space : Vec<Vec2> = Vec::with_capacity(self.world_size.0*self.world_size.1);
+        }
 
-            for x in 0..self.world_size.0{
-                for y in 0..self.world_size.1{
-                    let pair_of_coordinates = Vec2{ x : x.try_into().unwrap(), y : y.try_into().unwrap() };
-                    if !&self.snake.snake_body.contains(&pair_of_coordinates){
-                        free_space.push(pair_of_coordinates);
+        ///returns the neighbours of a cell inside the world grid,
+        ///according to the topology of the world, across the walls
+        ///when they wrap
+        pub fn neighbours(&self, pos : Vec2) -> Vec<Vec2>{
+            match self.rules.walls {
+                rules::Walls::Solid => self.topology.neighbours(pos, self.world_size),
+                rules::Walls::Wrap => {
+                    let mut neighbours : Vec<Vec2> = vec![];
+                    for direction in 0..self.topology.num_directions(){
+                        let cell = self.next_cell(pos, direction);
+                        if cell != pos && !neighbours.contains(&cell){
+                            neighbours.push(cell);
+                        }
                     }
+                    neighbours
                 }
             }
-            self.draw_world();
-            self.spawn_food();
-            
         }
 
         ///generic getter function for the game_world matrix
@@ -420,9 +815,7 @@ pub mod snake{
         }
         
         pub fn get_score(&self) -> usize{
-            let sum : i32 = self.game_world.matrix.iter().map(|x| x.iter().sum::<i32>()).sum();
-            let score = (sum - 3 + 1)/2 - 3;
-            score as usize
+            self.snake.snake_body.len().saturating_sub(3)
         }
 
         pub fn get_direction(&self) -> usize{
@@ -433,12 +826,46 @@ pub mod snake{
             self.seed
         }
 
+        pub fn get_rng_version(&self) -> u32{
+            self.rng_version
+        }
+
+        /// selects the version of the random generator, see [`rng`],
+        /// and seeds it again with the current seed
+        /// 
+        /// Games recorded with an older version are replayed bit for bit by
+        /// setting that version before calling reset with the recorded seed.
+        /// 
+        /// # Panics
+        /// 
+        /// Will panic if the version is not one of [`rng::RNG_VERSIONS`]
+        /// 
+        /// # Examples
+        /// 
+        /// ```
+        /// use snake_game_rust::snake::*;
+        /// use snake_game_rust::snake::rng::LEGACY_RNG_VERSION;
+        /// 
+        /// let mut game = GameEngine::new((10,10), (5,5), 1);
+        /// game.set_rng_version(LEGACY_RNG_VERSION);
+        /// game.reset(12);
+        /// assert_eq!(game.get_rng_version(), LEGACY_RNG_VERSION);
+        /// assert!(game.get_info_string().ends_with(",1"));
+        /// ```
+        pub fn set_rng_version(&mut self, version : u32){
+            self.rng_generator = EngineRng::new(version, self.seed);
+            self.rng_version = version;
+        }
+
+        /// the direction, seed, score and rng version, appended to the
+        /// flattened world and the action when a step is stored
         pub fn get_info_string(&self) -> String{
             let direction = self.get_direction();
             let seed = self.get_seed();
             let score = self.get_score();
+            let rng_version = self.get_rng_version();
 
-            format!(",{direction},{seed},{score}")
+            format!(",{direction},{seed},{score},{rng_version}")
         }
 
         pub fn get_flattened_world(&self) -> String
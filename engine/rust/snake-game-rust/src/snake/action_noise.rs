@@ -0,0 +1,63 @@
+use crate::snake::GameEngine;
+
+/// ALE style stochasticity applied to the actions given to `step`
+///
+/// * `sticky_prob` - probability that the previously executed action is
+///   repeated instead of the given one
+/// * `epsilon` - probability that a uniformly random action is executed
+///   instead of the given one
+///
+/// The sticky check happens first. Both draws use the engine's
+/// `rng_generator`, and no numbers are drawn while both probabilities are 0,
+/// so the default engine produces the same games as before.
+///
+/// # Examples
+///
+/// ```
+/// use snake_game_rust::snake::*;
+/// use snake_game_rust::snake::action_noise::ActionNoise;
+///
+/// let mut game = GameEngine::new((10,10), (5,5), 1);
+/// game.set_action_noise(ActionNoise { sticky_prob : 1.0, epsilon : 0.0 });
+/// game.reset(3);
+///
+/// // the forward action from the reset is repeated
+/// let outcome = game.step_outcome(0);
+/// assert_eq!(outcome.executed_action, 1);
+/// ```
+#[derive(Clone,Copy,Default,PartialEq,Debug)]
+pub struct ActionNoise{
+    pub sticky_prob : f64,
+    pub epsilon : f64
+}
+
+impl GameEngine {
+
+    /// sets the action noise used by the engine
+    ///
+    /// # Panics
+    ///
+    /// Will panic if either of the probabilities is outside of `[0, 1]`
+    pub fn set_action_noise(&mut self, action_noise : ActionNoise){
+
+        for prob in [action_noise.sticky_prob, action_noise.epsilon]{
+            if !(0.0..=1.0).contains(&prob){
+                panic!("Action noise probabilities have to be between 0 and 1, got {prob}");
+            }
+        }
+        self.action_noise = action_noise;
+    }
+
+    /// returns the action that is actually executed instead of `action`
+    pub(crate) fn noisy_action(&mut self, action : usize) -> usize{
+
+        if self.action_noise.sticky_prob > 0.0 && self.rng_generator.f64() < self.action_noise.sticky_prob{
+            return self.last_action;
+        }
+        if self.action_noise.epsilon > 0.0 && self.rng_generator.f64() < self.action_noise.epsilon{
+            return self.rng_generator.usize(0..self.topology.num_actions());
+        }
+        action
+    }
+
+}
@@ -0,0 +1,136 @@
+use crate::snake::GameEngine;
+use crate::snake::data_types::*;
+
+/// the content of a cell of the world
+///
+/// The world matrix stores the cells by their numeric code, which is stable
+/// and shared with the python wrapper, the recorded games and the UI:
+///
+/// * 0 - `Empty`
+/// * 1 - `Head`
+/// * 2 - `Body`
+/// * 3 - `Food`
+/// * 4 - `Obstacle`
+///
+/// New kinds of cells get the next free code, existing codes never change.
+///
+/// # Examples
+///
+/// ```
+/// use snake_game_rust::snake::cell::Cell;
+///
+/// assert_eq!(Cell::Food.code(), 3);
+/// assert_eq!(Cell::from_code(2), Some(Cell::Body));
+/// assert_eq!(Cell::from_code(-1), None);
+/// ```
+#[derive(Clone,Copy,Default,PartialEq,Eq,Hash,Debug)]
+pub enum Cell{
+    #[default]
+    Empty = 0,
+    Head = 1,
+    Body = 2,
+    Food = 3,
+    Obstacle = 4
+}
+
+impl Cell {
+
+    /// every cell, ordered by code
+    pub const ALL : [Cell;5] = [Cell::Empty, Cell::Head, Cell::Body, Cell::Food, Cell::Obstacle];
+
+    /// the numeric code of the cell stored in the world matrix
+    pub fn code(&self) -> i32{
+        *self as i32
+    }
+
+    /// the cell of a numeric code, `None` for unknown codes like
+    /// the hidden cells of a partially observable world
+    pub fn from_code(code : i32) -> Option<Cell>{
+        Cell::ALL.get(usize::try_from(code).ok()?).copied()
+    }
+
+    pub fn name(&self) -> &'static str{
+        match self {
+            Cell::Empty => "empty",
+            Cell::Head => "head",
+            Cell::Body => "body",
+            Cell::Food => "food",
+            Cell::Obstacle => "obstacle"
+        }
+    }
+
+    pub fn from_name(name : &str) -> Option<Cell>{
+        Cell::ALL.into_iter().find(|cell| cell.name() == name)
+    }
+
+    /// true for the cells the snake dies on, the snake and the obstacles
+    pub fn is_blocking(&self) -> bool{
+        matches!(self, Cell::Head | Cell::Body | Cell::Obstacle)
+    }
+}
+
+impl From<Cell> for i32 {
+    fn from(cell : Cell) -> i32{
+        cell.code()
+    }
+}
+
+impl GameEngine {
+
+    /// returns the cell at a position, `None` outside of the world
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snake_game_rust::snake::*;
+    /// use snake_game_rust::snake::cell::Cell;
+    /// use snake_game_rust::snake::data_types::Vec2;
+    ///
+    /// let game = GameEngine::new((10,10), (5,5), 1);
+    /// assert_eq!(game.cell_at(Vec2{ x : 5, y : 5 }), Some(Cell::Head));
+    /// assert_eq!(game.cell_at(Vec2{ x : 6, y : 5 }), Some(Cell::Body));
+    /// assert_eq!(game.cell_at(*game.get_food_pos()), Some(Cell::Food));
+    /// assert_eq!(game.cell_at(Vec2{ x : -1, y : 5 }), None);
+    /// ```
+    pub fn cell_at(&self, pos : Vec2) -> Option<Cell>{
+        if !self.in_world(pos){
+            return None;
+        }
+        Cell::from_code(self.game_world[(pos.x, pos.y)])
+    }
+
+    /// returns true if the snake would die entering the cell,
+    /// a cell outside of the world, of the snake or an obstacle
+    ///
+    /// The tail counts as blocked, whether the head may follow it
+    /// depends on the [`GameEngine::set_rules`] and the food.
+    pub fn is_blocked(&self, pos : Vec2) -> bool{
+        self.cell_at(pos).is_none_or(|cell| cell.is_blocking())
+    }
+
+    /// returns the index of the snake part at a position, 0 for the head
+    /// and the length minus one for the tail, `None` if the snake is not there
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snake_game_rust::snake::*;
+    /// use snake_game_rust::snake::data_types::Vec2;
+    ///
+    /// let game = GameEngine::new((10,10), (5,5), 1);
+    /// assert_eq!(game.body_index_at(Vec2{ x : 5, y : 5 }), Some(0));
+    /// assert_eq!(game.body_index_at(Vec2{ x : 7, y : 5 }), Some(2));
+    /// assert_eq!(game.body_index_at(Vec2{ x : 0, y : 0 }), None);
+    /// ```
+    pub fn body_index_at(&self, pos : Vec2) -> Option<usize>{
+        match self.cell_at(pos) {
+            Some(Cell::Head | Cell::Body) => self.snake.snake_body.iter().position(|part| *part == pos),
+            _ => None
+        }
+    }
+
+    pub(crate) fn in_world(&self, pos : Vec2) -> bool{
+        pos.x >= 0 && pos.y >= 0 && (pos.x as usize) < self.world_size.0 && (pos.y as usize) < self.world_size.1
+    }
+
+}
@@ -1,4 +1,5 @@
-use std::{ops::{Index, IndexMut, Add, AddAssign}, fmt::Display};
+use std::{ops::{Index, IndexMut, Add, AddAssign, Neg}, fmt::Display};
+use crate::snake::rng::LEGACY_RNG_VERSION;
 
 /// generic wrapper for a matrix type with implementations
 /// for printing and python style indexing
@@ -11,7 +12,7 @@ use std::{ops::{Index, IndexMut, Add, AddAssign}, fmt::Display};
 /// a[(3,4)] = 5;
 /// 
 /// ```
-#[derive(Clone)]
+#[derive(Clone,PartialEq,Eq,Debug)]
 pub struct Matrix{
     pub matrix : Vec<Vec<i32>>,
     pub size : (usize,usize)
@@ -35,7 +36,7 @@ impl Matrix {
     pub fn zeros(size : (usize,usize)) -> Matrix{
 
         Matrix {
-            matrix : vec![vec![0;size.0];size.1],
+            matrix : vec![vec![0;size.1];size.0],
             size : size
         }
 
@@ -111,7 +112,7 @@ impl IndexMut<(usize,usize)> for Matrix{
 
 /// generic 2D vector struct with implemenatations
 /// for equality and addition and a method for multiplying by a number
-#[derive(Clone,Copy,PartialEq,Eq,Hash)]
+#[derive(Clone,Copy,PartialEq,Eq,Hash,Debug)]
 pub struct Vec2{
     pub x : i32,
     pub y : i32
@@ -154,3 +155,146 @@ impl Vec2{
     }
 
 }
+
+/// a 3D vector for the voxel variant of the game, see [`crate::snake::voxel`]
+#[derive(Clone,Copy,PartialEq,Eq,Hash,Debug)]
+pub struct Vec3{
+    pub x : i32,
+    pub y : i32,
+    pub z : i32
+}
+
+impl Add for Vec3{
+    type Output = Vec3;
+    fn add(self, rhs: Self) -> Self::Output {
+        Vec3{x : self.x + rhs.x, y : self.y + rhs.y, z : self.z + rhs.z}
+    }
+}
+
+impl AddAssign for Vec3{
+    fn add_assign(&mut self, rhs: Self) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+        self.z += rhs.z;
+    }
+}
+
+impl Neg for Vec3{
+    type Output = Vec3;
+    fn neg(self) -> Self::Output {
+        Vec3{x : -self.x, y : -self.y, z : -self.z}
+    }
+}
+
+impl Vec3{
+
+    /// multiplies all components by a number
+    pub fn mul_by_i32(self, n : i32) -> Vec3{
+        Vec3 { x : self.x * n, y : self.y * n, z : self.z * n }
+    }
+
+    /// the cross product, for unit vectors along the axes it is the
+    /// third axis of a right handed frame
+    ///
+    /// # Examples
+    /// ```
+    /// use snake_game_rust::snake::data_types::Vec3;
+    ///
+    /// let x = Vec3 { x : 1, y : 0, z : 0 };
+    /// let y = Vec3 { x : 0, y : 1, z : 0 };
+    ///
+    /// assert_eq!(x.cross(y), Vec3 { x : 0, y : 0, z : 1 });
+    /// ```
+    pub fn cross(self, rhs : Vec3) -> Vec3{
+        Vec3 {
+            x : self.y * rhs.z - self.z * rhs.y,
+            y : self.z * rhs.x - self.x * rhs.z,
+            z : self.x * rhs.y - self.y * rhs.x
+        }
+    }
+
+    pub fn dot(self, rhs : Vec3) -> i32{
+        self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
+    }
+}
+
+/// full information about a single step of the game,
+/// returned by `GameEngine::step_outcome`
+/// 
+/// * `done` - true if the game has ended in some way
+/// * `food_eaten` - true if food has been eaten this iteration
+/// * `msg` - a short message describing the iteration, same as in `GameEngine::step`
+/// * `executed_action` - the action the engine executed, which differs from the
+///   given action when action noise is enabled
+/// * `delta` - the cells of the world that changed during the step
+#[derive(Clone,PartialEq,Eq,Debug)]
+pub struct StepOutcome{
+    pub done : bool,
+    pub food_eaten : bool,
+    pub msg : String,
+    pub executed_action : usize,
+    pub delta : Vec<CellChange>
+}
+
+/// a cell of the world that changed during a step, from `old` to `new`
+#[derive(Clone,Copy,PartialEq,Eq,Debug)]
+pub struct CellChange{
+    pub pos : Vec2,
+    pub old : i32,
+    pub new : i32
+}
+
+/// a single recorded step of a game, in the format the UI sends to the
+/// `DataEngine`, the world after the step followed by the action,
+/// the direction, the seed, the score and the rng version
+#[derive(Clone,PartialEq,Eq,Debug)]
+pub struct RecordedStep{
+    pub world : Matrix,
+    pub action : usize,
+    pub direction : usize,
+    pub seed : u64,
+    pub score : usize,
+    pub rng_version : u32
+}
+
+impl RecordedStep {
+
+    /// parses a row built from `get_flattened_world`, the action and
+    /// `get_info_string`, returns `None` if the row does not match the world size
+    ///
+    /// Rows stored before the rng version was recorded end after the score,
+    /// they were played with [`LEGACY_RNG_VERSION`].
+    pub fn from_row(row : &str, world_size : (usize,usize)) -> Option<RecordedStep>{
+
+        let values : Vec<&str> = row.split(',').map(|value| value.trim()).collect();
+        let cells = world_size.0*world_size.1;
+        let rng_version = match values.len() {
+            n if n == cells + 4 => LEGACY_RNG_VERSION,
+            n if n == cells + 5 => values[cells + 4].parse().ok()?,
+            _ => return None
+        };
+
+        let mut world = Matrix::zeros(world_size);
+        for (i, value) in values[..cells].iter().enumerate(){
+            world[(i / world_size.1, i % world_size.1)] = value.parse().ok()?;
+        }
+
+        Some(RecordedStep {
+            world,
+            action : values[cells].parse().ok()?,
+            direction : values[cells + 1].parse().ok()?,
+            seed : values[cells + 2].parse().ok()?,
+            score : values[cells + 3].parse().ok()?,
+            rng_version
+        })
+    }
+
+    /// formats the step as a row, the inverse of `from_row`
+    pub fn to_row(&self) -> String{
+        let mut row = String::new();
+        for cell in self.world.matrix.iter().flatten(){
+            row += &format!("{cell}, ");
+        }
+        row + &format!("{},{},{},{},{}", self.action, self.direction, self.seed, self.score, self.rng_version)
+    }
+}
@@ -0,0 +1,59 @@
+use crate::snake::GameEngine;
+use crate::snake::data_types::*;
+use crate::snake::cell::Cell;
+
+impl GameEngine {
+
+    /// writes a cell of the world during a step and records the change
+    ///
+    /// A cell written more than once keeps a single change from its value
+    /// before the step, and changes back to that value are dropped.
+    pub(crate) fn set_cell(&mut self, pos : Vec2, cell : Cell){
+
+        let value = cell.code();
+        let old = self.game_world[(pos.x, pos.y)];
+        self.game_world[(pos.x, pos.y)] = value;
+
+        match self.delta.iter().position(|change| change.pos == pos) {
+            Some(i) if self.delta[i].old == value => {
+                self.delta.remove(i);
+            },
+            Some(i) => self.delta[i].new = value,
+            None if old != value => self.delta.push(CellChange { pos, old, new : value }),
+            None => ()
+        }
+    }
+
+    /// returns the cells changed by the last step, in the order they changed
+    ///
+    /// Applying the changes to the world before the step gives the world after it,
+    /// so renderers and loggers can follow the game without copying the whole
+    /// world every step. At most a handful of cells change per step, the new head,
+    /// the old head becoming body, the freed tail or the new food, and the cells
+    /// changed by the dynamics. After a reset the delta is empty, read the full
+    /// world with [`GameEngine::get_world`] instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snake_game_rust::snake::*;
+    ///
+    /// let mut game = GameEngine::new((10,10), (5,5), 1);
+    /// game.reset(3);
+    /// let mut world = game.get_world();
+    ///
+    /// for action in [1, 0, 0, 2]{
+    ///     let outcome = game.step_outcome(action);
+    ///     for change in game.get_delta(){
+    ///         assert_eq!(world[(change.pos.x, change.pos.y)], change.old);
+    ///         world[(change.pos.x, change.pos.y)] = change.new;
+    ///     }
+    ///     assert_eq!(outcome.delta, game.get_delta());
+    ///     assert_eq!(world, game.get_world());
+    /// }
+    /// ```
+    pub fn get_delta(&self) -> &[CellChange]{
+        &self.delta
+    }
+
+}
@@ -0,0 +1,124 @@
+use crate::snake::GameEngine;
+use crate::snake::data_types::*;
+use crate::snake::cell::Cell;
+
+/// optional dynamics that change the world while an episode is running
+///
+/// By default everything except the snake is static. Every dynamic is
+/// driven by the engine's `rng_generator`, so a given seed always produces
+/// the same episode.
+///
+/// * `food_move_interval` - every `k` steps the food wanders to a random
+///   free neighbouring cell
+/// * `obstacle_interval` - survival mode, every `m` steps a new obstacle
+///   cell is added. Obstacles are placed on the outermost free ring of the
+///   world, so the hazard spreads from the walls inward
+///
+/// An interval of `None` or `Some(0)` disables the dynamic.
+///
+/// # Examples
+///
+/// ```
+/// use snake_game_rust::snake::*;
+/// use snake_game_rust::snake::dynamics::Dynamics;
+///
+/// let mut game = GameEngine::new((10,10), (5,5), 1);
+/// game.set_dynamics(Dynamics { food_move_interval : Some(3), obstacle_interval : Some(1) });
+/// game.reset(7);
+///
+/// game.step(1);
+/// game.step(1);
+///
+/// // one obstacle per step, all of them touching a wall
+/// assert_eq!(game.obstacles.len(), 2);
+/// for obstacle in &game.obstacles {
+///     assert!(obstacle.x == 0 || obstacle.y == 0 || obstacle.x == 9 || obstacle.y == 9);
+/// }
+/// ```
+#[derive(Clone,Copy,Default,PartialEq,Eq,Debug)]
+pub struct Dynamics{
+    pub food_move_interval : Option<usize>,
+    pub obstacle_interval : Option<usize>
+}
+
+impl Dynamics {
+
+    fn is_due(interval : Option<usize>, steps : usize) -> bool{
+        match interval {
+            Some(k) if k > 0 => steps.is_multiple_of(k),
+            _ => false
+        }
+    }
+
+}
+
+impl GameEngine {
+
+    /// sets the dynamics used by the engine, takes effect from the next step
+    ///
+    /// Obstacles already placed are kept until the next reset.
+    pub fn set_dynamics(&mut self, dynamics : Dynamics){
+        self.dynamics = dynamics;
+    }
+
+    /// runs the dynamics that are due after the current step
+    pub(crate) fn apply_dynamics(&mut self){
+
+        if Dynamics::is_due(self.dynamics.obstacle_interval, self.steps){
+            self.spawn_obstacle();
+        }
+        if Dynamics::is_due(self.dynamics.food_move_interval, self.steps){
+            self.move_food();
+        }
+    }
+
+    /// distance of a cell from the closest wall, 0 for cells touching a wall
+    fn ring_of(&self, cell : &Vec2) -> i32{
+        let max_x = self.world_size.0 as i32 - 1;
+        let max_y = self.world_size.1 as i32 - 1;
+        cell.x.min(cell.y).min(max_x - cell.x).min(max_y - cell.y)
+    }
+
+    fn spawn_obstacle(&mut self){
+
+        let food_pos = self.food_pos;
+        let outermost_ring = self.free_space.iter()
+            .filter(|cell| **cell != food_pos)
+            .map(|cell| self.ring_of(cell))
+            .min();
+
+        let Some(outermost_ring) = outermost_ring else {
+            return;
+        };
+
+        let candidates : Vec<usize> = (0..self.free_space.len())
+            .filter(|i| self.free_space[*i] != food_pos && self.ring_of(&self.free_space[*i]) == outermost_ring)
+            .collect();
+
+        let index = candidates[self.rng_generator.usize(0..candidates.len())];
+        let obstacle = self.free_space.remove(index);
+
+        self.set_cell(obstacle, Cell::Obstacle);
+        self.zobrist.toggle_obstacle(obstacle);
+        self.obstacles.push(obstacle);
+    }
+
+    fn move_food(&mut self){
+
+        let candidates : Vec<Vec2> = self.neighbours(self.food_pos).into_iter()
+            .filter(|cell| self.free_space.contains(cell))
+            .collect();
+
+        if candidates.is_empty(){
+            return;
+        }
+
+        let new_food_pos = candidates[self.rng_generator.usize(0..candidates.len())];
+
+        self.set_cell(self.food_pos, Cell::Empty);
+        self.food_pos = new_food_pos;
+        self.set_cell(self.food_pos, Cell::Food);
+        self.zobrist.set_food(Some(self.food_pos));
+    }
+
+}
@@ -0,0 +1,131 @@
+use crate::snake::GameEngine;
+use crate::snake::observation::ObservationType;
+
+/// the interface shared by the engines, the 2D [`GameEngine`] and the
+/// 3D [`crate::snake::voxel::VoxelEngine`]
+///
+/// Code written against the trait, like agents, data collection and
+/// environment wrappers, runs on every variant of the game. The actions
+/// are relative to the snake in both engines.
+///
+/// # Examples
+///
+/// ```
+/// use snake_game_rust::snake::*;
+/// use snake_game_rust::snake::env::SnakeEnv;
+/// use snake_game_rust::snake::voxel::VoxelEngine;
+/// use snake_game_rust::snake::observation::ObservationType;
+///
+/// fn episode_length(env : &mut impl SnakeEnv, seed : u64) -> usize{
+///     env.rollout(seed, &mut |_| 1, 100).len()
+/// }
+///
+/// let mut flat = GameEngine::new((10,10), (5,5), 1);
+/// let mut cube = VoxelEngine::new((6,6,6), (3,3,3), VoxelEngine::default_frame());
+/// assert_eq!(episode_length(&mut flat, 1), 6);
+/// assert_eq!(episode_length(&mut cube, 1), 4);
+/// assert_eq!(cube.observation_size(ObservationType::Grid), 216);
+/// ```
+pub trait SnakeEnv{
+
+    /// the number of actions accepted by `step`
+    fn num_actions(&self) -> usize;
+
+    /// the action that keeps the current heading
+    fn forward_action(&self) -> usize;
+
+    /// resets the game to its start, the food follows from the seed
+    fn reset(&mut self, seed : u64);
+
+    /// advances the game by one step, returns `(done, food_eaten, msg)`,
+    /// the step winning the game reports `"victory"` and no food eaten
+    fn step(&mut self, action : usize) -> (bool,bool,String);
+
+    /// encodes the current state as an observation
+    fn observe(&self, observation_type : ObservationType) -> Vec<f32>;
+
+    /// the length of the observations of a type
+    fn observation_size(&self, observation_type : ObservationType) -> usize;
+
+    /// the dimensions of the world, the shape of the grid observation
+    fn world_shape(&self) -> Vec<usize>;
+
+    fn get_score(&self) -> usize;
+
+    fn get_seed(&self) -> u64;
+
+    /// every cell of the world as a comma separated string
+    fn get_flattened_world(&self) -> String;
+
+    /// the direction, seed, score and rng version of the engine
+    fn get_info_string(&self) -> String;
+
+    /// the row the data collection stores after a step, the flattened
+    /// world followed by the action and the info string
+    fn collection_row(&self, action : usize) -> String{
+        format!("{}{action}{}", self.get_flattened_world(), self.get_info_string())
+    }
+
+    /// resets with the seed and plays the policy until the game ends or
+    /// for at most `max_steps` steps, returns the actions it took
+    fn rollout(&mut self, seed : u64, policy : &mut dyn FnMut(&Self) -> usize, max_steps : usize) -> Vec<usize>
+    where Self : Sized{
+        self.reset(seed);
+        let mut actions = vec![];
+        for _ in 0..max_steps{
+            let action = policy(self);
+            actions.push(action);
+            if self.step(action).0{
+                break;
+            }
+        }
+        actions
+    }
+}
+
+impl SnakeEnv for GameEngine {
+
+    fn num_actions(&self) -> usize{
+        self.topology.num_actions()
+    }
+
+    fn forward_action(&self) -> usize{
+        self.topology.forward_action()
+    }
+
+    fn reset(&mut self, seed : u64){
+        GameEngine::reset(self, seed)
+    }
+
+    fn step(&mut self, action : usize) -> (bool,bool,String){
+        GameEngine::step(self, action)
+    }
+
+    fn observe(&self, observation_type : ObservationType) -> Vec<f32>{
+        GameEngine::observe(self, observation_type)
+    }
+
+    fn observation_size(&self, observation_type : ObservationType) -> usize{
+        observation_type.size(self.world_size, self.topology.as_ref())
+    }
+
+    fn world_shape(&self) -> Vec<usize>{
+        vec![self.world_size.0, self.world_size.1]
+    }
+
+    fn get_score(&self) -> usize{
+        GameEngine::get_score(self)
+    }
+
+    fn get_seed(&self) -> u64{
+        GameEngine::get_seed(self)
+    }
+
+    fn get_flattened_world(&self) -> String{
+        GameEngine::get_flattened_world(self)
+    }
+
+    fn get_info_string(&self) -> String{
+        GameEngine::get_info_string(self)
+    }
+}
@@ -0,0 +1,49 @@
+use std::fmt::Display;
+use crate::snake::data_types::Vec2;
+
+/// errors returned by the engine, mostly when a game state can not be built
+#[derive(Clone,PartialEq,Eq,Debug)]
+pub enum EngineError{
+    /// one of the world dimensions is 0
+    ZeroWorldSize,
+    /// the direction does not exist in the topology of the world
+    InvalidDirection(usize),
+    /// the snake body has no cells
+    EmptyBody,
+    /// a cell lies outside of the world grid
+    OutOfBounds(Vec2),
+    /// a cell is taken by more than one part of the state
+    Overlap(Vec2),
+    /// the body segment with this index is not a neighbour of the previous one
+    NotContiguous(usize),
+    /// the head is facing its own neck
+    FacingNeck,
+    /// a replay could not be parsed, holds the offending line
+    InvalidReplay(String),
+    /// a replayed step differs from the recorded one, holds the step and a description
+    ReplayMismatch(usize,String),
+    /// no environment preset has this name
+    UnknownEnv(String),
+    /// the scenario generator found no state, holds the reason
+    NoScenario(String)
+}
+
+impl Display for EngineError{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EngineError::ZeroWorldSize => write!(f, "World size cannot be zero"),
+            EngineError::InvalidDirection(direction) => write!(f, "Direction {direction} does not exist in the topology"),
+            EngineError::EmptyBody => write!(f, "The snake body cannot be empty"),
+            EngineError::OutOfBounds(cell) => write!(f, "Cell ({}, {}) is outside of the world", cell.x, cell.y),
+            EngineError::Overlap(cell) => write!(f, "Cell ({}, {}) is used more than once", cell.x, cell.y),
+            EngineError::NotContiguous(index) => write!(f, "Body segment {index} is not next to segment {}", index - 1),
+            EngineError::FacingNeck => write!(f, "The snake cannot face its own neck"),
+            EngineError::InvalidReplay(line) => write!(f, "Invalid replay, {line}"),
+            EngineError::ReplayMismatch(step, description) => write!(f, "Replay differs at step {step}, {description}"),
+            EngineError::UnknownEnv(name) => write!(f, "No environment is registered as {name}"),
+            EngineError::NoScenario(reason) => write!(f, "No scenario generated, {reason}")
+        }
+    }
+}
+
+impl std::error::Error for EngineError {}
@@ -0,0 +1,120 @@
+use crate::snake::GameEngine;
+use crate::snake::data_types::*;
+use crate::snake::cell::Cell;
+use crate::snake::rng::splitmix64;
+
+/// how the engine chooses the cell of a new food
+///
+/// * `Random` - a uniformly random free cell drawn from the engine's
+///   `rng_generator`. The cell depends on the order of the free cells and
+///   so on the path the snake took, two players on the same seed see
+///   different food after their first different move
+/// * `Scheduled` - the fixed food schedule, the `k`-th food of an episode
+///   is placed on [`scheduled_cell`] of the seed and `k`. When that cell is
+///   taken the next candidates of the schedule are tried, so as long as the
+///   cells are free, every player on the same seed gets the same food
+///
+/// The schedule does not draw from `rng_generator`, so it is the same for
+/// every rng version.
+///
+/// # Examples
+///
+/// ```
+/// use snake_game_rust::snake::*;
+/// use snake_game_rust::snake::food_schedule::FoodPlacement;
+///
+/// let mut left = GameEngine::new((10,10), (5,2), 1);
+/// let mut right = GameEngine::new((10,10), (5,7), 1);
+/// left.set_food_placement(FoodPlacement::Scheduled);
+/// right.set_food_placement(FoodPlacement::Scheduled);
+/// left.reset(42);
+/// right.reset(42);
+///
+/// // the snakes start in different places but get the same food
+/// assert_eq!(left.food_pos, right.food_pos);
+/// ```
+#[derive(Clone,Copy,Default,PartialEq,Eq,Debug)]
+pub enum FoodPlacement{
+    #[default]
+    Random,
+    Scheduled
+}
+
+impl FoodPlacement {
+
+    pub fn name(&self) -> &'static str{
+        match self {
+            FoodPlacement::Random => "random",
+            FoodPlacement::Scheduled => "scheduled"
+        }
+    }
+
+    /// the placement with the given name, `"random"` or `"scheduled"`
+    pub fn from_name(name : &str) -> Option<FoodPlacement>{
+        match name {
+            "random" => Some(FoodPlacement::Random),
+            "scheduled" => Some(FoodPlacement::Scheduled),
+            _ => None
+        }
+    }
+}
+
+/// the `attempt`-th candidate cell of the `k`-th food of an episode on the seed
+///
+/// The candidates of a food are the SplitMix64 stream started from
+/// `seed ^ splitmix64(k)`, every number is mapped to a cell index in row
+/// major order by multiplying with the number of cells and keeping the high
+/// 64 bits. The result only depends on the arguments.
+pub fn scheduled_cell(seed : u64, k : usize, attempt : usize, world_size : (usize,usize)) -> Vec2{
+    candidates(seed, k, world_size).nth(attempt).unwrap()
+}
+
+/// the endless stream of candidate cells of the `k`-th food
+fn candidates(seed : u64, k : usize, world_size : (usize,usize)) -> impl Iterator<Item = Vec2>{
+    let mut k_state = k as u64;
+    let mut state = seed ^ splitmix64(&mut k_state);
+    let cells = (world_size.0*world_size.1) as u128;
+    std::iter::repeat_with(move || {
+        let index = ((splitmix64(&mut state) as u128 * cells) >> 64) as usize;
+        Vec2 { x : (index / world_size.1) as i32, y : (index % world_size.1) as i32 }
+    })
+}
+
+impl GameEngine {
+
+    /// sets how new food is placed, takes effect from the next food
+    ///
+    /// The schedule counts the food placed since the last reset, so call
+    /// reset afterwards to start the episode from its first food.
+    pub fn set_food_placement(&mut self, food_placement : FoodPlacement){
+        self.food_placement = food_placement;
+    }
+
+    /// the cell of the next food of the schedule
+    ///
+    /// Candidates are tried until a free cell is found. On crowded worlds,
+    /// after as many candidates as there are cells, the first free cell
+    /// after the last candidate in row major order is taken instead.
+    /// Must only be called while there is a free cell.
+    pub(crate) fn scheduled_food_pos(&self) -> Vec2{
+
+        let cells = self.world_size.0*self.world_size.1;
+        let is_free = |cell : Vec2| self.game_world[(cell.x, cell.y)] == Cell::Empty.code();
+
+        let mut candidate = Vec2 { x : 0, y : 0 };
+        for cell in candidates(self.seed, self.food_count, self.world_size).take(cells){
+            if is_free(cell){
+                return cell;
+            }
+            candidate = cell;
+        }
+        let start = candidate.x as usize*self.world_size.1 + candidate.y as usize;
+        (1..=cells)
+            .map(|offset| {
+                let index = (start + offset) % cells;
+                Vec2 { x : (index / self.world_size.1) as i32, y : (index % self.world_size.1) as i32 }
+            })
+            .find(|cell| is_free(*cell))
+            .unwrap()
+    }
+}
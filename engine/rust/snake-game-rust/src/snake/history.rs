@@ -0,0 +1,168 @@
+use std::collections::VecDeque;
+use crate::snake::GameEngine;
+use crate::snake::data_types::*;
+use crate::snake::snake::Snake;
+use crate::snake::rng::EngineRng;
+use crate::snake::visibility::FogOfWar;
+use crate::snake::zobrist::ZobristHash;
+
+/// a copy of everything a step changes, the world, the snake, the food,
+/// the free cells, the obstacles, the random generator, the fog of war and
+/// the hash
+///
+/// The settings of the engine, like the rules, dynamics or action noise,
+/// are not part of a snapshot.
+#[derive(Clone)]
+pub struct Snapshot{
+    game_world : Matrix,
+    rng_generator : EngineRng,
+    seed : u64,
+    snake : Snake,
+    food_pos : Vec2,
+    food_count : usize,
+    free_space : Vec<Vec2>,
+    obstacles : Vec<Vec2>,
+    steps : usize,
+    steps_since_food : usize,
+    last_action : usize,
+    fog : Option<FogOfWar>,
+    zobrist : ZobristHash,
+    delta : Vec<CellChange>
+}
+
+/// the bounded history of an engine, see [`GameEngine::set_history`]
+#[derive(Clone,Default)]
+pub struct History{
+    capacity : usize,
+    past : VecDeque<Snapshot>,
+    future : Vec<Snapshot>
+}
+
+impl GameEngine {
+
+    /// returns a copy of the current state
+    pub fn snapshot(&self) -> Snapshot{
+        Snapshot {
+            game_world : self.game_world.clone(),
+            rng_generator : self.rng_generator.clone(),
+            seed : self.seed,
+            snake : self.snake.clone(),
+            food_pos : self.food_pos,
+            food_count : self.food_count,
+            free_space : self.free_space.clone(),
+            obstacles : self.obstacles.clone(),
+            steps : self.steps,
+            steps_since_food : self.steps_since_food,
+            last_action : self.last_action,
+            fog : self.fog.clone(),
+            zobrist : self.zobrist.clone(),
+            delta : self.delta.clone()
+        }
+    }
+
+    /// returns to a state taken with [`GameEngine::snapshot`] of this engine
+    ///
+    /// The history is not changed, use [`GameEngine::undo`] to step back.
+    pub fn restore(&mut self, snapshot : &Snapshot){
+        let snapshot = snapshot.clone();
+        self.game_world = snapshot.game_world;
+        self.rng_generator = snapshot.rng_generator;
+        self.seed = snapshot.seed;
+        self.snake = snapshot.snake;
+        self.food_pos = snapshot.food_pos;
+        self.food_count = snapshot.food_count;
+        self.free_space = snapshot.free_space;
+        self.obstacles = snapshot.obstacles;
+        self.steps = snapshot.steps;
+        self.steps_since_food = snapshot.steps_since_food;
+        self.last_action = snapshot.last_action;
+        self.fog = snapshot.fog;
+        self.zobrist = snapshot.zobrist;
+        self.delta = snapshot.delta;
+    }
+
+    /// keeps the states before the last `capacity` steps, so they can be
+    /// undone, a capacity of 0 disables the history
+    ///
+    /// The history is cleared, and it is also cleared on every reset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snake_game_rust::snake::*;
+    ///
+    /// let mut game = GameEngine::new((10,10), (5,5), 1);
+    /// game.set_history(16);
+    /// game.reset(3);
+    ///
+    /// let world = game.get_world();
+    /// let hash = game.get_hash();
+    /// game.step(0);
+    /// let after = game.get_world();
+    ///
+    /// assert!(game.undo());
+    /// assert_eq!(game.get_world(), world);
+    /// assert_eq!(game.get_hash(), hash);
+    ///
+    /// assert!(game.redo());
+    /// assert_eq!(game.get_world(), after);
+    /// assert!(!game.redo());
+    /// ```
+    pub fn set_history(&mut self, capacity : usize){
+        self.history = History { capacity, past : VecDeque::with_capacity(capacity), future : vec![] };
+    }
+
+    /// stores the current state before a step, and drops the undone steps
+    pub(crate) fn push_history(&mut self){
+        if self.history.capacity == 0 {
+            return;
+        }
+        if self.history.past.len() == self.history.capacity {
+            self.history.past.pop_front();
+        }
+        let snapshot = self.snapshot();
+        self.history.past.push_back(snapshot);
+        self.history.future.clear();
+    }
+
+    /// forgets all stored states, used on reset
+    pub(crate) fn clear_history(&mut self){
+        self.history.past.clear();
+        self.history.future.clear();
+    }
+
+    /// returns to the state before the last step,
+    /// returns false if there is no stored state
+    pub fn undo(&mut self) -> bool{
+        let Some(snapshot) = self.history.past.pop_back() else {
+            return false;
+        };
+        let current = self.snapshot();
+        self.history.future.push(current);
+        self.restore(&snapshot);
+        true
+    }
+
+    /// steps forward again after an undo, returns false if there is nothing
+    /// to redo, every new step drops the undone steps
+    pub fn redo(&mut self) -> bool{
+        let Some(snapshot) = self.history.future.pop() else {
+            return false;
+        };
+        let current = self.snapshot();
+        self.history.past.push_back(current);
+        self.restore(&snapshot);
+        true
+    }
+
+    /// the number of steps that can be undone
+    pub fn undo_len(&self) -> usize{
+        self.history.past.len()
+    }
+
+    /// the number of steps that can be redone
+    pub fn redo_len(&self) -> usize{
+        self.history.future.len()
+    }
+
+}
@@ -0,0 +1,225 @@
+use std::collections::HashSet;
+use std::fmt::Display;
+use crate::snake::GameEngine;
+use crate::snake::data_types::*;
+use crate::snake::cell::Cell;
+
+/// a broken invariant of the engine state, see [`GameEngine::validate`]
+#[derive(Clone,PartialEq,Eq,Debug)]
+pub enum Violation{
+    /// the head differs from the first body segment
+    HeadNotFirst{ head : Vec2, first : Option<Vec2> },
+    /// a body segment lies outside of the world
+    BodyOutOfBounds(Vec2),
+    /// a cell is covered by more than one body segment
+    BodyOverlap(Vec2),
+    /// the body segment with this index is not a neighbour of the previous one
+    NotContiguous(usize),
+    /// the world holds a different cell than the state describes
+    WrongCell{ pos : Vec2, expected : Cell, found : i32 },
+    /// the number of food cells in the world, when there is free space there has to be exactly one
+    FoodCount(usize),
+    /// the food lies outside of the world
+    FoodOutOfBounds(Vec2),
+    /// a free cell is listed more than once
+    DuplicateFreeCell(Vec2),
+    /// a listed free cell is taken by the snake or an obstacle
+    TakenFreeCell(Vec2),
+    /// an empty cell of the world is missing from the free cells
+    MissingFreeCell(Vec2),
+    /// the incremental hash differs from the hash of the state
+    Hash{ incremental : u64, computed : u64 }
+}
+
+impl Display for Violation{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cell = |pos : &Vec2| format!("({}, {})", pos.x, pos.y);
+        match self {
+            Violation::HeadNotFirst{ head, first : Some(first) } => write!(f, "the head {} differs from the first body segment {}", cell(head), cell(first)),
+            Violation::HeadNotFirst{ head, first : None } => write!(f, "the head {} has no body", cell(head)),
+            Violation::BodyOutOfBounds(pos) => write!(f, "body segment {} is outside of the world", cell(pos)),
+            Violation::BodyOverlap(pos) => write!(f, "cell {} is covered by the body more than once", cell(pos)),
+            Violation::NotContiguous(index) => write!(f, "body segment {index} is not next to segment {}", index - 1),
+            Violation::WrongCell{ pos, expected, found } => write!(f, "cell {} should be {} but holds {found}", cell(pos), expected.name()),
+            Violation::FoodCount(count) => write!(f, "the world holds {count} food cells"),
+            Violation::FoodOutOfBounds(pos) => write!(f, "the food {} is outside of the world", cell(pos)),
+            Violation::DuplicateFreeCell(pos) => write!(f, "free cell {} is listed more than once", cell(pos)),
+            Violation::TakenFreeCell(pos) => write!(f, "free cell {} is not free", cell(pos)),
+            Violation::MissingFreeCell(pos) => write!(f, "cell {} is free but not listed", cell(pos)),
+            Violation::Hash{ incremental, computed } => write!(f, "the hash is {incremental:#x} but the state hashes to {computed:#x}")
+        }
+    }
+}
+
+/// every violation found by [`GameEngine::validate`], with the step it was found at
+#[derive(Clone,PartialEq,Eq,Debug)]
+pub struct InvariantReport{
+    pub steps : usize,
+    pub violations : Vec<Violation>
+}
+
+impl Display for InvariantReport{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} invariant violations at step {}", self.violations.len(), self.steps)?;
+        for violation in &self.violations{
+            write!(f, "\n  * {violation}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for InvariantReport {}
+
+impl GameEngine {
+
+    /// checks that the redundant parts of the state agree with each other
+    ///
+    /// * the head is the first body segment
+    /// * the body lies inside of the world, without overlaps, and every
+    ///   segment is a neighbour of the previous one
+    /// * the world holds the head, the body, the obstacles and the food
+    ///   where the state puts them, and nothing else
+    /// * there is exactly one food cell, none once the snake fills the world
+    /// * `free_space` lists every cell without the snake or an obstacle
+    ///   exactly once, the food cell included
+    /// * the incremental hash matches the hash of the state
+    ///
+    /// The state after the step that ended the game with a collision or a
+    /// victory is left as it was when the game ended and does not pass.
+    /// With the `debug-invariants` feature the engine validates itself
+    /// after every reset and every step, and panics with the report.
+    /// Engines on the [`crate::snake::rules::LEGACY_RULES_VERSION`] list the
+    /// cell of a tail chased by the head as free, like the games recorded
+    /// with it did, their reports are collected in
+    /// [`GameEngine::get_invariant_reports`] instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snake_game_rust::snake::*;
+    /// use snake_game_rust::snake::cell::Cell;
+    /// use snake_game_rust::snake::data_types::Vec2;
+    /// use snake_game_rust::snake::invariants::Violation;
+    ///
+    /// let mut game = GameEngine::new((10,10), (5,5), 1);
+    /// game.reset(3);
+    /// game.step(0);
+    /// assert_eq!(game.validate(), Ok(()));
+    ///
+    /// let head = *game.get_snake_head();
+    /// game.game_world[(0, 0)] = Cell::Body.code();
+    /// game.free_space.push(head);
+    /// let report = game.validate().unwrap_err();
+    /// assert_eq!(report.violations, vec![
+    ///     Violation::WrongCell{ pos : Vec2{ x : 0, y : 0 }, expected : Cell::Empty, found : 2 },
+    ///     Violation::TakenFreeCell(head)
+    /// ]);
+    /// ```
+    pub fn validate(&self) -> Result<(),InvariantReport>{
+
+        let mut violations = vec![];
+        let body = &self.snake.snake_body;
+
+        if body.first() != Some(&self.snake.snake_head){
+            violations.push(Violation::HeadNotFirst{ head : self.snake.snake_head, first : body.first().copied() });
+        }
+
+        //the cells the world should hold, everything else is empty
+        let mut expected : Vec<(Vec2,Cell)> = vec![];
+        let mut covered : HashSet<Vec2> = HashSet::new();
+        for (i, segment) in body.iter().enumerate(){
+            if !self.in_world(*segment){
+                violations.push(Violation::BodyOutOfBounds(*segment));
+                continue;
+            }
+            if !covered.insert(*segment){
+                violations.push(Violation::BodyOverlap(*segment));
+            }
+            if i > 0 && !(0..self.topology.num_directions()).any(|d| self.next_cell(*segment, d) == body[i-1]){
+                violations.push(Violation::NotContiguous(i));
+            }
+            expected.push((*segment, if i == 0 { Cell::Head } else { Cell::Body }));
+        }
+        for obstacle in &self.obstacles{
+            expected.push((*obstacle, Cell::Obstacle));
+        }
+
+        let no_free_space = self.free_space.is_empty();
+        if !self.in_world(self.food_pos){
+            violations.push(Violation::FoodOutOfBounds(self.food_pos));
+        } else if !no_free_space{
+            expected.push((self.food_pos, Cell::Food));
+        }
+
+        let mut world = Matrix::zeros(self.world_size);
+        for (pos, cell) in &expected{
+            if self.in_world(*pos){
+                world[(pos.x, pos.y)] = cell.code();
+            }
+        }
+
+        let mut food_count = 0;
+        let mut free : HashSet<Vec2> = HashSet::new();
+        for x in 0..self.world_size.0{
+            for y in 0..self.world_size.1{
+                let pos = Vec2{ x : x as i32, y : y as i32 };
+                let found = self.game_world[(pos.x, pos.y)];
+                let cell = Cell::from_code(world[(pos.x, pos.y)]).unwrap_or_default();
+                if found != cell.code(){
+                    violations.push(Violation::WrongCell{ pos, expected : cell, found });
+                }
+                if found == Cell::Food.code(){
+                    food_count += 1;
+                }
+                if matches!(cell, Cell::Empty | Cell::Food){
+                    free.insert(pos);
+                }
+            }
+        }
+        if food_count != usize::from(!no_free_space){
+            violations.push(Violation::FoodCount(food_count));
+        }
+
+        let mut listed : HashSet<Vec2> = HashSet::new();
+        for cell in &self.free_space{
+            if !listed.insert(*cell){
+                violations.push(Violation::DuplicateFreeCell(*cell));
+            } else if !free.contains(cell){
+                violations.push(Violation::TakenFreeCell(*cell));
+            }
+        }
+        let mut missing : Vec<Vec2> = free.difference(&listed).copied().collect();
+        missing.sort_by_key(|cell| (cell.x, cell.y));
+        violations.extend(missing.into_iter().map(Violation::MissingFreeCell));
+
+        let computed = self.compute_hash();
+        if self.get_hash() != computed{
+            violations.push(Violation::Hash{ incremental : self.get_hash(), computed });
+        }
+
+        match violations.is_empty() {
+            true => Ok(()),
+            false => Err(InvariantReport { steps : self.steps, violations })
+        }
+    }
+
+    /// the reports found by the `debug-invariants` feature since the last
+    /// reset on an engine with the [`crate::snake::rules::LEGACY_RULES_VERSION`],
+    /// always empty without the feature
+    pub fn get_invariant_reports(&self) -> &[InvariantReport]{
+        &self.invariant_reports
+    }
+
+    /// validates the state with the `debug-invariants` feature, does nothing without it
+    #[allow(unused_variables)]
+    pub(crate) fn debug_validate(&mut self, context : &str){
+        #[cfg(feature = "debug-invariants")]
+        if let Err(report) = self.validate(){
+            if self.rules_version != crate::snake::rules::LEGACY_RULES_VERSION{
+                panic!("Engine state broken after {context}, {report}");
+            }
+            self.invariant_reports.push(report);
+        }
+    }
+
+}
@@ -0,0 +1,67 @@
+use crate::snake::GameEngine;
+use crate::snake::data_types::*;
+use crate::snake::cell::Cell;
+use crate::snake::errors::EngineError;
+
+impl GameEngine {
+
+    /// sets obstacles that are part of the world from the start of every
+    /// episode, like the walls of a maze, and resets the engine with its seed
+    ///
+    /// The obstacles cannot lie outside of the world or on the starting snake.
+    /// Obstacles spawned by the dynamics are added to the layout during the
+    /// episode and removed again on reset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snake_game_rust::snake::*;
+    /// use snake_game_rust::snake::cell::Cell;
+    /// use snake_game_rust::snake::data_types::Vec2;
+    /// use snake_game_rust::snake::errors::EngineError;
+    ///
+    /// let mut game = GameEngine::new((10,10), (5,5), 1);
+    /// let wall : Vec<Vec2> = (2..8).map(|y| Vec2{ x : 2, y }).collect();
+    /// game.set_obstacle_layout(wall).unwrap();
+    ///
+    /// assert_eq!(game.cell_at(Vec2{ x : 2, y : 5 }), Some(Cell::Obstacle));
+    /// assert_eq!(game.step(1).2, "alive");
+    /// assert_eq!(game.step(1).2, "alive");
+    /// assert_eq!(game.step(1).2, "obstacle");
+    ///
+    /// let on_snake = vec![Vec2{ x : 6, y : 5 }];
+    /// assert_eq!(game.set_obstacle_layout(on_snake), Err(EngineError::Overlap(Vec2{ x : 6, y : 5 })));
+    /// ```
+    pub fn set_obstacle_layout(&mut self, obstacles : Vec<Vec2>) -> Result<(),EngineError>{
+
+        for (i, obstacle) in obstacles.iter().enumerate(){
+            let inside = obstacle.x >= 0 && obstacle.y >= 0
+                && (obstacle.x as usize) < self.world_size.0 && (obstacle.y as usize) < self.world_size.1;
+            if !inside{
+                return Err(EngineError::OutOfBounds(*obstacle));
+            }
+            if obstacles[..i].contains(obstacle) || self.starting_snake().snake_body.contains(obstacle){
+                return Err(EngineError::Overlap(*obstacle));
+            }
+        }
+        self.obstacle_layout = obstacles;
+        self.reset(self.seed);
+        Ok(())
+    }
+
+    /// the obstacles placed on every reset
+    pub fn get_obstacle_layout(&self) -> &[Vec2]{
+        &self.obstacle_layout
+    }
+
+    /// places the layout into a freshly reset world, before the food
+    pub(crate) fn place_obstacle_layout(&mut self){
+        for i in 0..self.obstacle_layout.len(){
+            let obstacle = self.obstacle_layout[i];
+            self.free_space.retain(|cell| *cell != obstacle);
+            self.game_world[(obstacle.x, obstacle.y)] = Cell::Obstacle.code();
+            self.obstacles.push(obstacle);
+        }
+    }
+
+}
@@ -0,0 +1,70 @@
+use crate::snake::GameEngine;
+
+/// limits on the length of an episode, both disabled by default
+///
+/// * `max_steps` - the game ends with `"timeout"` after this many steps
+/// * `max_steps_without_food` - the game ends with `"starved"` when the
+///   snake has not eaten for this many steps
+///
+/// A timeout cuts the episode short, the snake could have kept going,
+/// so learning code should treat it as a truncation and not as a death.
+///
+/// # Examples
+///
+/// ```
+/// use snake_game_rust::snake::*;
+/// use snake_game_rust::snake::limits::Limits;
+///
+/// let mut game = GameEngine::new((10,10), (5,5), 1);
+/// game.set_limits(Limits { max_steps : Some(4), max_steps_without_food : None });
+///
+/// for _ in 0..3{
+///     let (_, _, msg) = game.step(0);
+///     assert_eq!(msg, "alive");
+/// }
+/// let (done, _, msg) = game.step(0);
+/// assert!(done);
+/// assert_eq!(msg, "timeout");
+/// ```
+#[derive(Clone,Copy,Default,PartialEq,Eq,Debug)]
+pub struct Limits{
+    pub max_steps : Option<usize>,
+    pub max_steps_without_food : Option<usize>
+}
+
+impl Limits {
+
+    /// no limits
+    pub fn none() -> Limits{
+        Limits::default()
+    }
+}
+
+impl GameEngine {
+
+    /// sets the episode limits, takes effect from the next step
+    pub fn set_limits(&mut self, limits : Limits){
+        self.limits = limits;
+    }
+
+    pub fn get_limits(&self) -> Limits{
+        self.limits
+    }
+
+    /// the number of steps since the snake last ate, or since the reset
+    pub fn get_steps_since_food(&self) -> usize{
+        self.steps_since_food
+    }
+
+    /// the message of the limit reached after the last step, if any
+    pub(crate) fn reached_limit(&self) -> Option<&'static str>{
+        if self.limits.max_steps.is_some_and(|max_steps| self.steps >= max_steps){
+            return Some("timeout");
+        }
+        if self.limits.max_steps_without_food.is_some_and(|max_steps| self.steps_since_food >= max_steps){
+            return Some("starved");
+        }
+        None
+    }
+
+}
@@ -0,0 +1,183 @@
+use crate::snake::GameEngine;
+use crate::snake::data_types::*;
+use crate::snake::cell::Cell;
+use crate::snake::topology::Topology;
+use crate::snake::rules::Walls;
+
+/// the encodings available for observations
+///
+/// * `Grid` - the flattened world matrix, one value per cell, row by row
+/// * `Features` - a compact feature vector, for `n` directions of the topology
+///   it holds `4 + 2n` values:
+///     * 4 flags for the food being above, below, left and right of the head
+///     * `n` flags for danger, a wall, body or obstacle, next to the head in every direction
+///     * `n` values of a one hot encoding of the direction
+///
+///   The danger flags and the direction are both ordered by direction index,
+///   on the square grid left, up, right and down.
+///
+/// `Features` is not the 12 value observation of `snake_env_12in_turn.py`,
+/// which orders the danger and the direction differently and only counts the
+/// body and the walls as danger. Agents trained on one do not run on the other.
+#[derive(Clone,Copy,PartialEq,Eq,Debug)]
+pub enum ObservationType{
+    Grid,
+    Features
+}
+
+impl ObservationType {
+
+    /// the length of the encoded observation
+    pub fn size(&self, world_size : (usize,usize), topology : &dyn Topology) -> usize{
+        match self {
+            ObservationType::Grid => world_size.0*world_size.1,
+            ObservationType::Features => 4 + 2*topology.num_directions()
+        }
+    }
+}
+
+/// encodes a world as an observation
+///
+/// The world can be masked, hidden cells are then not treated as danger
+/// and hidden food is not reported. With wrapping walls the cells across the
+/// wall are checked for danger instead of the wall.
+pub fn encode(
+    observation_type : ObservationType,
+    world : &Matrix,
+    head : Vec2,
+    direction : usize,
+    topology : &dyn Topology,
+    walls : Walls
+) -> Vec<f32>{
+    match observation_type {
+        ObservationType::Grid => world.matrix.iter().flatten().map(|cell| *cell as f32).collect(),
+        ObservationType::Features => {
+            let mut food = None;
+            for (x, row) in world.matrix.iter().enumerate(){
+                if let Some(y) = row.iter().position(|cell| *cell == Cell::Food.code()){
+                    food = Some(Vec2{ x : x as i32, y : y as i32 });
+                }
+            }
+            features(head, direction, food, topology, |pos| {
+                let cell = walls.apply(pos, world.size);
+                let outside = cell.x < 0 || cell.y < 0 || cell.x as usize >= world.size.0 || cell.y as usize >= world.size.1;
+                //hidden cells read as empty, they are not danger
+                (!outside).then(|| Cell::from_code(world[(cell.x, cell.y)]).unwrap_or(Cell::Empty))
+            })
+        }
+    }
+}
+
+/// the `Features` encoding, see [`ObservationType`]
+///
+/// `food` is the position of the food, `None` when there is no food or it
+/// is hidden. `cell_at` returns the cell at a position or `None` outside of
+/// the world, those cells are danger like the blocking ones.
+pub fn features(
+    head : Vec2,
+    direction : usize,
+    food : Option<Vec2>,
+    topology : &dyn Topology,
+    cell_at : impl Fn(Vec2) -> Option<Cell>
+) -> Vec<f32>{
+
+    let mut obs = Vec::with_capacity(4 + 2*topology.num_directions());
+
+    match food {
+        Some(food) => obs.extend([
+            (food.x < head.x) as i32 as f32,
+            (food.x > head.x) as i32 as f32,
+            (food.y < head.y) as i32 as f32,
+            (food.y > head.y) as i32 as f32
+        ]),
+        None => obs.extend([0.0;4])
+    }
+
+    for d in 0..topology.num_directions(){
+        let danger = cell_at(topology.advance(head, d)).is_none_or(|cell| cell.is_blocking());
+        obs.push(danger as i32 as f32);
+    }
+
+    for d in 0..topology.num_directions(){
+        obs.push((d == direction) as i32 as f32);
+    }
+
+    obs
+}
+
+/// the cells of a square window of `2*radius + 1` cells on each side,
+/// centred on the head, row by row
+///
+/// The window has the same size on every board, which makes it the
+/// observation for worlds too large to encode as a grid. `cell_at` returns
+/// the cell at a position or `None` outside of the world, those cells read
+/// as [`Cell::Obstacle`] since the snake dies on both.
+pub fn viewport(radius : usize, head : Vec2, cell_at : impl Fn(Vec2) -> Option<Cell>) -> Vec<f32>{
+    let radius = radius as i32;
+    let mut obs = Vec::with_capacity(((2*radius + 1)*(2*radius + 1)) as usize);
+    for dx in -radius..=radius{
+        for dy in -radius..=radius{
+            let cell = cell_at(head + Vec2 { x : dx, y : dy }).unwrap_or(Cell::Obstacle);
+            obs.push(cell.code() as f32);
+        }
+    }
+    obs
+}
+
+impl GameEngine {
+
+    /// encodes the cells around the head as an observation, see [`viewport`],
+    /// with wrapping walls the window continues across the wall
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snake_game_rust::snake::*;
+    ///
+    /// let game = GameEngine::new((10,10), (5,5), 1);
+    /// let window = game.observe_viewport(1);
+    ///
+    /// assert_eq!(window.len(), 9);
+    /// // the head in the centre and the neck below it
+    /// assert_eq!(window[4], 1.0);
+    /// assert_eq!(window[7], 2.0);
+    ///
+    /// // a window reaching past the walls
+    /// assert_eq!(game.observe_viewport(6).len(), 169);
+    /// assert_eq!(game.observe_viewport(6)[0], 4.0);
+    /// ```
+    pub fn observe_viewport(&self, radius : usize) -> Vec<f32>{
+        viewport(radius, self.snake.snake_head, |pos| self.cell_at(self.rules.walls.apply(pos, self.world_size)))
+    }
+
+    /// encodes the current world as an observation
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snake_game_rust::snake::*;
+    /// use snake_game_rust::snake::observation::ObservationType;
+    ///
+    /// let game = GameEngine::new((10,10), (5,5), 1);
+    ///
+    /// assert_eq!(game.observe(ObservationType::Grid).len(), 100);
+    ///
+    /// let features = game.observe(ObservationType::Features);
+    /// assert_eq!(features.len(), 12);
+    /// // the neck below the head is the only danger
+    /// assert_eq!(&features[4..8], &[0.0, 0.0, 0.0, 1.0]);
+    /// // facing up
+    /// assert_eq!(&features[8..], &[0.0, 1.0, 0.0, 0.0]);
+    /// ```
+    pub fn observe(&self, observation_type : ObservationType) -> Vec<f32>{
+        encode(
+            observation_type,
+            &self.game_world,
+            self.snake.snake_head,
+            self.snake.direction,
+            self.topology.as_ref(),
+            self.rules.walls
+        )
+    }
+
+}
@@ -0,0 +1,104 @@
+use crate::snake::GameEngine;
+use crate::snake::data_types::*;
+use crate::snake::rng::{EngineRng, CURRENT_RNG_VERSION};
+use crate::snake::rules::TailRule;
+
+/// chooses the actions of an agent playing the game
+///
+/// Closures taking the engine are policies too, so scripted agents
+/// don't need their own type.
+///
+/// # Examples
+///
+/// ```
+/// use snake_game_rust::snake::*;
+/// use snake_game_rust::snake::policy::GreedyPolicy;
+///
+/// let mut game = GameEngine::new((10,10), (5,5), 1);
+/// game.reset(3);
+///
+/// let actions = game.play(&mut GreedyPolicy, 500);
+/// assert!(game.get_score() > 0);
+///
+/// // the actions replay the same game
+/// game.reset(3);
+/// let mut replayed = actions.iter().copied();
+/// assert_eq!(game.play(&mut |_ : &GameEngine| replayed.next().unwrap(), 500), actions);
+/// ```
+pub trait Policy{
+    /// the next action for the current state of the engine
+    fn act(&mut self, engine : &GameEngine) -> usize;
+}
+
+impl<F : FnMut(&GameEngine) -> usize> Policy for F {
+    fn act(&mut self, engine : &GameEngine) -> usize{
+        self(engine)
+    }
+}
+
+/// picks uniformly random actions, seeded for reproducible episodes
+pub struct RandomPolicy{
+    rng : EngineRng
+}
+
+impl RandomPolicy {
+
+    pub fn new(seed : u64) -> RandomPolicy{
+        RandomPolicy { rng : EngineRng::new(CURRENT_RNG_VERSION, seed) }
+    }
+}
+
+impl Policy for RandomPolicy {
+    fn act(&mut self, engine : &GameEngine) -> usize{
+        self.rng.usize(0..engine.topology.num_actions())
+    }
+}
+
+/// moves towards the food and avoids the cells it would die on in the next
+/// step, prefers going forward when several actions are equally good
+///
+/// It only looks one step ahead, so it still traps itself in its body.
+#[derive(Clone,Copy,Default,Debug)]
+pub struct GreedyPolicy;
+
+impl Policy for GreedyPolicy {
+    fn act(&mut self, engine : &GameEngine) -> usize{
+
+        let topology = engine.topology.as_ref();
+        let forward = topology.forward_action();
+        let head = engine.snake.snake_head;
+        let tail = *engine.snake.snake_body.last().unwrap();
+        let food = engine.food_pos;
+
+        let distance = |cell : Vec2| (cell.x - food.x).abs() + (cell.y - food.y).abs();
+
+        (0..topology.num_actions())
+            .map(|action| {
+                let cell = engine.next_cell(head, topology.turn(engine.snake.direction, action));
+                //the tail moves away in the same step, the head never eats on the tail cell
+                let safe = !engine.is_blocked(cell) || (cell == tail && engine.get_rules().tail == TailRule::Chase);
+                (action, safe, distance(cell))
+            })
+            .min_by_key(|(action, safe, distance)| (!safe, *distance, *action != forward))
+            .map(|(action, _, _)| action)
+            .unwrap_or(forward)
+    }
+}
+
+impl GameEngine {
+
+    /// plays the policy until the game ends or for at most `max_steps` steps,
+    /// returns the actions it took
+    pub fn play(&mut self, policy : &mut impl Policy, max_steps : usize) -> Vec<usize>{
+        let mut actions = vec![];
+        for _ in 0..max_steps{
+            let action = policy.act(self);
+            actions.push(action);
+            if self.step(action).0{
+                break;
+            }
+        }
+        actions
+    }
+
+}
@@ -0,0 +1,158 @@
+//! Named and versioned environment presets
+//!
+//! A preset pins everything that changes the game, the world size, the start,
+//! the topology, the rules and their version, the limits, the obstacle layout,
+//! the food placement and the random generator version. Results reported on a preset name are
+//! therefore reproducible with any later version of the engine.
+//!
+//! Presets are never changed once published. A changed definition gets a new
+//! version suffix, `classic-10x10-v1` next to `classic-10x10-v0`.
+//!
+//! The `-v1` presets play the [`CURRENT_RULES_VERSION`], where the cell
+//! the head chases the tail into stays taken, the `-v0` presets keep the
+//! [`LEGACY_RULES_VERSION`] they were published with.
+//!
+//! # Examples
+//!
+//! ```
+//! use snake_game_rust::snake::registry;
+//!
+//! let mut game = registry::make("classic-10x10-v0").unwrap();
+//! game.reset(42);
+//! game.step(1);
+//!
+//! assert!(registry::make("classic-10x10").is_err());
+//! assert!(registry::names().contains(&"maze-1-v0"));
+//! ```
+
+use crate::snake::GameEngine;
+use crate::snake::data_types::*;
+use crate::snake::errors::EngineError;
+use crate::snake::topology;
+use crate::snake::rules::{Rules, Walls, LEGACY_RULES_VERSION, CURRENT_RULES_VERSION};
+use crate::snake::limits::Limits;
+use crate::snake::food_schedule::FoodPlacement;
+
+/// the layout of `maze-1-v0`, `#` marks an obstacle
+const MAZE_1 : [&str;12] = [
+    "............",
+    ".##########.",
+    "............",
+    ".####..####.",
+    "............",
+    "....#..#....",
+    "....#..#....",
+    "............",
+    ".####..####.",
+    "............",
+    ".##########.",
+    "............"
+];
+
+/// the definition of a preset, see [`EnvSpec::make`]
+#[derive(Clone,PartialEq,Eq,Debug)]
+pub struct EnvSpec{
+    pub name : &'static str,
+    pub world_size : (usize,usize),
+    pub starting_pos : (usize,usize),
+    pub starting_direction : usize,
+    pub topology : &'static str,
+    pub rules : Rules,
+    pub rules_version : u32,
+    pub limits : Limits,
+    pub obstacles : Vec<Vec2>,
+    pub food_placement : FoodPlacement,
+    pub rng_version : u32
+}
+
+impl EnvSpec {
+
+    /// a classic square world with solid walls and limits scaled to its size,
+    /// at most 10 steps per cell and one cell worth of steps between meals,
+    /// on the legacy rules of the `-v0` presets
+    fn classic(name : &'static str, size : usize) -> EnvSpec{
+        let cells = size*size;
+        EnvSpec {
+            name,
+            world_size : (size, size),
+            starting_pos : (size/2, size/2),
+            starting_direction : 1,
+            topology : "square",
+            rules : Rules::classic(),
+            rules_version : LEGACY_RULES_VERSION,
+            limits : Limits { max_steps : Some(10*cells), max_steps_without_food : Some(cells) },
+            obstacles : vec![],
+            food_placement : FoodPlacement::Random,
+            //pinned instead of the current version, which changes over time
+            rng_version : 2
+        }
+    }
+
+    /// builds an engine of the preset, reset it with a seed before playing
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the preset itself is invalid, which the tests rule out
+    pub fn make(&self) -> GameEngine{
+        let topology = match topology::from_name(self.topology) {
+            Some(topology) => topology,
+            None => panic!("Unknown topology {} in {}", self.topology, self.name)
+        };
+        let mut engine = GameEngine::with_topology(self.world_size, self.starting_pos, self.starting_direction, topology);
+        engine.set_rng_version(self.rng_version);
+        engine.set_rules(self.rules);
+        engine.set_rules_version(self.rules_version);
+        engine.set_limits(self.limits);
+        engine.set_food_placement(self.food_placement);
+        if let Err(err) = engine.set_obstacle_layout(self.obstacles.clone()){
+            panic!("Invalid obstacle layout of {}, {err}", self.name);
+        }
+        engine
+    }
+}
+
+/// every registered preset
+pub fn specs() -> Vec<EnvSpec>{
+    let maze : Vec<Vec2> = MAZE_1.iter().enumerate()
+        .flat_map(|(x, row)| row.char_indices()
+            .filter(|(_, cell)| *cell == '#')
+            .map(move |(y, _)| Vec2 { x : x as i32, y : y as i32 }))
+        .collect();
+
+    let wrap = |name| EnvSpec {
+        rules : Rules { walls : Walls::Wrap, ..Rules::classic() },
+        ..EnvSpec::classic(name, 15)
+    };
+    let maze = |name| EnvSpec {
+        starting_pos : (6, 5),
+        obstacles : maze.clone(),
+        ..EnvSpec::classic(name, 12)
+    };
+    let v1 = |spec : EnvSpec| EnvSpec { rules_version : CURRENT_RULES_VERSION, ..spec };
+
+    vec![
+        EnvSpec::classic("classic-10x10-v0", 10),
+        EnvSpec::classic("classic-20x20-v0", 20),
+        wrap("wrap-15x15-v0"),
+        maze("maze-1-v0"),
+        v1(EnvSpec::classic("classic-10x10-v1", 10)),
+        v1(EnvSpec::classic("classic-20x20-v1", 20)),
+        v1(wrap("wrap-15x15-v1")),
+        v1(maze("maze-1-v1"))
+    ]
+}
+
+/// the names of the registered presets
+pub fn names() -> Vec<&'static str>{
+    specs().iter().map(|spec| spec.name).collect()
+}
+
+/// the preset with the given name
+pub fn spec(name : &str) -> Option<EnvSpec>{
+    specs().into_iter().find(|spec| spec.name == name)
+}
+
+/// builds an engine of the preset with the given name
+pub fn make(name : &str) -> Result<GameEngine,EngineError>{
+    spec(name).map(|spec| spec.make()).ok_or_else(|| EngineError::UnknownEnv(name.to_string()))
+}
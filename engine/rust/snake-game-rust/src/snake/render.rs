@@ -0,0 +1,299 @@
+//! Headless rendering of the world to RGB images
+//!
+//! The world is drawn like it is printed, the row `x` of the [`Matrix`] is the
+//! row of squares at the height `x * cell_size` in the image, so the frames
+//! match `np.array(py_get_world())` in python. No window or display is needed.
+//!
+//! With the `render` feature the frames are encoded as PNG images and whole
+//! episodes as animated GIF or APNG files:
+//!
+//! ```toml
+//! snake-game-rust = { path = "../snake-game-rust", features = ["render"] }
+//! ```
+
+use crate::snake::GameEngine;
+use crate::snake::data_types::*;
+use crate::snake::cell::Cell;
+use crate::snake::replay::Replay;
+use crate::snake::visibility::HIDDEN_CELL;
+
+/// an RGB color
+pub type Rgb = [u8;3];
+
+/// the colors of the cells, the default matches the colors of the UI
+#[derive(Clone,Copy,PartialEq,Eq,Debug)]
+pub struct Palette{
+    pub empty : Rgb,
+    pub head : Rgb,
+    pub body : Rgb,
+    pub food : Rgb,
+    pub obstacle : Rgb,
+    /// the color of hidden cells of a masked world and unknown codes
+    pub hidden : Rgb
+}
+
+impl Default for Palette {
+    fn default() -> Palette{
+        Palette {
+            empty : [128, 128, 128],
+            head : [0, 0, 255],
+            body : [0, 255, 255],
+            food : [255, 0, 0],
+            obstacle : [51, 51, 51],
+            hidden : [0, 0, 0]
+        }
+    }
+}
+
+impl Palette {
+
+    /// the color of a cell code of the world matrix
+    pub fn color(&self, code : i32) -> Rgb{
+        match Cell::from_code(code) {
+            Some(Cell::Empty) => self.empty,
+            Some(Cell::Head) => self.head,
+            Some(Cell::Body) => self.body,
+            Some(Cell::Food) => self.food,
+            Some(Cell::Obstacle) => self.obstacle,
+            None => self.hidden
+        }
+    }
+
+    /// every color of the palette, without duplicates
+    fn colors(&self) -> Vec<Rgb>{
+        let mut colors = vec![];
+        for code in (0..Cell::ALL.len() as i32).chain([HIDDEN_CELL]){
+            let color = self.color(code);
+            if !colors.contains(&color){
+                colors.push(color);
+            }
+        }
+        colors
+    }
+}
+
+/// an RGB image, three bytes per pixel, row by row
+#[derive(Clone,PartialEq,Eq,Debug)]
+pub struct Frame{
+    pub width : usize,
+    pub height : usize,
+    pub pixels : Vec<u8>
+}
+
+impl Frame {
+
+    /// the color of the pixel in the column `x` and the row `y`
+    pub fn pixel(&self, x : usize, y : usize) -> Rgb{
+        let i = 3*(y*self.width + x);
+        [self.pixels[i], self.pixels[i+1], self.pixels[i+2]]
+    }
+}
+
+/// draws worlds with square cells of a given size
+///
+/// # Examples
+///
+/// ```
+/// use snake_game_rust::snake::*;
+/// use snake_game_rust::snake::render::{Renderer, Palette};
+///
+/// let game = GameEngine::new((10,10), (5,5), 1);
+/// let renderer = Renderer::new(4);
+/// let frame = renderer.render(&game.get_world());
+///
+/// assert_eq!((frame.width, frame.height), (40, 40));
+/// // the head at (5,5) is drawn in the column 5 and the row 5
+/// assert_eq!(frame.pixel(5*4 + 1, 5*4 + 2), Palette::default().head);
+/// ```
+#[derive(Clone,Copy,PartialEq,Eq,Debug)]
+pub struct Renderer{
+    pub cell_size : usize,
+    pub palette : Palette
+}
+
+impl Default for Renderer {
+    fn default() -> Renderer{
+        Renderer::new(16)
+    }
+}
+
+impl Renderer {
+
+    /// # Panics
+    ///
+    /// Will panic if the cell size is 0
+    pub fn new(cell_size : usize) -> Renderer{
+        assert!(cell_size > 0, "The cell size has to be positive");
+        Renderer { cell_size, palette : Palette::default() }
+    }
+
+    pub fn with_palette(cell_size : usize, palette : Palette) -> Renderer{
+        Renderer { palette, ..Renderer::new(cell_size) }
+    }
+
+    /// draws a world matrix, `cell_size` pixels per cell
+    pub fn render(&self, world : &Matrix) -> Frame{
+
+        let width = world.size.1*self.cell_size;
+        let height = world.size.0*self.cell_size;
+        let mut pixels = Vec::with_capacity(3*width*height);
+
+        for row in &world.matrix{
+            let mut line = Vec::with_capacity(3*width);
+            for cell in row{
+                let color = self.palette.color(*cell);
+                for _ in 0..self.cell_size{
+                    line.extend_from_slice(&color);
+                }
+            }
+            for _ in 0..self.cell_size{
+                pixels.extend_from_slice(&line);
+            }
+        }
+        Frame { width, height, pixels }
+    }
+
+    /// draws every state of a replay, from the reset to the last step
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the replay uses an unknown topology or rng version
+    pub fn render_replay(&self, replay : &Replay) -> Vec<Frame>{
+        let mut engine = replay.engine();
+        let mut frames = vec![self.render(&engine.game_world)];
+        for action in replay.actions(){
+            let (done, _, _) = engine.step(action);
+            frames.push(self.render(&engine.game_world));
+            if done{
+                break;
+            }
+        }
+        frames
+    }
+
+    /// the colors the frames of this renderer are made of
+    pub fn colors(&self) -> Vec<Rgb>{
+        self.palette.colors()
+    }
+}
+
+impl GameEngine {
+
+    /// draws the current world, see [`Renderer`]
+    pub fn render(&self, renderer : &Renderer) -> Frame{
+        renderer.render(&self.game_world)
+    }
+
+}
+
+#[cfg(feature = "render")]
+mod encode{
+
+    use std::fs::File;
+    use std::io::{self, BufWriter, Write};
+    use std::path::Path;
+    use super::{Frame, Rgb};
+
+    fn other(err : impl std::error::Error + Send + Sync + 'static) -> io::Error{
+        io::Error::other(err)
+    }
+
+    fn check_sizes(frames : &[Frame]) -> io::Result<(usize,usize)>{
+        let Some(first) = frames.first() else {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "an animation needs at least one frame"));
+        };
+        if frames.iter().any(|frame| (frame.width, frame.height) != (first.width, first.height)){
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "the frames of an animation have different sizes"));
+        }
+        Ok((first.width, first.height))
+    }
+
+    fn png_encoder<W : Write>(writer : W, width : usize, height : usize) -> io::Result<png::Encoder<'static, W>>{
+        let width = u32::try_from(width).map_err(other)?;
+        let height = u32::try_from(height).map_err(other)?;
+        let mut encoder = png::Encoder::new(writer, width, height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        Ok(encoder)
+    }
+
+    impl Frame {
+
+        /// encodes the frame as a PNG image
+        pub fn write_png<W : Write>(&self, writer : W) -> io::Result<()>{
+            let mut writer = png_encoder(writer, self.width, self.height)?.write_header().map_err(other)?;
+            writer.write_image_data(&self.pixels).map_err(other)?;
+            writer.finish().map_err(other)
+        }
+
+        pub fn save_png(&self, path : impl AsRef<Path>) -> io::Result<()>{
+            self.write_png(BufWriter::new(File::create(path)?))
+        }
+    }
+
+    /// encodes the frames as an endlessly looping animated GIF,
+    /// showing every frame for `delay_ms` milliseconds
+    ///
+    /// The GIF stores colors in a palette of at most 256 colors, the `colors`
+    /// of the frames, usually [`super::Renderer::colors`]. Pixels of other
+    /// colors are drawn with the closest color of the palette.
+    pub fn write_gif<W : Write>(frames : &[Frame], colors : &[Rgb], delay_ms : u32, writer : W) -> io::Result<()>{
+
+        let (width, height) = check_sizes(frames)?;
+        if colors.is_empty() || colors.len() > 256{
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "a GIF palette holds 1 to 256 colors"));
+        }
+        let width = u16::try_from(width).map_err(other)?;
+        let height = u16::try_from(height).map_err(other)?;
+
+        let palette : Vec<u8> = colors.iter().flatten().copied().collect();
+        let mut encoder = gif::Encoder::new(writer, width, height, &palette).map_err(other)?;
+        encoder.set_repeat(gif::Repeat::Infinite).map_err(other)?;
+
+        for frame in frames{
+            let indices : Vec<u8> = frame.pixels.chunks_exact(3).map(|pixel| closest(colors, pixel)).collect();
+            let mut gif_frame = gif::Frame::from_indexed_pixels(width, height, indices, None);
+            //the delay of GIF frames is stored in hundredths of a second
+            gif_frame.delay = u16::try_from(delay_ms/10).unwrap_or(u16::MAX);
+            encoder.write_frame(&gif_frame).map_err(other)?;
+        }
+        Ok(())
+    }
+
+    pub fn save_gif(frames : &[Frame], colors : &[Rgb], delay_ms : u32, path : impl AsRef<Path>) -> io::Result<()>{
+        write_gif(frames, colors, delay_ms, BufWriter::new(File::create(path)?))
+    }
+
+    /// encodes the frames as an endlessly looping animated PNG,
+    /// showing every frame for `delay_ms` milliseconds
+    pub fn write_apng<W : Write>(frames : &[Frame], delay_ms : u32, writer : W) -> io::Result<()>{
+
+        let (width, height) = check_sizes(frames)?;
+        let frame_count = u32::try_from(frames.len()).map_err(other)?;
+
+        let mut encoder = png_encoder(writer, width, height)?;
+        encoder.set_animated(frame_count, 0).map_err(other)?;
+        encoder.set_frame_delay(u16::try_from(delay_ms).unwrap_or(u16::MAX), 1000).map_err(other)?;
+
+        let mut writer = encoder.write_header().map_err(other)?;
+        for frame in frames{
+            writer.write_image_data(&frame.pixels).map_err(other)?;
+        }
+        writer.finish().map_err(other)
+    }
+
+    pub fn save_apng(frames : &[Frame], delay_ms : u32, path : impl AsRef<Path>) -> io::Result<()>{
+        write_apng(frames, delay_ms, BufWriter::new(File::create(path)?))
+    }
+
+    /// the index of the palette color closest to the pixel
+    fn closest(colors : &[Rgb], pixel : &[u8]) -> u8{
+        let distance = |color : &Rgb| -> u32 {
+            color.iter().zip(pixel).map(|(a, b)| (*a as i32 - *b as i32).pow(2) as u32).sum()
+        };
+        (0..colors.len()).min_by_key(|i| distance(&colors[*i])).unwrap_or(0) as u8
+    }
+}
+
+#[cfg(feature = "render")]
+pub use encode::{write_gif, save_gif, write_apng, save_apng};
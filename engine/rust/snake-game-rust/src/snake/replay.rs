@@ -0,0 +1,474 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
+use crate::snake::GameEngine;
+use crate::snake::data_types::*;
+use crate::snake::env::SnakeEnv;
+use crate::snake::errors::EngineError;
+use crate::snake::topology;
+use crate::snake::rng::LEGACY_RNG_VERSION;
+use crate::snake::food_schedule::FoodPlacement;
+use crate::snake::rules::{Rules, TailRule, Reversal, Walls, LEGACY_RULES_VERSION};
+use crate::snake::limits::Limits;
+
+/// a single recorded step of a replay, the action and what it led to
+#[derive(Clone,PartialEq,Eq,Debug)]
+pub struct ReplayStep<P = Vec2>{
+    pub action : usize,
+    pub msg : String,
+    pub food_eaten : bool,
+    pub head : P,
+    pub food_pos : P,
+    pub length : usize
+}
+
+/// a position written to replays as its coordinates
+pub trait ReplayPos : Copy + PartialEq + Eq + Debug{
+
+    /// the number of coordinates
+    const DIMENSIONS : usize;
+
+    fn coordinates(&self) -> Vec<i32>;
+
+    /// the position of [`ReplayPos::DIMENSIONS`] coordinates
+    fn from_coordinates(coordinates : &[i32]) -> Self;
+}
+
+impl ReplayPos for Vec2 {
+
+    const DIMENSIONS : usize = 2;
+
+    fn coordinates(&self) -> Vec<i32>{
+        vec![self.x, self.y]
+    }
+
+    fn from_coordinates(coordinates : &[i32]) -> Vec2{
+        Vec2 { x : coordinates[0], y : coordinates[1] }
+    }
+}
+
+impl ReplayPos for Vec3 {
+
+    const DIMENSIONS : usize = 3;
+
+    fn coordinates(&self) -> Vec<i32>{
+        vec![self.x, self.y, self.z]
+    }
+
+    fn from_coordinates(coordinates : &[i32]) -> Vec3{
+        Vec3 { x : coordinates[0], y : coordinates[1], z : coordinates[2] }
+    }
+}
+
+/// a line of a replay split into its fields, the first being the key
+pub struct ReplayLine<'a>{
+    pub fields : Vec<&'a str>,
+    line : &'a str,
+    number : usize
+}
+
+impl<'a> ReplayLine<'a> {
+
+    /// the error for this line
+    pub fn invalid(&self) -> EngineError{
+        EngineError::InvalidReplay(format!("line {}: {}", self.number, self.line))
+    }
+
+    pub fn field(&self, index : usize) -> Result<&'a str,EngineError>{
+        self.fields.get(index).copied().ok_or_else(|| self.invalid())
+    }
+
+    pub fn parse<T : FromStr>(&self, index : usize) -> Result<T,EngineError>{
+        self.field(index)?.parse().map_err(|_| self.invalid())
+    }
+
+    pub fn number(&self, index : usize) -> Result<i64,EngineError>{
+        self.parse(index)
+    }
+
+    /// the position in the fields starting at `index`
+    pub fn pos<P : ReplayPos>(&self, index : usize) -> Result<P,EngineError>{
+        let coordinates = (index..index + P::DIMENSIONS)
+            .map(|index| self.parse(index))
+            .collect::<Result<Vec<i32>,EngineError>>()?;
+        Ok(P::from_coordinates(&coordinates))
+    }
+}
+
+/// the recording, the verification and the text format shared by the
+/// replays of the engines, [`Replay`] and [`crate::snake::voxel::VoxelReplay`]
+///
+/// A replay is a header with the settings of the engine, the seed and the
+/// food after the reset, followed by one `step` line per step with the
+/// action, the message, whether food was eaten, the head, the food position
+/// and the length of the snake. The implementations only provide the header.
+pub trait ReplayFormat : Sized{
+
+    type Engine : SnakeEnv;
+    type Pos : ReplayPos;
+
+    /// a replay of the settings of an engine that was just reset with
+    /// the seed, without steps
+    fn header(engine : &Self::Engine, seed : u64) -> Self;
+
+    /// a replay with the defaults of missing header lines, filled by `parse`
+    fn empty() -> Self;
+
+    /// an engine with the settings of the replay, reset to its seed
+    fn new_engine(&self) -> Self::Engine;
+
+    /// the head, the food position and the length of the snake of an engine
+    fn snake_state(engine : &Self::Engine) -> (Self::Pos,Self::Pos,usize);
+
+    fn seed(&self) -> u64;
+
+    /// the food after the reset
+    fn food_pos(&self) -> Self::Pos;
+
+    fn steps(&self) -> &[ReplayStep<Self::Pos>];
+
+    fn steps_mut(&mut self) -> &mut Vec<ReplayStep<Self::Pos>>;
+
+    /// reads a header line into the replay, `Ok(false)` for an unknown key
+    fn parse_header(&mut self, line : &ReplayLine) -> Result<bool,EngineError>;
+
+    /// writes the header lines
+    fn write_header(&self, f : &mut Formatter<'_>) -> std::fmt::Result;
+
+    /// resets the engine with the seed and plays the actions until the game ends
+    fn record_game(engine : &mut Self::Engine, seed : u64, actions : &[usize]) -> Self{
+
+        engine.reset(seed);
+        let mut replay = Self::header(engine, seed);
+
+        for action in actions{
+            let (done, food_eaten, msg) = engine.step(*action);
+            let (head, food_pos, length) = Self::snake_state(engine);
+            replay.steps_mut().push(ReplayStep { action : *action, msg, food_eaten, head, food_pos, length });
+            if done {
+                break;
+            }
+        }
+        replay
+    }
+
+    /// the recorded actions
+    fn recorded_actions(&self) -> Vec<usize>{
+        self.steps().iter().map(|step| step.action).collect()
+    }
+
+    /// plays the replay again and checks that every step leads to the recorded result
+    fn verify_game(&self) -> Result<(),EngineError>{
+
+        let replayed = Self::record_game(&mut self.new_engine(), self.seed(), &self.recorded_actions());
+
+        if replayed.food_pos() != self.food_pos(){
+            return Err(EngineError::ReplayMismatch(0, format!(
+                "food after reset at {:?}, recorded {:?}", replayed.food_pos(), self.food_pos()
+            )));
+        }
+        for (i, step) in self.steps().iter().enumerate(){
+            match replayed.steps().get(i) {
+                Some(replayed_step) if replayed_step == step => (),
+                Some(replayed_step) => return Err(EngineError::ReplayMismatch(i + 1, format!(
+                    "got {replayed_step:?}, recorded {step:?}"
+                ))),
+                None => return Err(EngineError::ReplayMismatch(i + 1, "the game ended earlier".to_string()))
+            }
+        }
+        Ok(())
+    }
+
+    /// parses a replay from its text format, empty lines and lines
+    /// starting with `#` are ignored
+    fn parse_text(text : &str) -> Result<Self,EngineError>{
+
+        let mut replay = Self::empty();
+        let dimensions = Self::Pos::DIMENSIONS;
+
+        for (i, line) in text.lines().enumerate(){
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#'){
+                continue;
+            }
+            let line = ReplayLine { fields : line.split_whitespace().collect(), line, number : i + 1 };
+
+            if line.fields[0] == "step"{
+                let step = ReplayStep {
+                    action : line.parse(1)?,
+                    msg : line.field(2)?.to_string(),
+                    food_eaten : line.number(3)? == 1,
+                    head : line.pos(4)?,
+                    food_pos : line.pos(4 + dimensions)?,
+                    length : line.parse(4 + 2*dimensions)?
+                };
+                replay.steps_mut().push(step);
+            } else if !replay.parse_header(&line)?{
+                return Err(line.invalid());
+            }
+        }
+        Ok(replay)
+    }
+
+    /// writes the replay in its text format
+    fn write_text(&self, f : &mut Formatter<'_>) -> std::fmt::Result{
+        self.write_header(f)?;
+        for step in self.steps(){
+            write!(f, "step {} {} {}", step.action, step.msg, step.food_eaten as i32)?;
+            for coordinate in step.head.coordinates().into_iter().chain(step.food_pos.coordinates()){
+                write!(f, " {coordinate}")?;
+            }
+            writeln!(f, " {}", step.length)?;
+        }
+        Ok(())
+    }
+}
+
+/// a recorded game, the engine settings, the seed and every step
+///
+/// Replays are stored as plain text, one entry per line, so they can be
+/// checked into the repository and diffed:
+///
+/// ```text
+/// world_size 10 10
+/// starting_pos 5 5
+/// starting_direction 1
+/// topology square
+/// seed 42
+/// rng_version 2
+/// food_placement random
+/// rules chase ignore solid
+/// rules_version 2
+/// limits 1000 100
+/// obstacles 2,3 2,4
+/// food 3 7
+/// step 1 alive 0 4 5 3 7 3
+/// ```
+///
+/// `food` is the food position after the reset, every `step` line holds the
+/// action, the message, whether food was eaten, the head, the food position
+/// and the length of the snake after the step. Empty lines and lines
+/// starting with `#` are ignored. Replays without an `rng_version` line
+/// were recorded before the engine had versioned generators and are played
+/// with [`LEGACY_RNG_VERSION`], replays without a `food_placement` line
+/// use random food and replays without `rules` the classic rules with solid
+/// walls. Replays without a `rules_version` line are played with
+/// [`LEGACY_RULES_VERSION`], the line is left out for that version so the
+/// replays recorded before it existed keep their text. `limits` holds the
+/// step limits, `none` for a disabled limit, and `obstacles` the obstacle
+/// layout, both are empty when the line is missing.
+///
+/// # Examples
+///
+/// ```
+/// use snake_game_rust::snake::*;
+/// use snake_game_rust::snake::replay::Replay;
+///
+/// let mut game = GameEngine::new((10,10), (5,5), 1);
+/// let replay = Replay::record(&mut game, 42, &[1, 0, 0, 2]);
+///
+/// let text = replay.to_string();
+/// let parsed = Replay::parse(&text).unwrap();
+/// assert_eq!(parsed, replay);
+/// assert!(parsed.verify().is_ok());
+/// ```
+#[derive(Clone,PartialEq,Eq,Debug)]
+pub struct Replay{
+    pub world_size : (usize,usize),
+    pub starting_pos : (usize,usize),
+    pub starting_direction : usize,
+    pub topology : String,
+    pub seed : u64,
+    pub rng_version : u32,
+    pub food_placement : FoodPlacement,
+    pub rules : Rules,
+    pub rules_version : u32,
+    pub limits : Limits,
+    pub obstacles : Vec<Vec2>,
+    pub food_pos : Vec2,
+    pub steps : Vec<ReplayStep>
+}
+
+impl Replay {
+
+    /// resets the engine with the seed and plays the actions until the game ends
+    ///
+    /// The engine settings stored in the replay are the world size, the starting
+    /// position and direction, the topology, the rng version, the food
+    /// placement, the rules and their version, the limits and the obstacle layout, other settings
+    /// like dynamics are not recorded.
+    pub fn record(engine : &mut GameEngine, seed : u64, actions : &[usize]) -> Replay{
+        Replay::record_game(engine, seed, actions)
+    }
+
+    /// the recorded actions
+    pub fn actions(&self) -> Vec<usize>{
+        self.recorded_actions()
+    }
+
+    /// returns an engine with the settings of the replay, reset to its seed
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the replay uses an unknown topology, rng or rules version,
+    /// or if an obstacle lies outside of the world or on the snake
+    pub fn engine(&self) -> GameEngine{
+        let topology = match topology::from_name(&self.topology) {
+            Some(topology) => topology,
+            None => panic!("Unknown topology {}", self.topology)
+        };
+        let mut engine = GameEngine::with_topology(self.world_size, self.starting_pos, self.starting_direction, topology);
+        engine.set_rng_version(self.rng_version);
+        engine.set_food_placement(self.food_placement);
+        engine.set_rules(self.rules);
+        engine.set_rules_version(self.rules_version);
+        engine.set_limits(self.limits);
+        if let Err(err) = engine.set_obstacle_layout(self.obstacles.clone()){
+            panic!("Invalid obstacle layout, {err}");
+        }
+        engine.reset(self.seed);
+        engine
+    }
+
+    /// plays the replay again and checks that every step leads to the recorded result
+    pub fn verify(&self) -> Result<(),EngineError>{
+        self.verify_game()
+    }
+
+    /// parses a replay from its text format
+    pub fn parse(text : &str) -> Result<Replay,EngineError>{
+        Replay::parse_text(text)
+    }
+}
+
+impl ReplayFormat for Replay {
+
+    type Engine = GameEngine;
+    type Pos = Vec2;
+
+    fn header(engine : &GameEngine, seed : u64) -> Replay{
+        Replay {
+            world_size : engine.world_size,
+            starting_pos : engine.starting_pos,
+            starting_direction : engine.starting_direction,
+            topology : engine.topology.name().to_string(),
+            seed,
+            rng_version : engine.rng_version,
+            food_placement : engine.food_placement,
+            rules : engine.rules,
+            rules_version : engine.rules_version,
+            limits : engine.limits,
+            obstacles : engine.obstacle_layout.clone(),
+            food_pos : engine.food_pos,
+            steps : vec![]
+        }
+    }
+
+    fn empty() -> Replay{
+        Replay {
+            world_size : (0,0),
+            starting_pos : (0,0),
+            starting_direction : 0,
+            topology : "square".to_string(),
+            seed : 0,
+            rng_version : LEGACY_RNG_VERSION,
+            food_placement : FoodPlacement::Random,
+            rules : Rules::classic(),
+            rules_version : LEGACY_RULES_VERSION,
+            limits : Limits::none(),
+            obstacles : vec![],
+            food_pos : Vec2 { x : 0, y : 0 },
+            steps : vec![]
+        }
+    }
+
+    fn new_engine(&self) -> GameEngine{
+        self.engine()
+    }
+
+    fn snake_state(engine : &GameEngine) -> (Vec2,Vec2,usize){
+        (engine.snake.snake_head, engine.food_pos, engine.snake.snake_body.len())
+    }
+
+    fn seed(&self) -> u64{
+        self.seed
+    }
+
+    fn food_pos(&self) -> Vec2{
+        self.food_pos
+    }
+
+    fn steps(&self) -> &[ReplayStep]{
+        &self.steps
+    }
+
+    fn steps_mut(&mut self) -> &mut Vec<ReplayStep>{
+        &mut self.steps
+    }
+
+    fn parse_header(&mut self, line : &ReplayLine) -> Result<bool,EngineError>{
+        let limit = |index : usize| -> Result<Option<usize>,EngineError> {
+            match line.field(index)? {
+                "none" => Ok(None),
+                _ => line.parse(index).map(Some)
+            }
+        };
+        match line.fields[0] {
+            "world_size" => self.world_size = (line.parse(1)?, line.parse(2)?),
+            "starting_pos" => self.starting_pos = (line.parse(1)?, line.parse(2)?),
+            "starting_direction" => self.starting_direction = line.parse(1)?,
+            "topology" => self.topology = line.field(1)?.to_string(),
+            "seed" => self.seed = line.parse(1)?,
+            "rng_version" => self.rng_version = line.parse(1)?,
+            "food_placement" => self.food_placement = FoodPlacement::from_name(line.field(1)?)
+                .ok_or_else(|| line.invalid())?,
+            "rules" => self.rules = Rules {
+                tail : TailRule::from_name(line.field(1)?).ok_or_else(|| line.invalid())?,
+                reversal : Reversal::from_name(line.field(2)?).ok_or_else(|| line.invalid())?,
+                walls : match line.fields.get(3) {
+                    Some(field) => Walls::from_name(field).ok_or_else(|| line.invalid())?,
+                    None => Walls::Solid
+                }
+            },
+            "rules_version" => self.rules_version = line.parse(1)?,
+            "limits" => self.limits = Limits { max_steps : limit(1)?, max_steps_without_food : limit(2)? },
+            "obstacles" => self.obstacles = line.fields[1..].iter()
+                .map(|field| {
+                    let (x, y) = field.split_once(',')?;
+                    Some(Vec2 { x : x.parse().ok()?, y : y.parse().ok()? })
+                })
+                .collect::<Option<Vec<Vec2>>>()
+                .ok_or_else(|| line.invalid())?,
+            "food" => self.food_pos = line.pos(1)?,
+            _ => return Ok(false)
+        }
+        Ok(true)
+    }
+
+    fn write_header(&self, f : &mut Formatter<'_>) -> std::fmt::Result{
+        writeln!(f, "world_size {} {}", self.world_size.0, self.world_size.1)?;
+        writeln!(f, "starting_pos {} {}", self.starting_pos.0, self.starting_pos.1)?;
+        writeln!(f, "starting_direction {}", self.starting_direction)?;
+        writeln!(f, "topology {}", self.topology)?;
+        writeln!(f, "seed {}", self.seed)?;
+        writeln!(f, "rng_version {}", self.rng_version)?;
+        writeln!(f, "food_placement {}", self.food_placement.name())?;
+        let limit = |limit : Option<usize>| limit.map_or("none".to_string(), |limit| limit.to_string());
+        writeln!(f, "rules {} {} {}", self.rules.tail.name(), self.rules.reversal.name(), self.rules.walls.name())?;
+        if self.rules_version != LEGACY_RULES_VERSION{
+            writeln!(f, "rules_version {}", self.rules_version)?;
+        }
+        writeln!(f, "limits {} {}", limit(self.limits.max_steps), limit(self.limits.max_steps_without_food))?;
+        write!(f, "obstacles")?;
+        for obstacle in &self.obstacles{
+            write!(f, " {},{}", obstacle.x, obstacle.y)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "food {} {}", self.food_pos.x, self.food_pos.y)
+    }
+}
+
+impl Display for Replay{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.write_text(f)
+    }
+}
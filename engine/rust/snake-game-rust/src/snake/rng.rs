@@ -0,0 +1,192 @@
+//! The random number generators owned by the engine
+//!
+//! Recorded games and seeds handed out by the `DataEngine` are only useful
+//! if the same seed always gives the same food sequence, so the engine does
+//! not rely on an external crate for its random numbers. Every algorithm is
+//! identified by a version number, which is stored with every run, and an
+//! algorithm is never changed once it has a version.
+//!
+//! * version 1 - WyRand, bit for bit the generator of `fastrand` 1.9.0 on
+//!   64-bit platforms, used by every game recorded before the versioning
+//! * version 2 - PCG32 (XSH RR with a 64-bit state), the default for new engines
+//!
+//! Both versions draw bounded integers with the same method, Lemire's
+//! multiply and reject on 64-bit numbers, and floats from the top 52 bits
+//! of a 64-bit number, so they only differ in the stream of raw numbers.
+
+use std::ops::Range;
+
+/// WyRand, compatible with `fastrand` 1.9.0
+pub const LEGACY_RNG_VERSION : u32 = 1;
+
+/// PCG32, used by default
+pub const CURRENT_RNG_VERSION : u32 = 2;
+
+/// every version the engine can replay
+pub const RNG_VERSIONS : [u32;2] = [LEGACY_RNG_VERSION, CURRENT_RNG_VERSION];
+
+const PCG_MULTIPLIER : u64 = 6364136223846793005;
+const PCG_INCREMENT : u64 = 1442695040888963407;
+
+/// SplitMix64, returns the next number and advances the state
+///
+/// Not an engine generator, used for the fixed tables and schedules
+/// that have to be stable, like the Zobrist keys.
+pub fn splitmix64(state : &mut u64) -> u64{
+    *state = state.wrapping_add(0x9E3779B97F4A7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+/// a versioned random number generator with a 64-bit state
+///
+/// # Examples
+///
+/// ```
+/// use snake_game_rust::snake::rng::*;
+///
+/// let mut rng = EngineRng::new(CURRENT_RNG_VERSION, 42);
+/// let a = rng.usize(0..10);
+///
+/// let mut same = EngineRng::new(CURRENT_RNG_VERSION, 42);
+/// assert_eq!(same.usize(0..10), a);
+///
+/// // the state can be saved and restored
+/// let state = rng.state();
+/// let next = rng.u64();
+/// assert_eq!(EngineRng::from_state(CURRENT_RNG_VERSION, state).u64(), next);
+/// ```
+#[derive(Clone,PartialEq,Eq,Debug)]
+pub struct EngineRng{
+    version : u32,
+    state : u64
+}
+
+impl EngineRng {
+
+    /// creates a generator of the given version initialized with a seed
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the version is not one of [`RNG_VERSIONS`]
+    pub fn new(version : u32, seed : u64) -> EngineRng{
+        let mut rng = EngineRng::from_state(version, 0);
+        rng.seed(seed);
+        rng
+    }
+
+    /// creates a generator of the given version with its raw state,
+    /// as returned by [`EngineRng::state`]
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the version is not one of [`RNG_VERSIONS`]
+    pub fn from_state(version : u32, state : u64) -> EngineRng{
+        if !RNG_VERSIONS.contains(&version){
+            panic!("Unknown rng version {version}, expected one of {RNG_VERSIONS:?}");
+        }
+        EngineRng { version, state }
+    }
+
+    /// creates a generator seeded from the system time, for games that
+    /// do not need to be reproduced
+    pub fn from_entropy(version : u32) -> EngineRng{
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_nanos() as u64)
+            .unwrap_or(0x4d595df4d0f33173);
+        let mut state = nanos;
+        EngineRng::new(version, splitmix64(&mut state))
+    }
+
+    /// initializes the generator with a seed
+    ///
+    /// Version 1 uses the seed as the state, version 2 follows the
+    /// PCG reference seeding, `state = 0`, advance, `state += seed`, advance.
+    pub fn seed(&mut self, seed : u64){
+        match self.version {
+            LEGACY_RNG_VERSION => self.state = seed,
+            _ => {
+                self.state = 0;
+                self.pcg_advance();
+                self.state = self.state.wrapping_add(seed);
+                self.pcg_advance();
+            }
+        }
+    }
+
+    pub fn version(&self) -> u32{
+        self.version
+    }
+
+    /// the raw state, restoring it with [`EngineRng::from_state`]
+    /// continues the same stream
+    pub fn state(&self) -> u64{
+        self.state
+    }
+
+    fn pcg_advance(&mut self){
+        self.state = self.state.wrapping_mul(PCG_MULTIPLIER).wrapping_add(PCG_INCREMENT);
+    }
+
+    fn pcg_u32(&mut self) -> u32{
+        let old = self.state;
+        self.pcg_advance();
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rot = (old >> 59) as u32;
+        xorshifted.rotate_right(rot)
+    }
+
+    /// a random `u64`
+    pub fn u64(&mut self) -> u64{
+        match self.version {
+            LEGACY_RNG_VERSION => {
+                self.state = self.state.wrapping_add(0xA0761D6478BD642F);
+                let t = u128::from(self.state) * u128::from(self.state ^ 0xE7037ED1A0B428DB);
+                (t as u64) ^ (t >> 64) as u64
+            },
+            _ => {
+                let high = self.pcg_u32() as u64;
+                let low = self.pcg_u32() as u64;
+                (high << 32) | low
+            }
+        }
+    }
+
+    /// a random `u64` in `0..n`, Lemire's multiply and reject
+    fn u64_below(&mut self, n : u64) -> u64{
+        let mut r = self.u64();
+        let mut hi = ((r as u128 * n as u128) >> 64) as u64;
+        let mut lo = r.wrapping_mul(n);
+        if lo < n {
+            let t = n.wrapping_neg() % n;
+            while lo < t {
+                r = self.u64();
+                hi = ((r as u128 * n as u128) >> 64) as u64;
+                lo = r.wrapping_mul(n);
+            }
+        }
+        hi
+    }
+
+    /// a random `usize` in the range
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the range is empty
+    pub fn usize(&mut self, range : Range<usize>) -> usize{
+        if range.is_empty(){
+            panic!("empty range: {range:?}");
+        }
+        range.start + self.u64_below((range.end - range.start) as u64) as usize
+    }
+
+    /// a random `f64` in `[0, 1)`
+    pub fn f64(&mut self) -> f64{
+        let b = 64;
+        let f = f64::MANTISSA_DIGITS - 1;
+        f64::from_bits((1 << (b - 2)) - (1 << f) + (self.u64() >> (b - f))) - 1.0
+    }
+}
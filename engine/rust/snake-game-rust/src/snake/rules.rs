@@ -0,0 +1,281 @@
+use crate::snake::GameEngine;
+use crate::snake::data_types::*;
+
+/// the rules of the engines before the cell the head chased the tail into
+/// was kept taken, the games recorded up to the `-v0` presets list the
+/// cell as free and can spawn the food under the head
+pub const LEGACY_RULES_VERSION : u32 = 1;
+
+/// the rules version of new engines
+pub const CURRENT_RULES_VERSION : u32 = 2;
+
+/// every rules version the engine can play, see [`GameEngine::set_rules_version`]
+pub const RULES_VERSIONS : [u32;2] = [LEGACY_RULES_VERSION, CURRENT_RULES_VERSION];
+
+/// whether the head may move into the cell the tail is leaving
+///
+/// * `Chase` - the tail moves away in the same step, so the cell is free,
+///   unless the snake eats on that step and its tail stays in place
+/// * `Block` - every cell of the body blocks the head, including the tail
+#[derive(Clone,Copy,Default,PartialEq,Eq,Debug)]
+pub enum TailRule{
+    #[default]
+    Chase,
+    Block
+}
+
+/// what happens when the snake is told to move straight back,
+/// only possible with absolute directions, see [`GameEngine::step_direction`]
+///
+/// * `Ignore` - the move is ignored and the snake keeps going forward
+/// * `Collide` - the head moves back into the neck and the game ends
+///   with a `"body"` collision
+#[derive(Clone,Copy,Default,PartialEq,Eq,Debug)]
+pub enum Reversal{
+    #[default]
+    Ignore,
+    Collide
+}
+
+/// what happens when the head leaves the world
+///
+/// * `Solid` - the snake dies on the wall
+/// * `Wrap` - the head enters the world again on the opposite side,
+///   hexagonal grids need an even number of rows to wrap around
+#[derive(Clone,Copy,Default,PartialEq,Eq,Debug)]
+pub enum Walls{
+    #[default]
+    Solid,
+    Wrap
+}
+
+/// the collision rules of the engine
+///
+/// The two presets match the common conventions of snake benchmarks:
+///
+/// * [`Rules::classic`] - the default, chasing the tail is legal and moving
+///   back is ignored, as in most playable snake games
+/// * [`Rules::strict`] - the tail blocks and moving back kills the snake,
+///   as in environments that treat every body cell as an obstacle
+///
+/// Both have solid walls, wrapping worlds set `walls` to [`Walls::Wrap`].
+///
+/// # Examples
+///
+/// ```
+/// use snake_game_rust::snake::*;
+/// use snake_game_rust::snake::rules::Rules;
+///
+/// let mut game = GameEngine::new((10,10), (5,5), 1);
+/// game.set_rules(Rules::strict());
+///
+/// // facing up, moving down runs into the neck
+/// let outcome = game.step_direction(3);
+/// assert!(outcome.done);
+/// assert_eq!(outcome.msg, "body");
+///
+/// game.set_rules(Rules::classic());
+/// game.reset(0);
+/// let outcome = game.step_direction(3);
+/// assert!(!outcome.done);
+/// assert_eq!(game.get_direction(), 1);
+/// ```
+#[derive(Clone,Copy,Default,PartialEq,Eq,Debug)]
+pub struct Rules{
+    pub tail : TailRule,
+    pub reversal : Reversal,
+    pub walls : Walls
+}
+
+impl Rules {
+
+    pub fn classic() -> Rules{
+        Rules { tail : TailRule::Chase, reversal : Reversal::Ignore, walls : Walls::Solid }
+    }
+
+    pub fn strict() -> Rules{
+        Rules { tail : TailRule::Block, reversal : Reversal::Collide, walls : Walls::Solid }
+    }
+}
+
+impl TailRule {
+
+    pub fn name(&self) -> &'static str{
+        match self {
+            TailRule::Chase => "chase",
+            TailRule::Block => "block"
+        }
+    }
+
+    /// the rule with the given name, `"chase"` or `"block"`
+    pub fn from_name(name : &str) -> Option<TailRule>{
+        match name {
+            "chase" => Some(TailRule::Chase),
+            "block" => Some(TailRule::Block),
+            _ => None
+        }
+    }
+}
+
+impl Reversal {
+
+    pub fn name(&self) -> &'static str{
+        match self {
+            Reversal::Ignore => "ignore",
+            Reversal::Collide => "collide"
+        }
+    }
+
+    /// the rule with the given name, `"ignore"` or `"collide"`
+    pub fn from_name(name : &str) -> Option<Reversal>{
+        match name {
+            "ignore" => Some(Reversal::Ignore),
+            "collide" => Some(Reversal::Collide),
+            _ => None
+        }
+    }
+}
+
+impl Walls {
+
+    pub fn name(&self) -> &'static str{
+        match self {
+            Walls::Solid => "solid",
+            Walls::Wrap => "wrap"
+        }
+    }
+
+    /// moves a cell that left the world back into it when the walls wrap
+    pub fn apply(&self, cell : Vec2, world_size : (usize,usize)) -> Vec2{
+        match self {
+            Walls::Solid => cell,
+            Walls::Wrap => Vec2 {
+                x : cell.x.rem_euclid(world_size.0 as i32),
+                y : cell.y.rem_euclid(world_size.1 as i32)
+            }
+        }
+    }
+
+    /// the rule with the given name, `"solid"` or `"wrap"`
+    pub fn from_name(name : &str) -> Option<Walls>{
+        match name {
+            "solid" => Some(Walls::Solid),
+            "wrap" => Some(Walls::Wrap),
+            _ => None
+        }
+    }
+}
+
+impl GameEngine {
+
+    /// sets the collision rules, takes effect from the next step
+    pub fn set_rules(&mut self, rules : Rules){
+        self.rules = rules;
+    }
+
+    pub fn get_rules(&self) -> Rules{
+        self.rules
+    }
+
+    /// selects the version of the engine rules, set the version a game was
+    /// recorded with to replay it, takes effect from the next step
+    ///
+    /// Fixes of the game rules that change recorded games get a new
+    /// version, the old behaviour stays available under the old one.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the version is not one of the [`RULES_VERSIONS`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snake_game_rust::snake::*;
+    /// use snake_game_rust::snake::rules::{LEGACY_RULES_VERSION, CURRENT_RULES_VERSION};
+    ///
+    /// let mut game = GameEngine::new((10,10), (5,5), 1);
+    /// assert_eq!(game.get_rules_version(), CURRENT_RULES_VERSION);
+    ///
+    /// game.set_rules_version(LEGACY_RULES_VERSION);
+    /// assert_eq!(game.get_rules_version(), LEGACY_RULES_VERSION);
+    /// ```
+    pub fn set_rules_version(&mut self, version : u32){
+        if !RULES_VERSIONS.contains(&version){
+            panic!("Unknown rules version {version}, expected one of {RULES_VERSIONS:?}");
+        }
+        self.rules_version = version;
+    }
+
+    pub fn get_rules_version(&self) -> u32{
+        self.rules_version
+    }
+
+    /// the cell reached by moving one cell from `pos` in a direction,
+    /// wrapped around the world under [`Walls::Wrap`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snake_game_rust::snake::*;
+    /// use snake_game_rust::snake::data_types::Vec2;
+    /// use snake_game_rust::snake::rules::{Rules, Walls};
+    ///
+    /// let mut game = GameEngine::new((10,10), (5,5), 1);
+    /// assert_eq!(game.next_cell(Vec2{ x : 0, y : 3 }, 1), Vec2{ x : -1, y : 3 });
+    ///
+    /// game.set_rules(Rules { walls : Walls::Wrap, ..Rules::classic() });
+    /// assert_eq!(game.next_cell(Vec2{ x : 0, y : 3 }, 1), Vec2{ x : 9, y : 3 });
+    /// ```
+    pub fn next_cell(&self, pos : Vec2, direction : usize) -> Vec2{
+        self.rules.walls.apply(self.topology.advance(pos, direction), self.world_size)
+    }
+
+    /// returns true if the head, already moved to its new cell, collides with
+    /// the body
+    ///
+    /// The tail is only left out under [`TailRule::Chase`] when the snake
+    /// does not eat on this step, as the tail stays in place while it grows.
+    pub(crate) fn hits_body(&self) -> bool{
+
+        let body = &self.snake.snake_body;
+        let head = self.snake.snake_head;
+        let eats = head == self.food_pos && self.free_space.contains(&head);
+        let tail_moves = self.rules.tail == TailRule::Chase && !eats;
+        let end = if tail_moves { body.len() - 1 } else { body.len() };
+
+        body[1..end].contains(&head)
+    }
+
+    /// advances the game by one step in an absolute direction instead of a
+    /// relative action
+    ///
+    /// Directions reached by one of the actions are played as that action,
+    /// with the action noise applied. Directions no action reaches, like
+    /// straight back on the square grid, follow the [`Reversal`] rule, with
+    /// `Collide` the snake moves that way without noise and
+    /// `executed_action` is the number of actions, as no action was executed.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the direction does not exist in the topology
+    pub fn step_direction(&mut self, direction : usize) -> StepOutcome{
+
+        if direction >= self.topology.num_directions() {
+            panic!("Expected direction to be smaller than {}, got {direction} instead", self.topology.num_directions());
+        }
+
+        let action = (0..self.topology.num_actions())
+            .find(|action| self.topology.turn(self.snake.direction, *action) == direction);
+
+        match (action, self.rules.reversal) {
+            (Some(action), _) => self.step_outcome(action),
+            (None, Reversal::Ignore) => self.step_outcome(self.topology.forward_action()),
+            (None, Reversal::Collide) => {
+                self.push_history();
+                let mut outcome = self.advance(direction);
+                outcome.executed_action = self.topology.num_actions();
+                outcome
+            }
+        }
+    }
+}
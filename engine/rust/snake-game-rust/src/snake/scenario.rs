@@ -0,0 +1,372 @@
+//! Random mid-game states
+//!
+//! Early episodes end long before the snake grows, so an agent trained from
+//! the start rarely sees a long snake. The [`ScenarioGenerator`] builds valid
+//! states with a body of a requested length, a direction and food, loaded
+//! into an engine with [`GameEngine::from_parts`]. Resetting the engine starts
+//! again from the same body and direction, with new food.
+//!
+//! The body is built by a self-avoiding random walk, which covers shapes no
+//! player would make, or by playing the [`GreedyPolicy`] until the snake
+//! is long enough, which gives the shapes of real games.
+//!
+//! [`GameEngine::path_to_food`] finds the shortest way to the food, so the
+//! scenarios can come with a known solvability, useful for benchmarks of hard
+//! positions. The search is only run when asked for with
+//! [`ScenarioGenerator::with_steps_to_food`] or
+//! [`ScenarioGenerator::with_solvable_only`].
+//!
+//! # Examples
+//!
+//! ```
+//! use snake_game_rust::snake::scenario::{ScenarioGenerator, BodyMethod};
+//!
+//! let generator = ScenarioGenerator::new((10,10), 30)
+//!     .with_method(BodyMethod::RandomWalk)
+//!     .with_steps_to_food(true);
+//! let scenario = generator.generate(7).unwrap();
+//! assert_eq!(scenario.body.len(), 30);
+//! assert_eq!(generator.generate(7).unwrap(), scenario);
+//!
+//! let mut game = scenario.engine();
+//! assert_eq!(game.get_snake_head(), &scenario.body[0]);
+//! if let Some(steps) = scenario.steps_to_food{
+//!     let path = game.path_to_food().unwrap();
+//!     assert_eq!(path.len(), steps);
+//!     for action in path{
+//!         game.step(action);
+//!     }
+//!     assert_eq!(game.snake.snake_body.len(), 31);
+//! }
+//! ```
+
+use std::sync::Arc;
+use std::collections::{HashMap, VecDeque};
+use crate::snake::GameEngine;
+use crate::snake::data_types::*;
+use crate::snake::errors::EngineError;
+use crate::snake::topology::{self, Topology, SquareGrid};
+use crate::snake::rng::{EngineRng, CURRENT_RNG_VERSION};
+use crate::snake::rules::TailRule;
+use crate::snake::policy::{Policy, GreedyPolicy};
+
+/// the number of fresh starts before the generator gives up
+const ATTEMPTS : usize = 100;
+
+/// how the body of a scenario is built
+#[derive(Clone,Copy,Default,PartialEq,Eq,Debug)]
+pub enum BodyMethod{
+    /// a self-avoiding random walk from a random head, backtracking when stuck
+    #[default]
+    RandomWalk,
+    /// the body of a [`GreedyPolicy`] game once the snake is long enough
+    Solver
+}
+
+impl BodyMethod {
+
+    pub fn name(&self) -> &'static str{
+        match self {
+            BodyMethod::RandomWalk => "random_walk",
+            BodyMethod::Solver => "solver"
+        }
+    }
+
+    pub fn from_name(name : &str) -> Option<BodyMethod>{
+        match name {
+            "random_walk" => Some(BodyMethod::RandomWalk),
+            "solver" => Some(BodyMethod::Solver),
+            _ => None
+        }
+    }
+}
+
+/// a generated state, see [`ScenarioGenerator::generate`]
+#[derive(Clone,PartialEq,Eq,Debug)]
+pub struct Scenario{
+    pub world_size : (usize,usize),
+    pub body : Vec<Vec2>,
+    pub direction : usize,
+    pub food_pos : Vec2,
+    pub seed : u64,
+    /// the fewest steps that reach the food, `None` if no actions reach it
+    /// or the generator did not search for them
+    pub steps_to_food : Option<usize>,
+    pub topology : &'static str
+}
+
+impl Scenario {
+
+    /// an engine starting from the scenario, its generator continues
+    /// from the seed of the scenario
+    pub fn engine(&self) -> GameEngine{
+        let topology = match topology::from_name(self.topology) {
+            Some(topology) => topology,
+            None => panic!("Unknown topology {}", self.topology)
+        };
+        match GameEngine::from_parts_with_topology(self.world_size, self.body.clone(), self.direction, Some(self.food_pos), self.seed, topology) {
+            Ok(engine) => engine,
+            Err(err) => panic!("Generated an invalid scenario, {err}")
+        }
+    }
+}
+
+/// builds random mid-game states with a body of a given length
+///
+/// Generation is seeded, the same generator and seed give the same scenario.
+#[derive(Clone)]
+pub struct ScenarioGenerator{
+    pub world_size : (usize,usize),
+    pub length : usize,
+    pub method : BodyMethod,
+    pub topology : Arc<dyn Topology>,
+    /// only keep scenarios where the food can be reached
+    pub solvable_only : bool,
+    /// search for the fewest steps to the food of every scenario
+    pub steps_to_food : bool,
+    pub rng_version : u32
+}
+
+impl ScenarioGenerator {
+
+    /// random walk bodies of `length` cells on a square grid
+    pub fn new(world_size : (usize,usize), length : usize) -> ScenarioGenerator{
+        ScenarioGenerator {
+            world_size,
+            length,
+            method : BodyMethod::default(),
+            topology : Arc::new(SquareGrid),
+            solvable_only : false,
+            steps_to_food : false,
+            rng_version : CURRENT_RNG_VERSION
+        }
+    }
+
+    pub fn with_method(mut self, method : BodyMethod) -> ScenarioGenerator{
+        self.method = method;
+        self
+    }
+
+    pub fn with_topology(mut self, topology : Arc<dyn Topology>) -> ScenarioGenerator{
+        self.topology = topology;
+        self
+    }
+
+    pub fn with_solvable_only(mut self, solvable_only : bool) -> ScenarioGenerator{
+        self.solvable_only = solvable_only;
+        self
+    }
+
+    pub fn with_steps_to_food(mut self, steps_to_food : bool) -> ScenarioGenerator{
+        self.steps_to_food = steps_to_food;
+        self
+    }
+
+    /// generates the scenario of a seed
+    ///
+    /// Returns [`EngineError::NoScenario`] when the body does not fit into
+    /// the world or no scenario was found within a bounded number of
+    /// attempts, which happens for bodies filling most of the world and
+    /// for solver bodies longer than the greedy player grows.
+    pub fn generate(&self, seed : u64) -> Result<Scenario,EngineError>{
+
+        let (rows, cols) = self.world_size;
+        if rows == 0 || cols == 0{
+            return Err(EngineError::ZeroWorldSize);
+        }
+        if self.length == 0{
+            return Err(EngineError::EmptyBody);
+        }
+        //there has to be room for the food
+        if self.length >= rows*cols{
+            return Err(EngineError::NoScenario(format!("a body of {} cells leaves no room for food in a {rows}x{cols} world", self.length)));
+        }
+
+        let mut rng = EngineRng::new(self.rng_version, seed);
+        for _ in 0..ATTEMPTS{
+            let engine = match self.method {
+                BodyMethod::RandomWalk => self.random_walk(&mut rng).and_then(|body| self.place(body, seed, &mut rng)),
+                BodyMethod::Solver => self.solver_game(rng.u64())
+            };
+            let Some(engine) = engine else { continue };
+
+            let steps_to_food = match self.steps_to_food || self.solvable_only {
+                true => engine.path_to_food().map(|path| path.len()),
+                false => None
+            };
+            if self.solvable_only && steps_to_food.is_none(){
+                continue;
+            }
+            return Ok(Scenario {
+                world_size : self.world_size,
+                body : engine.snake.snake_body.clone(),
+                direction : engine.snake.direction,
+                food_pos : engine.food_pos,
+                seed,
+                steps_to_food,
+                topology : self.topology.name()
+            });
+        }
+        Err(EngineError::NoScenario(format!("no {} body of {} cells found in {ATTEMPTS} attempts", self.method.name(), self.length)))
+    }
+
+    /// a self-avoiding walk of `length` cells, listed from the head,
+    /// or None when the search ran out of its budget
+    fn random_walk(&self, rng : &mut EngineRng) -> Option<Vec<Vec2>>{
+
+        let (rows, cols) = self.world_size;
+        let head = Vec2 { x : rng.usize(0..rows) as i32, y : rng.usize(0..cols) as i32 };
+
+        let mut taken = vec![false; rows*cols];
+        let index = |cell : Vec2| cell.x as usize * cols + cell.y as usize;
+        taken[index(head)] = true;
+
+        //depth first with the untried neighbours of every segment
+        let mut body = vec![head];
+        let mut untried = vec![self.shuffled_neighbours(head, rng)];
+        let mut budget = 100*self.length;
+
+        while body.len() < self.length{
+            if budget == 0 || body.is_empty(){
+                return None;
+            }
+            budget -= 1;
+            match untried.last_mut().unwrap().pop() {
+                Some(cell) if !taken[index(cell)] => {
+                    taken[index(cell)] = true;
+                    body.push(cell);
+                    untried.push(self.shuffled_neighbours(cell, rng));
+                },
+                Some(_) => {},
+                None => {
+                    let dead_end = body.pop().unwrap();
+                    taken[index(dead_end)] = false;
+                    untried.pop();
+                }
+            }
+        }
+        Some(body)
+    }
+
+    fn shuffled_neighbours(&self, pos : Vec2, rng : &mut EngineRng) -> Vec<Vec2>{
+        let mut neighbours = self.topology.neighbours(pos, self.world_size);
+        for i in (1..neighbours.len()).rev(){
+            neighbours.swap(i, rng.usize(0..i+1));
+        }
+        neighbours
+    }
+
+    /// loads a body with a random direction that does not face the neck,
+    /// the engine spawns the food
+    fn place(&self, body : Vec<Vec2>, seed : u64, rng : &mut EngineRng) -> Option<GameEngine>{
+        let directions : Vec<usize> = (0..self.topology.num_directions())
+            .filter(|d| body.len() < 2 || self.topology.advance(body[0], *d) != body[1])
+            .collect();
+        let direction = directions[rng.usize(0..directions.len())];
+        GameEngine::from_parts_with_topology(self.world_size, body, direction, None, seed, self.topology.clone()).ok()
+    }
+
+    /// plays a greedy game until the snake has `length` cells,
+    /// or None when it dies first
+    fn solver_game(&self, seed : u64) -> Option<GameEngine>{
+
+        let (rows, cols) = self.world_size;
+        let mut engine = GameEngine::with_topology(self.world_size, (rows/2, cols/2), 1, self.topology.clone());
+        engine.set_rng_version(self.rng_version);
+        engine.reset(seed);
+
+        //a snake that eats at least every world worth of steps
+        for _ in 0..self.length*rows*cols{
+            if engine.snake.snake_body.len() >= self.length{
+                return Some(engine);
+            }
+            if engine.step(GreedyPolicy.act(&engine)).0{
+                return None;
+            }
+        }
+        None
+    }
+}
+
+impl GameEngine {
+
+    /// the shortest list of actions that takes the head to the food,
+    /// or None when no actions reach it
+    ///
+    /// The search knows when every body segment moves away, so it passes
+    /// through cells the body leaves in time. It assumes that the world
+    /// stays as it is, without action noise, wandering food or new
+    /// obstacles, see [`crate::snake::dynamics`].
+    ///
+    /// Only the states the search reaches are stored, the memory grows with
+    /// the free cells around the head and not with the whole world.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snake_game_rust::snake::*;
+    /// use snake_game_rust::snake::data_types::Vec2;
+    ///
+    /// //the food lies behind the head, the snake has to turn around
+    /// let body = vec![Vec2{ x : 2, y : 2 }, Vec2{ x : 3, y : 2 }];
+    /// let game = GameEngine::from_parts((5,5), body, 1, Some(Vec2{ x : 4, y : 2 }), 0).unwrap();
+    /// assert_eq!(game.path_to_food().unwrap().len(), 4);
+    /// ```
+    pub fn path_to_food(&self) -> Option<Vec<usize>>{
+
+        if self.free_space.is_empty(){
+            return None;
+        }
+        let (rows, cols) = self.world_size;
+        let directions = self.topology.num_directions();
+        let body = &self.snake.snake_body;
+        let length = body.len();
+        let index = |cell : Vec2| cell.x as usize * cols + cell.y as usize;
+
+        //the first step the head can enter each cell, a segment leaves
+        //its cell once every segment behind it did
+        let blocking_tail = usize::from(self.rules.tail == TailRule::Block);
+        let mut free_from = vec![0; rows*cols];
+        for (i, segment) in body.iter().enumerate(){
+            free_from[index(*segment)] = length - i + blocking_tail;
+        }
+        for obstacle in &self.obstacles{
+            free_from[index(*obstacle)] = usize::MAX;
+        }
+
+        //after the whole body moved away the time no longer matters
+        let horizon = length + 1;
+        let state = |cell : Vec2, direction : usize, step : usize| (index(cell)*directions + direction)*(horizon + 1) + step.min(horizon);
+        let mut parent : HashMap<usize,(usize,usize)> = HashMap::new();
+
+        let mut queue = VecDeque::from([(self.snake.snake_head, self.snake.direction, 0)]);
+
+        while let Some((cell, direction, step)) = queue.pop_front(){
+            for action in 0..self.topology.num_actions(){
+                let next_direction = self.topology.turn(direction, action);
+                let next = self.next_cell(cell, next_direction);
+                if !self.in_world(next) || free_from[index(next)] > step + 1{
+                    continue;
+                }
+                let next_state = state(next, next_direction, step + 1);
+                if parent.contains_key(&next_state){
+                    continue;
+                }
+                parent.insert(next_state, (state(cell, direction, step), action));
+
+                if next == self.food_pos{
+                    let mut actions = vec![];
+                    let mut current = next_state;
+                    while let Some(&(previous, action)) = parent.get(&current){
+                        actions.push(action);
+                        current = previous;
+                    }
+                    actions.reverse();
+                    return Some(actions);
+                }
+                queue.push_back((next, next_direction, step + 1));
+            }
+        }
+        None
+    }
+}
@@ -1,8 +1,10 @@
 use crate::snake::data_types::*;
+use crate::snake::topology::Topology;
 
 
 /// a basic struct to hold some data from the GameEngine struct
 /// exists only for structural reasons
+#[derive(Clone)]
 pub struct Snake{
     pub snake_body : Vec<Vec2>,
     pub snake_head : Vec2,
@@ -11,29 +13,29 @@ pub struct Snake{
 
 impl Snake {
     
-    pub fn new(snake_head : Vec2, direction : usize) -> Self{
-
-        let delta_x = match direction {
-            1 => -1,
-            3 => 1,
-            _ => 0
-        };
-        let delta_y = match direction {
-            0 => -1,
-            2 => 1,
-            _ => 0
-            
-        };
-        let offset = Vec2{x : -delta_x, y: -delta_y };
+    /// creates a snake of length 3 with the head in `snake_head` and
+    /// the rest of the body placed behind it, with respect to the direction
+    /// and topology of the world
+    pub fn new(snake_head : Vec2, direction : usize, topology : &dyn Topology) -> Self{
+
+        let backwards = topology.opposite(direction);
+
+        let neck = topology.advance(snake_head, backwards);
+        let tail = topology.advance(neck, backwards);
 
         let snake_body = vec![
-            snake_head.clone(),
-            snake_head.clone() + offset.clone(),
-            snake_head.clone() + offset.clone().mul_by_i32(2)
+            snake_head,
+            neck,
+            tail
         ];
         
         Snake { snake_body : snake_body, snake_head: snake_head, direction: direction }
 
     }
 
-}
\ No newline at end of file
+    /// creates a snake from a body ordered from head to tail
+    pub fn from_body(snake_body : Vec<Vec2>, direction : usize) -> Self{
+        Snake { snake_head : snake_body[0], snake_body, direction }
+    }
+
+}
@@ -0,0 +1,359 @@
+//! A sparse engine for large boards
+//!
+//! [`GameEngine`](crate::snake::GameEngine) keeps a dense world matrix and
+//! a list of every free cell, so the memory and the cost of a step grow with
+//! the area of the board. [`SparseEngine`] only stores what is on the board:
+//!
+//! * the body as a ring buffer from head to tail, next to a hash set of the
+//!   covered cells for the collision checks
+//! * the food position, sampled by drawing random cells until one is free
+//!
+//! A step costs the same on a 10×10 and a 1000×1000 board. Drawing the food
+//! needs `W·H / free` tries on average, only once the snake covers most of
+//! the board the engine falls back to counting the free cells.
+//!
+//! The game is the classic one on the square grid, the tail moves out of
+//! the way of the head and the walls are solid, with the actions, messages
+//! and [`Limits`] of the dense engine. Agents observe a fixed size window
+//! around the head with [`SparseEngine::observe_viewport`], the grid
+//! observation of [`SnakeEnv`] builds the whole board and is meant for
+//! small boards and debugging.
+//!
+//! # Examples
+//!
+//! ```
+//! use snake_game_rust::snake::sparse::SparseEngine;
+//!
+//! let mut game = SparseEngine::new((1000,1000), (500,500), 1);
+//! game.reset(7);
+//!
+//! for _ in 0..100{
+//!     let (done, _, msg) = game.step(1);
+//!     assert!(!done, "{msg}");
+//! }
+//! assert_eq!(game.get_snake_head().x, 400);
+//! assert_eq!(game.observe_viewport(5).len(), 121);
+//! ```
+
+use std::collections::{HashSet, VecDeque};
+use crate::snake::data_types::*;
+use crate::snake::cell::Cell;
+use crate::snake::env::SnakeEnv;
+use crate::snake::limits::Limits;
+use crate::snake::observation::{self, ObservationType};
+use crate::snake::rng::{EngineRng, CURRENT_RNG_VERSION};
+use crate::snake::topology::{SquareGrid, Topology};
+
+/// the number of random cells drawn for the food before falling back
+/// to counting the free cells
+const FOOD_TRIES : usize = 64;
+
+/// the snake game on a board of any size, see the module documentation
+pub struct SparseEngine{
+    pub world_size : (usize,usize),
+    pub snake_body : VecDeque<Vec2>,
+    pub occupied : HashSet<Vec2>,
+    pub direction : usize,
+    pub starting_pos : (usize,usize),
+    pub starting_direction : usize,
+    pub food_pos : Option<Vec2>,
+    pub limits : Limits,
+    pub rng_generator : EngineRng,
+    pub rng_version : u32,
+    pub seed : u64,
+    pub steps : usize,
+    pub steps_since_food : usize
+}
+
+impl SparseEngine {
+
+    /// a new engine with a snake of length 3, the head at `starting_pos` and
+    /// the body behind it, reset with a random seed
+    ///
+    /// # Panics
+    ///
+    /// Will panic if one of the world dimensions is 0, if the direction is
+    /// not one of the 4 directions of the square grid or if the snake would
+    /// be spawned outside of the world
+    pub fn new(world_size : (usize,usize), starting_pos : (usize,usize), starting_direction : usize) -> SparseEngine{
+
+        if world_size.0 == 0 || world_size.1 == 0{
+            panic!("World size cannot be zero!")
+        }
+        if starting_direction >= SquareGrid.num_directions(){
+            panic!("Direction has to be smaller than {} on a square grid and not {starting_direction}", SquareGrid.num_directions())
+        }
+
+        let mut engine = SparseEngine {
+            world_size,
+            snake_body : VecDeque::new(),
+            occupied : HashSet::new(),
+            direction : starting_direction,
+            starting_pos,
+            starting_direction,
+            food_pos : None,
+            limits : Limits::none(),
+            rng_generator : EngineRng::from_entropy(CURRENT_RNG_VERSION),
+            rng_version : CURRENT_RNG_VERSION,
+            seed : 0,
+            steps : 0,
+            steps_since_food : 0
+        };
+        for segment in engine.starting_body(){
+            if !engine.in_world(segment){
+                panic!("The snake at {starting_pos:?} does not fit into the world of size {world_size:?}");
+            }
+        }
+        let seed = engine.rng_generator.u64();
+        engine.reset(seed);
+        engine
+    }
+
+    fn starting_body(&self) -> Vec<Vec2>{
+        let head = Vec2 { x : self.starting_pos.0 as i32, y : self.starting_pos.1 as i32 };
+        let backwards = SquareGrid.opposite(self.starting_direction);
+        let neck = SquareGrid.advance(head, backwards);
+        vec![head, neck, SquareGrid.advance(neck, backwards)]
+    }
+
+    fn in_world(&self, pos : Vec2) -> bool{
+        pos.x >= 0 && pos.y >= 0 && (pos.x as usize) < self.world_size.0 && (pos.y as usize) < self.world_size.1
+    }
+
+    /// the cell at a position, None outside of the world
+    pub fn cell_at(&self, pos : Vec2) -> Option<Cell>{
+        if !self.in_world(pos){
+            return None;
+        }
+        if self.snake_body.front() == Some(&pos){
+            Some(Cell::Head)
+        } else if self.occupied.contains(&pos){
+            Some(Cell::Body)
+        } else if self.food_pos == Some(pos){
+            Some(Cell::Food)
+        } else {
+            Some(Cell::Empty)
+        }
+    }
+
+    /// resets the world state given a seed, in time proportional to the
+    /// length of the snake and not the size of the board
+    pub fn reset(&mut self, seed : u64){
+        self.seed = seed;
+        self.steps = 0;
+        self.steps_since_food = 0;
+        self.rng_generator = EngineRng::new(self.rng_version, seed);
+        self.direction = self.starting_direction;
+        self.snake_body = self.starting_body().into();
+        self.occupied = self.snake_body.iter().copied().collect();
+        self.spawn_food();
+    }
+
+    /// selects the version of the random generator, see [`crate::snake::rng`],
+    /// takes effect on the next reset
+    pub fn set_rng_version(&mut self, version : u32){
+        self.rng_generator = EngineRng::new(version, self.seed);
+        self.rng_version = version;
+    }
+
+    /// sets the episode limits, takes effect from the next step
+    pub fn set_limits(&mut self, limits : Limits){
+        self.limits = limits;
+    }
+
+    pub fn get_limits(&self) -> Limits{
+        self.limits
+    }
+
+    /// places the food on a uniformly drawn free cell, or removes it when
+    /// the snake fills the board
+    fn spawn_food(&mut self){
+        let (rows, cols) = self.world_size;
+        let free = rows*cols - self.snake_body.len();
+        self.food_pos = None;
+        if free == 0{
+            return;
+        }
+        for _ in 0..FOOD_TRIES{
+            let cell = Vec2 { x : self.rng_generator.usize(0..rows) as i32, y : self.rng_generator.usize(0..cols) as i32 };
+            if !self.occupied.contains(&cell){
+                self.food_pos = Some(cell);
+                return;
+            }
+        }
+        //the snake covers most of the board, pick the n-th free cell instead
+        let n = self.rng_generator.usize(0..free);
+        self.food_pos = (0..rows*cols)
+            .map(|i| Vec2 { x : (i / cols) as i32, y : (i % cols) as i32 })
+            .filter(|cell| !self.occupied.contains(cell))
+            .nth(n);
+    }
+
+    /// Forwards the game by one iteration, returns `(done, food_eaten, msg)`
+    /// with the messages of [`crate::snake::GameEngine::step`]. The state
+    /// is left unchanged by the step that kills the snake.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the action is not 0 - turn left, 1 - go forward or 2 - turn right
+    pub fn step(&mut self, action : usize) -> (bool,bool,String){
+
+        if action >= SquareGrid.num_actions(){
+            panic!("Expected action to be smaller than {}, got {action} instead", SquareGrid.num_actions());
+        }
+        let direction = SquareGrid.turn(self.direction, action);
+        let head = SquareGrid.advance(self.snake_body[0], direction);
+
+        if !self.in_world(head){
+            return (true, false, "wall".to_string());
+        }
+        let eats = self.food_pos == Some(head);
+        //the tail moves away in the same step unless the snake grows
+        let tail = self.snake_body[self.snake_body.len() - 1];
+        if self.occupied.contains(&head) && (eats || head != tail){
+            return (true, false, "body".to_string());
+        }
+
+        self.direction = direction;
+        self.steps += 1;
+        if !eats{
+            self.snake_body.pop_back();
+            self.occupied.remove(&tail);
+        }
+        self.snake_body.push_front(head);
+        self.occupied.insert(head);
+
+        if eats{
+            self.steps_since_food = 0;
+            self.spawn_food();
+            //like the dense engine, the winning step reports no food eaten
+            if self.food_pos.is_none(){
+                return (true, false, "victory".to_string());
+            }
+        } else {
+            self.steps_since_food += 1;
+        }
+
+        if self.limits.max_steps.is_some_and(|max_steps| self.steps >= max_steps){
+            return (true, eats, "timeout".to_string());
+        }
+        if self.limits.max_steps_without_food.is_some_and(|max_steps| self.steps_since_food >= max_steps){
+            return (true, eats, "starved".to_string());
+        }
+        (false, eats, "alive".to_string())
+    }
+
+    pub fn get_snake_head(&self) -> &Vec2{
+        &self.snake_body[0]
+    }
+
+    /// the food, None once the snake fills the board
+    pub fn get_food_pos(&self) -> Option<&Vec2>{
+        self.food_pos.as_ref()
+    }
+
+    pub fn get_direction(&self) -> usize{
+        self.direction
+    }
+
+    pub fn get_score(&self) -> usize{
+        self.snake_body.len().saturating_sub(3)
+    }
+
+    /// the number of steps since the snake last ate, or since the reset
+    pub fn get_steps_since_food(&self) -> usize{
+        self.steps_since_food
+    }
+
+    /// builds the dense world matrix, takes time and memory proportional
+    /// to the size of the board
+    pub fn get_world(&self) -> Matrix{
+        let mut world = Matrix::zeros(self.world_size);
+        if let Some(food) = self.food_pos{
+            world[(food.x, food.y)] = Cell::Food.code();
+        }
+        for (i, segment) in self.snake_body.iter().enumerate(){
+            world[(segment.x, segment.y)] = if i == 0 { Cell::Head.code() } else { Cell::Body.code() };
+        }
+        world
+    }
+
+    /// encodes the cells around the head as an observation of
+    /// `(2*radius + 1)²` values, see [`observation::viewport`]
+    pub fn observe_viewport(&self, radius : usize) -> Vec<f32>{
+        observation::viewport(radius, self.snake_body[0], |pos| self.cell_at(pos))
+    }
+
+    /// encodes the current state as an observation, the 12 features of the
+    /// square grid are computed from the snake and the food alone, the grid
+    /// builds the dense world first
+    pub fn observe(&self, observation_type : ObservationType) -> Vec<f32>{
+        match observation_type {
+            ObservationType::Grid => self.get_world().matrix.iter().flatten().map(|cell| *cell as f32).collect(),
+            ObservationType::Features => observation::features(
+                self.snake_body[0],
+                self.direction,
+                self.food_pos,
+                &SquareGrid,
+                |pos| self.cell_at(pos)
+            )
+        }
+    }
+
+    /// the direction, seed, score and rng version, like
+    /// [`crate::snake::GameEngine::get_info_string`]
+    pub fn get_info_string(&self) -> String{
+        format!(",{},{},{},{}", self.direction, self.seed, self.get_score(), self.rng_version)
+    }
+
+    pub fn get_flattened_world(&self) -> String{
+        self.get_world().matrix.iter().flatten().map(|cell| cell.to_string() + ", ").collect()
+    }
+}
+
+impl SnakeEnv for SparseEngine {
+
+    fn num_actions(&self) -> usize{
+        SquareGrid.num_actions()
+    }
+
+    fn forward_action(&self) -> usize{
+        SquareGrid.forward_action()
+    }
+
+    fn reset(&mut self, seed : u64){
+        SparseEngine::reset(self, seed)
+    }
+
+    fn step(&mut self, action : usize) -> (bool,bool,String){
+        SparseEngine::step(self, action)
+    }
+
+    fn observe(&self, observation_type : ObservationType) -> Vec<f32>{
+        SparseEngine::observe(self, observation_type)
+    }
+
+    fn observation_size(&self, observation_type : ObservationType) -> usize{
+        observation_type.size(self.world_size, &SquareGrid)
+    }
+
+    fn world_shape(&self) -> Vec<usize>{
+        vec![self.world_size.0, self.world_size.1]
+    }
+
+    fn get_score(&self) -> usize{
+        SparseEngine::get_score(self)
+    }
+
+    fn get_seed(&self) -> u64{
+        self.seed
+    }
+
+    fn get_flattened_world(&self) -> String{
+        SparseEngine::get_flattened_world(self)
+    }
+
+    fn get_info_string(&self) -> String{
+        SparseEngine::get_info_string(self)
+    }
+}
@@ -0,0 +1,215 @@
+use crate::snake::data_types::*;
+use crate::snake::topology::Topology;
+
+/// the 8 symmetries of a square world grid, the dihedral group of the square
+///
+/// `Rot90` rotates clockwise, `FlipX` mirrors the rows (top becomes bottom),
+/// `FlipY` mirrors the columns (left becomes right) and `Transpose` and
+/// `AntiTranspose` mirror along the diagonals.
+///
+/// The symmetries that swap the axes turn an `n x m` world into an `m x n`
+/// one, so only the 4 others map a rectangular world onto itself.
+///
+/// Reflections turn left turns into right turns, so on the square grid
+/// the relative actions 0 and 2 swap.
+///
+/// # Examples
+///
+/// ```
+/// use snake_game_rust::snake::symmetry::Symmetry;
+/// use snake_game_rust::snake::topology::SquareGrid;
+/// use snake_game_rust::snake::data_types::*;
+///
+/// let mut world = Matrix::zeros((3,3));
+/// world[(0,1)] = 1;
+///
+/// let rotated = Symmetry::Rot90.apply_matrix(&world);
+/// assert_eq!(rotated[(1,2)], 1);
+/// assert_eq!(Symmetry::Rot90.apply_vec2(Vec2{ x : 0, y : 1 }, (3,3)), Vec2{ x : 1, y : 2 });
+///
+/// // facing up becomes facing right
+/// assert_eq!(Symmetry::Rot90.apply_direction(1, &SquareGrid), 2);
+/// // a left turn becomes a right turn in the mirror
+/// assert_eq!(Symmetry::FlipY.apply_action(0, &SquareGrid), 2);
+/// assert_eq!(Symmetry::Rot90.apply_action(0, &SquareGrid), 0);
+/// ```
+#[derive(Clone,Copy,PartialEq,Eq,Debug,Hash)]
+pub enum Symmetry{
+    Identity,
+    Rot90,
+    Rot180,
+    Rot270,
+    FlipX,
+    FlipY,
+    Transpose,
+    AntiTranspose
+}
+
+impl Symmetry {
+
+    pub const ALL : [Symmetry;8] = [
+        Symmetry::Identity,
+        Symmetry::Rot90,
+        Symmetry::Rot180,
+        Symmetry::Rot270,
+        Symmetry::FlipX,
+        Symmetry::FlipY,
+        Symmetry::Transpose,
+        Symmetry::AntiTranspose
+    ];
+
+    /// the symmetries that map a world of the given size onto itself
+    pub fn of_world(world_size : (usize,usize)) -> Vec<Symmetry>{
+        Symmetry::ALL.into_iter()
+            .filter(|symmetry| world_size.0 == world_size.1 || !symmetry.swaps_axes())
+            .collect()
+    }
+
+    /// true for the symmetries that exchange rows and columns
+    pub fn swaps_axes(&self) -> bool{
+        matches!(self, Symmetry::Rot90 | Symmetry::Rot270 | Symmetry::Transpose | Symmetry::AntiTranspose)
+    }
+
+    /// true for the mirror images, which swap left and right turns
+    pub fn is_reflection(&self) -> bool{
+        matches!(self, Symmetry::FlipX | Symmetry::FlipY | Symmetry::Transpose | Symmetry::AntiTranspose)
+    }
+
+    /// the symmetry undoing this one
+    pub fn inverse(&self) -> Symmetry{
+        match self {
+            Symmetry::Rot90 => Symmetry::Rot270,
+            Symmetry::Rot270 => Symmetry::Rot90,
+            other => *other
+        }
+    }
+
+    /// the size of a world of the given size after applying the symmetry
+    pub fn apply_size(&self, world_size : (usize,usize)) -> (usize,usize){
+        match self.swaps_axes() {
+            true => (world_size.1, world_size.0),
+            false => world_size
+        }
+    }
+
+    /// transforms an offset between two cells, for example a move of the snake
+    pub fn apply_offset(&self, v : Vec2) -> Vec2{
+        let (x, y) = (v.x, v.y);
+        let (x, y) = match self {
+            Symmetry::Identity => (x, y),
+            Symmetry::Rot90 => (y, -x),
+            Symmetry::Rot180 => (-x, -y),
+            Symmetry::Rot270 => (-y, x),
+            Symmetry::FlipX => (-x, y),
+            Symmetry::FlipY => (x, -y),
+            Symmetry::Transpose => (y, x),
+            Symmetry::AntiTranspose => (-y, -x)
+        };
+        Vec2{ x, y }
+    }
+
+    /// transforms the position of a cell in a world of the given size
+    pub fn apply_vec2(&self, p : Vec2, world_size : (usize,usize)) -> Vec2{
+        let max = Vec2{ x : world_size.0 as i32 - 1, y : world_size.1 as i32 - 1 };
+        let (x, y) = (p.x, p.y);
+        let (x, y) = match self {
+            Symmetry::Identity => (x, y),
+            Symmetry::Rot90 => (y, max.x - x),
+            Symmetry::Rot180 => (max.x - x, max.y - y),
+            Symmetry::Rot270 => (max.y - y, x),
+            Symmetry::FlipX => (max.x - x, y),
+            Symmetry::FlipY => (x, max.y - y),
+            Symmetry::Transpose => (y, x),
+            Symmetry::AntiTranspose => (max.y - y, max.x - x)
+        };
+        Vec2{ x, y }
+    }
+
+    /// transforms a whole world matrix
+    pub fn apply_matrix(&self, matrix : &Matrix) -> Matrix{
+        let mut transformed = Matrix::zeros(self.apply_size(matrix.size));
+        for x in 0..matrix.size.0{
+            for y in 0..matrix.size.1{
+                let p = self.apply_vec2(Vec2{ x : x as i32, y : y as i32 }, matrix.size);
+                transformed[(p.x, p.y)] = matrix[(x,y)];
+            }
+        }
+        transformed
+    }
+
+    /// transforms a direction of the given topology
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the topology has no direction matching the transformed
+    /// one, as is the case for rotations of a hexagonal grid.
+    pub fn apply_direction(&self, direction : usize, topology : &dyn Topology) -> usize{
+        let origin = Vec2{ x : 0, y : 0 };
+        let offset = self.apply_offset(topology.offset(origin, direction));
+        match (0..topology.num_directions()).find(|d| topology.offset(origin, *d) == offset) {
+            Some(d) => d,
+            None => panic!("The {} grid is not symmetric under {self:?}", topology.name())
+        }
+    }
+
+    /// transforms a relative action of the given topology,
+    /// reflections exchange left and right turns
+    pub fn apply_action(&self, action : usize, topology : &dyn Topology) -> usize{
+        if !self.is_reflection(){
+            return action;
+        }
+        let turn = -topology.turns()[action];
+        topology.turns().iter().position(|t| *t == turn).unwrap()
+    }
+
+    /// transforms every step of a recorded trajectory
+    pub fn apply_trajectory(&self, trajectory : &[RecordedStep], topology : &dyn Topology) -> Vec<RecordedStep>{
+        trajectory.iter()
+            .map(|step| RecordedStep {
+                world : self.apply_matrix(&step.world),
+                action : self.apply_action(step.action, topology),
+                direction : self.apply_direction(step.direction, topology),
+                seed : step.seed,
+                score : step.score,
+                rng_version : step.rng_version
+            })
+            .collect()
+    }
+}
+
+/// returns the trajectory under every symmetry of its world and topology,
+/// the first one being the original, which gives 8 trajectories on square
+/// worlds
+///
+/// Trajectories from hexagonal grids are only returned as they are.
+///
+/// # Examples
+///
+/// ```
+/// use snake_game_rust::snake::*;
+/// use snake_game_rust::snake::symmetry::augment;
+/// use snake_game_rust::snake::topology::SquareGrid;
+/// use snake_game_rust::snake::data_types::RecordedStep;
+///
+/// let mut game = GameEngine::new((10,10), (5,5), 1);
+/// game.reset(2);
+/// let mut trajectory = vec![];
+/// for action in [0, 1, 2]{
+///     game.step(action);
+///     let row = game.get_flattened_world() + &format!("{action}") + &game.get_info_string();
+///     trajectory.push(RecordedStep::from_row(&row, (10,10)).unwrap());
+/// }
+///
+/// let augmented = augment(&trajectory, &SquareGrid);
+/// assert_eq!(augmented.len(), 8);
+/// assert_eq!(augmented[0], trajectory);
+/// ```
+pub fn augment(trajectory : &[RecordedStep], topology : &dyn Topology) -> Vec<Vec<RecordedStep>>{
+    let world_size = match trajectory.first() {
+        Some(step) => step.world.size,
+        None => return vec![]
+    };
+    topology.symmetries(world_size).iter()
+        .map(|symmetry| symmetry.apply_trajectory(trajectory, topology))
+        .collect()
+}
@@ -0,0 +1,233 @@
+use std::sync::Arc;
+use crate::snake::data_types::*;
+use crate::snake::symmetry::Symmetry;
+
+/// returns the topology with the given name, `"square"`, `"hex"` or `"octile"`
+///
+/// # Examples
+///
+/// ```
+/// use snake_game_rust::snake::topology::from_name;
+///
+/// let hex = from_name("hex").unwrap();
+/// assert_eq!(hex.num_directions(), 6);
+/// assert!(from_name("triangle").is_none());
+/// ```
+pub fn from_name(name : &str) -> Option<Arc<dyn Topology>>{
+    match name {
+        "square" => Some(Arc::new(SquareGrid)),
+        "hex" => Some(Arc::new(HexGrid)),
+        "octile" => Some(Arc::new(OctileGrid)),
+        _ => None
+    }
+}
+
+/// describes how cells of the world grid are connected
+///
+/// Directions are numbered clockwise starting from left, the snake moves
+/// by one cell in its current direction every step, and the actions passed
+/// to `GameEngine::step` are relative turns listed by `turns`.
+///
+/// Coordinates follow the engine, `x` is the row and `y` is the column of
+/// the world matrix.
+pub trait Topology : Send + Sync {
+
+    /// a short name of the topology
+    fn name(&self) -> &'static str;
+
+    /// number of directions the snake can face
+    fn num_directions(&self) -> usize;
+
+    /// the relative turn, in directions, performed by each action
+    ///
+    /// The turn of action `i` is `turns()[i]`, negative values are left turns.
+    fn turns(&self) -> &'static [isize];
+
+    /// the offset of the neighbouring cell in a given direction
+    ///
+    /// The position is needed for grids in which the offset depends on the row.
+    fn offset(&self, pos : Vec2, direction : usize) -> Vec2;
+
+    /// number of actions accepted by the engine
+    fn num_actions(&self) -> usize{
+        self.turns().len()
+    }
+
+    /// the action that keeps the current direction
+    fn forward_action(&self) -> usize{
+        self.turns().iter().position(|turn| *turn == 0).unwrap()
+    }
+
+    /// the direction after performing an action
+    fn turn(&self, direction : usize, action : usize) -> usize{
+        let n = self.num_directions() as isize;
+        (direction as isize + self.turns()[action]).rem_euclid(n) as usize
+    }
+
+    /// the direction pointing back
+    fn opposite(&self, direction : usize) -> usize{
+        (direction + self.num_directions()/2) % self.num_directions()
+    }
+
+    /// the cell reached by moving one cell in a direction
+    fn advance(&self, pos : Vec2, direction : usize) -> Vec2{
+        pos + self.offset(pos, direction)
+    }
+
+    /// all neighbouring cells that lie inside the world grid, ordered by direction
+    fn neighbours(&self, pos : Vec2, world_size : (usize,usize)) -> Vec<Vec2>{
+        (0..self.num_directions())
+            .map(|direction| self.advance(pos, direction))
+            .filter(|cell| cell.x >= 0 && cell.y >= 0 && (cell.x as usize) < world_size.0 && (cell.y as usize) < world_size.1)
+            .collect()
+    }
+
+    /// the symmetries mapping a world of the given size and its directions
+    /// onto themselves, the identity first
+    ///
+    /// Grids whose offsets follow the square axes have all the symmetries
+    /// of [`Symmetry::of_world`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snake_game_rust::snake::topology::*;
+    ///
+    /// assert_eq!(SquareGrid.symmetries((10,10)).len(), 8);
+    /// assert_eq!(OctileGrid.symmetries((10,12)).len(), 4);
+    /// assert_eq!(HexGrid.symmetries((10,10)).len(), 1);
+    /// ```
+    fn symmetries(&self, world_size : (usize,usize)) -> Vec<Symmetry>{
+        Symmetry::of_world(world_size)
+    }
+}
+
+/// the standard square grid with 4 neighbours
+///
+/// * 0 - left
+/// * 1 - up
+/// * 2 - right
+/// * 3 - down
+///
+/// Actions are 0 - turn left, 1 - go forward, 2 - turn right
+#[derive(Clone,Copy,Debug,Default)]
+pub struct SquareGrid;
+
+impl Topology for SquareGrid {
+
+    fn name(&self) -> &'static str{
+        "square"
+    }
+
+    fn num_directions(&self) -> usize{
+        4
+    }
+
+    fn turns(&self) -> &'static [isize]{
+        &[-1, 0, 1]
+    }
+
+    fn offset(&self, _pos : Vec2, direction : usize) -> Vec2{
+        match direction {
+            0 => Vec2{x : 0, y : -1},
+            1 => Vec2{x : -1, y : 0},
+            2 => Vec2{x : 0, y : 1},
+            3 => Vec2{x : 1, y : 0},
+            _ => panic!("Direction {direction} does not exist on a square grid")
+        }
+    }
+}
+
+/// a hexagonal grid in "odd-r" offset coordinates, odd rows are shifted
+/// half a cell to the right
+///
+/// * 0 - left
+/// * 1 - up left
+/// * 2 - up right
+/// * 3 - right
+/// * 4 - down right
+/// * 5 - down left
+///
+/// Actions are 0 - turn 120° left, 1 - turn 60° left, 2 - go forward,
+/// 3 - turn 60° right, 4 - turn 120° right
+#[derive(Clone,Copy,Debug,Default)]
+pub struct HexGrid;
+
+impl Topology for HexGrid {
+
+    fn name(&self) -> &'static str{
+        "hex"
+    }
+
+    fn num_directions(&self) -> usize{
+        6
+    }
+
+    fn turns(&self) -> &'static [isize]{
+        &[-2, -1, 0, 1, 2]
+    }
+
+    fn offset(&self, pos : Vec2, direction : usize) -> Vec2{
+        let shift = pos.x.rem_euclid(2);
+        match direction {
+            0 => Vec2{x : 0, y : -1},
+            1 => Vec2{x : -1, y : shift - 1},
+            2 => Vec2{x : -1, y : shift},
+            3 => Vec2{x : 0, y : 1},
+            4 => Vec2{x : 1, y : shift},
+            5 => Vec2{x : 1, y : shift - 1},
+            _ => panic!("Direction {direction} does not exist on a hexagonal grid")
+        }
+    }
+
+    /// the shifted rows are not mapped onto each other by the symmetries of
+    /// the square, only the identity is left
+    fn symmetries(&self, _world_size : (usize,usize)) -> Vec<Symmetry>{
+        vec![Symmetry::Identity]
+    }
+}
+
+/// a square grid with 8 neighbours, diagonal moves are allowed
+///
+/// * 0 - left
+/// * 1 - up left
+/// * 2 - up
+/// * 3 - up right
+/// * 4 - right
+/// * 5 - down right
+/// * 6 - down
+/// * 7 - down left
+///
+/// Actions are 0 - turn 90° left, 1 - turn 45° left, 2 - go forward,
+/// 3 - turn 45° right, 4 - turn 90° right
+#[derive(Clone,Copy,Debug,Default)]
+pub struct OctileGrid;
+
+impl Topology for OctileGrid {
+
+    fn name(&self) -> &'static str{
+        "octile"
+    }
+
+    fn num_directions(&self) -> usize{
+        8
+    }
+
+    fn turns(&self) -> &'static [isize]{
+        &[-2, -1, 0, 1, 2]
+    }
+
+    fn offset(&self, _pos : Vec2, direction : usize) -> Vec2{
+        match direction {
+            0 => Vec2{x : 0, y : -1},
+            1 => Vec2{x : -1, y : -1},
+            2 => Vec2{x : -1, y : 0},
+            3 => Vec2{x : -1, y : 1},
+            4 => Vec2{x : 0, y : 1},
+            5 => Vec2{x : 1, y : 1},
+            6 => Vec2{x : 1, y : 0},
+            7 => Vec2{x : 1, y : -1},
+            _ => panic!("Direction {direction} does not exist on an 8-connected grid")
+        }
+    }
+}
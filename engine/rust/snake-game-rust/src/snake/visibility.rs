@@ -0,0 +1,216 @@
+use crate::snake::GameEngine;
+use crate::snake::data_types::*;
+use crate::snake::cell::Cell;
+use crate::snake::observation::{self, ObservationType};
+
+/// value of hidden cells in the masked world
+pub const HIDDEN_CELL : i32 = -1;
+
+/// values of the visibility mask
+///
+/// * 0 - hidden, the cell has never been seen
+/// * 1 - stale, the cell was seen before and the masked world holds
+///   its last seen value
+/// * 2 - visible
+pub const MASK_HIDDEN : i32 = 0;
+pub const MASK_STALE : i32 = 1;
+pub const MASK_VISIBLE : i32 = 2;
+
+/// the distance used for the view radius
+#[derive(Clone,Copy,PartialEq,Eq,Debug)]
+pub enum Metric{
+    Manhattan,
+    Euclidean
+}
+
+/// the visibility model of a partially observable game
+///
+/// * `radius` - the snake sees cells within this distance of its head
+/// * `metric` - the distance used for the radius
+/// * `line_of_sight` - if true, cells behind the snake body or obstacles
+///   are not visible
+///
+/// Distances are measured in grid coordinates for every topology.
+///
+/// # Examples
+///
+/// ```
+/// use snake_game_rust::snake::*;
+/// use snake_game_rust::snake::visibility::*;
+///
+/// let mut game = GameEngine::new((10,10), (5,5), 1);
+/// game.set_visibility(Some(Visibility { radius : 2, metric : Metric::Manhattan, line_of_sight : false }));
+///
+/// let world = game.get_masked_world();
+/// assert_eq!(world[(5,5)], 1);
+/// assert_eq!(world[(0,0)], HIDDEN_CELL);
+///
+/// // after moving away the cells are remembered but stale
+/// game.step(1);
+/// game.step(1);
+/// game.step(1);
+/// let mask = game.get_visibility_mask();
+/// assert_eq!(mask[(7,5)], MASK_STALE);
+/// assert_eq!(mask[(2,5)], MASK_VISIBLE);
+/// ```
+#[derive(Clone,Copy,PartialEq,Eq,Debug)]
+pub struct Visibility{
+    pub radius : usize,
+    pub metric : Metric,
+    pub line_of_sight : bool
+}
+
+/// the state of the fog of war, what the snake has seen so far
+#[derive(Clone)]
+pub struct FogOfWar{
+    pub visibility : Visibility,
+    pub memory : Matrix,
+    pub mask : Matrix
+}
+
+impl Visibility {
+
+    fn in_radius(&self, from : Vec2, to : Vec2) -> bool{
+        let dx = (from.x - to.x).unsigned_abs() as usize;
+        let dy = (from.y - to.y).unsigned_abs() as usize;
+        match self.metric {
+            Metric::Manhattan => dx + dy <= self.radius,
+            Metric::Euclidean => dx*dx + dy*dy <= self.radius*self.radius
+        }
+    }
+
+    /// checks the cells strictly between `from` and `to` on a Bresenham line
+    fn is_line_clear(world : &Matrix, from : Vec2, to : Vec2) -> bool{
+
+        let dx = (to.x - from.x).abs();
+        let dy = -(to.y - from.y).abs();
+        let sx = (to.x - from.x).signum();
+        let sy = (to.y - from.y).signum();
+        let mut err = dx + dy;
+        let mut cell = from;
+
+        loop {
+            let e2 = 2*err;
+            if e2 >= dy {
+                err += dy;
+                cell.x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                cell.y += sy;
+            }
+            if cell == to {
+                return true;
+            }
+            if Cell::from_code(world[(cell.x, cell.y)]).is_some_and(|cell| cell.is_blocking()){
+                return false;
+            }
+        }
+    }
+
+    /// returns true if the cell `to` is visible from the snake head at `from`
+    pub fn is_visible(&self, world : &Matrix, from : Vec2, to : Vec2) -> bool{
+        if !self.in_radius(from, to){
+            return false;
+        }
+        !self.line_of_sight || from == to || Visibility::is_line_clear(world, from, to)
+    }
+}
+
+impl GameEngine {
+
+    /// enables the fog of war with the given visibility model,
+    /// or disables it when `None` is given
+    ///
+    /// The memory of seen cells is cleared, and it is also cleared on every reset.
+    pub fn set_visibility(&mut self, visibility : Option<Visibility>){
+        self.fog = visibility.map(|visibility| FogOfWar {
+            visibility,
+            memory : Matrix::zeros(self.world_size),
+            mask : Matrix::zeros(self.world_size)
+        });
+        self.update_fog();
+    }
+
+    /// updates what the snake sees from the current head position
+    pub(crate) fn update_fog(&mut self){
+
+        let Some(fog) = &mut self.fog else {
+            return;
+        };
+
+        let head = self.snake.snake_head;
+        let head_in_world = head.x >= 0 && head.y >= 0
+            && (head.x as usize) < self.world_size.0 && (head.y as usize) < self.world_size.1;
+
+        for x in 0..self.world_size.0{
+            for y in 0..self.world_size.1{
+                let cell = Vec2{ x : x as i32, y : y as i32 };
+                if head_in_world && fog.visibility.is_visible(&self.game_world, head, cell){
+                    fog.memory[(x,y)] = self.game_world[(x,y)];
+                    fog.mask[(x,y)] = MASK_VISIBLE;
+                } else if fog.mask[(x,y)] == MASK_VISIBLE{
+                    fog.mask[(x,y)] = MASK_STALE;
+                }
+            }
+        }
+    }
+
+    /// clears the memory of the fog of war, used on reset
+    pub(crate) fn reset_fog(&mut self){
+        if let Some(fog) = &mut self.fog{
+            fog.memory = Matrix::zeros(self.world_size);
+            fog.mask = Matrix::zeros(self.world_size);
+        }
+        self.update_fog();
+    }
+
+    /// returns the world as seen by the snake
+    ///
+    /// Visible cells hold their current value, stale cells the value they had
+    /// when last seen and hidden cells hold [`HIDDEN_CELL`].
+    /// Without a fog of war the full world is returned.
+    pub fn get_masked_world(&self) -> Matrix{
+        let Some(fog) = &self.fog else {
+            return self.get_world();
+        };
+        let mut world = fog.memory.clone();
+        for x in 0..self.world_size.0{
+            for y in 0..self.world_size.1{
+                if fog.mask[(x,y)] == MASK_HIDDEN{
+                    world[(x,y)] = HIDDEN_CELL;
+                }
+            }
+        }
+        world
+    }
+
+    /// returns the visibility mask, see [`MASK_HIDDEN`], [`MASK_STALE`] and [`MASK_VISIBLE`]
+    ///
+    /// Without a fog of war every cell is visible.
+    pub fn get_visibility_mask(&self) -> Matrix{
+        match &self.fog {
+            Some(fog) => fog.mask.clone(),
+            None => {
+                let mut mask = Matrix::zeros(self.world_size);
+                for row in mask.matrix.iter_mut(){
+                    row.fill(MASK_VISIBLE);
+                }
+                mask
+            }
+        }
+    }
+
+    /// encodes the masked world as an observation, see [`GameEngine::observe`]
+    pub fn observe_masked(&self, observation_type : ObservationType) -> Vec<f32>{
+        observation::encode(
+            observation_type,
+            &self.get_masked_world(),
+            self.snake.snake_head,
+            self.snake.direction,
+            self.topology.as_ref(),
+            self.rules.walls
+        )
+    }
+
+}
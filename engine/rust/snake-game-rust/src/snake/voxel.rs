@@ -0,0 +1,570 @@
+//! A 3D variant of the game on a voxel grid
+//!
+//! The snake moves through an X×Y×Z lattice of cells. It has no absolute
+//! direction a player could pick, instead it carries a [`Frame`], the
+//! heading and the up vector of the head, and turns relative to it:
+//!
+//! * 0 - turn left, around the up vector
+//! * 1 - go straight
+//! * 2 - turn right
+//! * 3 - pitch up, the head moves along the up vector
+//! * 4 - pitch down
+//!
+//! The food, the collisions, the messages and the free space bookkeeping
+//! follow [`GameEngine`](crate::snake::GameEngine) with the classic rules,
+//! the tail moves out of the way of the head and the walls are solid.
+//! Cells hold the codes of [`Cell`], stored in a flat vector indexed by
+//! `(x*Y + y)*Z + z`.
+//!
+//! The engine implements [`SnakeEnv`], so agents, environment wrappers and
+//! the data collection work on both variants, and games are recorded and
+//! verified with [`VoxelReplay`].
+//!
+//! # Examples
+//!
+//! ```
+//! use snake_game_rust::snake::voxel::*;
+//!
+//! let mut game = VoxelEngine::new((8,8,8), (4,4,4), VoxelEngine::default_frame());
+//! game.reset(7);
+//!
+//! let (done, _, msg) = game.step(PITCH_UP);
+//! assert!(!done);
+//! assert_eq!(msg, "alive");
+//! assert_eq!(game.get_snake_head().z, 5);
+//! ```
+
+use std::fmt::{Display, Formatter};
+use crate::snake::data_types::*;
+use crate::snake::cell::Cell;
+use crate::snake::env::SnakeEnv;
+use crate::snake::errors::EngineError;
+use crate::snake::observation::ObservationType;
+use crate::snake::rng::{EngineRng, CURRENT_RNG_VERSION};
+use crate::snake::replay::{ReplayFormat, ReplayLine, ReplayStep};
+
+pub const TURN_LEFT : usize = 0;
+pub const STRAIGHT : usize = 1;
+pub const TURN_RIGHT : usize = 2;
+pub const PITCH_UP : usize = 3;
+pub const PITCH_DOWN : usize = 4;
+
+/// the number of actions of the voxel engine
+pub const NUM_ACTIONS : usize = 5;
+
+/// the unit vectors along the axes, the first four are the directions of the
+/// square grid in the `z` plane, 0 - left, 1 - up, 2 - right, 3 - down,
+/// followed by 4 - below and 5 - above in `z`
+pub const DIRECTIONS : [Vec3;6] = [
+    Vec3 { x : 0, y : -1, z : 0 },
+    Vec3 { x : -1, y : 0, z : 0 },
+    Vec3 { x : 0, y : 1, z : 0 },
+    Vec3 { x : 1, y : 0, z : 0 },
+    Vec3 { x : 0, y : 0, z : -1 },
+    Vec3 { x : 0, y : 0, z : 1 }
+];
+
+/// the orientation of the head, the heading and the up vector are
+/// perpendicular unit vectors along the axes
+#[derive(Clone,Copy,PartialEq,Eq,Hash,Debug)]
+pub struct Frame{
+    pub heading : Vec3,
+    pub up : Vec3
+}
+
+impl Frame {
+
+    /// the frame with the given heading and up directions, indices into
+    /// [`DIRECTIONS`], None if they are not perpendicular
+    pub fn from_directions(heading : usize, up : usize) -> Option<Frame>{
+        let frame = Frame { heading : *DIRECTIONS.get(heading)?, up : *DIRECTIONS.get(up)? };
+        match frame.heading.dot(frame.up) {
+            0 => Some(frame),
+            _ => None
+        }
+    }
+
+    /// the indices of the heading and the up vector in [`DIRECTIONS`]
+    pub fn directions(&self) -> (usize,usize){
+        let index = |v : Vec3| DIRECTIONS.iter().position(|d| *d == v).unwrap();
+        (index(self.heading), index(self.up))
+    }
+
+    /// a single number for the frame, `6*heading + up` with the indices of
+    /// [`Frame::directions`], it is the direction of the info string
+    pub fn index(&self) -> usize{
+        let (heading, up) = self.directions();
+        6*heading + up
+    }
+
+    /// the left of the head, in the right handed frame of heading, left and up
+    pub fn left(&self) -> Vec3{
+        self.up.cross(self.heading)
+    }
+
+    /// the frame after an action
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the action is not one of the [`NUM_ACTIONS`] actions
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snake_game_rust::snake::voxel::*;
+    ///
+    /// let frame = VoxelEngine::default_frame();
+    /// // a full circle in either plane ends where it started
+    /// assert_eq!((0..4).fold(frame, |frame, _| frame.turn(TURN_LEFT)), frame);
+    /// assert_eq!((0..4).fold(frame, |frame, _| frame.turn(PITCH_DOWN)), frame);
+    /// // pitching up and turning right keeps the up vector a right angle away
+    /// let turned = frame.turn(PITCH_UP).turn(TURN_RIGHT);
+    /// assert_eq!(turned.heading.dot(turned.up), 0);
+    /// ```
+    pub fn turn(&self, action : usize) -> Frame{
+        match action {
+            TURN_LEFT => Frame { heading : self.left(), up : self.up },
+            STRAIGHT => *self,
+            TURN_RIGHT => Frame { heading : -self.left(), up : self.up },
+            PITCH_UP => Frame { heading : self.up, up : -self.heading },
+            PITCH_DOWN => Frame { heading : -self.up, up : self.heading },
+            _ => panic!("Expected action to be smaller than {NUM_ACTIONS}, got {action} instead")
+        }
+    }
+}
+
+/// the snake game on an X×Y×Z voxel grid, see the module documentation
+pub struct VoxelEngine{
+    pub world_size : (usize,usize,usize),
+    pub cells : Vec<i32>,
+    pub snake_body : Vec<Vec3>,
+    pub frame : Frame,
+    pub starting_pos : (usize,usize,usize),
+    pub starting_frame : Frame,
+    pub food_pos : Vec3,
+    pub free_space : Vec<Vec3>,
+    pub rng_generator : EngineRng,
+    pub rng_version : u32,
+    pub seed : u64,
+    pub steps : usize
+}
+
+impl VoxelEngine {
+
+    /// a new engine with a snake of length 3, the head at `starting_pos` and
+    /// the body behind it, reset with a random seed
+    ///
+    /// # Panics
+    ///
+    /// Will panic if one of the world dimensions is 0, if the frame is not
+    /// one of [`Frame::from_directions`] or if the snake would be spawned
+    /// outside of the world
+    pub fn new(world_size : (usize,usize,usize), starting_pos : (usize,usize,usize), starting_frame : Frame) -> VoxelEngine{
+
+        if world_size.0 == 0 || world_size.1 == 0 || world_size.2 == 0{
+            panic!("World size cannot be zero!")
+        }
+        let (heading, up) = (starting_frame.heading, starting_frame.up);
+        if !DIRECTIONS.contains(&heading) || !DIRECTIONS.contains(&up) || heading.dot(up) != 0{
+            panic!("The heading and up vector of {starting_frame:?} have to be perpendicular unit vectors along the axes")
+        }
+
+        let mut engine = VoxelEngine {
+            world_size,
+            cells : vec![],
+            snake_body : vec![],
+            frame : starting_frame,
+            starting_pos,
+            starting_frame,
+            food_pos : Vec3 { x : 0, y : 0, z : 0 },
+            free_space : vec![],
+            rng_generator : EngineRng::from_entropy(CURRENT_RNG_VERSION),
+            rng_version : CURRENT_RNG_VERSION,
+            seed : 0,
+            steps : 0
+        };
+        for segment in engine.starting_body(){
+            if !engine.in_world(segment){
+                panic!("The snake at {starting_pos:?} does not fit into the world of size {world_size:?}");
+            }
+        }
+        let seed = engine.rng_generator.u64();
+        engine.reset(seed);
+        engine
+    }
+
+    /// heading up in the `z` plane like the default 2D snake, with `z` above
+    pub fn default_frame() -> Frame{
+        Frame { heading : DIRECTIONS[1], up : DIRECTIONS[5] }
+    }
+
+    fn starting_body(&self) -> Vec<Vec3>{
+        let head = Vec3 { x : self.starting_pos.0 as i32, y : self.starting_pos.1 as i32, z : self.starting_pos.2 as i32 };
+        (0..3).map(|i| head + (-self.starting_frame.heading).mul_by_i32(i)).collect()
+    }
+
+    fn in_world(&self, pos : Vec3) -> bool{
+        pos.x >= 0 && pos.y >= 0 && pos.z >= 0
+            && (pos.x as usize) < self.world_size.0 && (pos.y as usize) < self.world_size.1 && (pos.z as usize) < self.world_size.2
+    }
+
+    fn index(&self, pos : Vec3) -> usize{
+        (pos.x as usize * self.world_size.1 + pos.y as usize) * self.world_size.2 + pos.z as usize
+    }
+
+    fn set_cell(&mut self, pos : Vec3, cell : Cell){
+        let index = self.index(pos);
+        self.cells[index] = cell.code();
+    }
+
+    /// the cell at a position, None outside of the world
+    pub fn cell_at(&self, pos : Vec3) -> Option<Cell>{
+        match self.in_world(pos) {
+            true => Cell::from_code(self.cells[self.index(pos)]),
+            false => None
+        }
+    }
+
+    /// resets the world state given a seed
+    pub fn reset(&mut self, seed : u64){
+
+        self.seed = seed;
+        self.steps = 0;
+        self.rng_generator = EngineRng::new(self.rng_version, seed);
+        self.frame = self.starting_frame;
+        self.snake_body = self.starting_body();
+
+        let (rows, cols, layers) = self.world_size;
+        self.cells = vec![Cell::Empty.code(); rows*cols*layers];
+        self.free_space = Vec::with_capacity(rows*cols*layers);
+        for x in 0..rows{
+            for y in 0..cols{
+                for z in 0..layers{
+                    let pos = Vec3 { x : x as i32, y : y as i32, z : z as i32 };
+                    if !self.snake_body.contains(&pos){
+                        self.free_space.push(pos);
+                    }
+                }
+            }
+        }
+        for (i, segment) in self.snake_body.clone().into_iter().enumerate(){
+            self.set_cell(segment, if i == 0 { Cell::Head } else { Cell::Body });
+        }
+        self.spawn_food();
+    }
+
+    /// selects the version of the random generator, see [`crate::snake::rng`],
+    /// takes effect on the next reset
+    pub fn set_rng_version(&mut self, version : u32){
+        self.rng_generator = EngineRng::new(version, self.seed);
+        self.rng_version = version;
+    }
+
+    fn spawn_food(&mut self){
+        if self.free_space.is_empty(){
+            return;
+        }
+        let index = self.rng_generator.usize(0..self.free_space.len());
+        self.food_pos = self.free_space[index];
+        self.set_cell(self.food_pos, Cell::Food);
+    }
+
+    /// Forwards the game by one iteration, returns `(done, food_eaten, msg)`
+    /// with the messages of [`crate::snake::GameEngine::step`]. The state
+    /// is left unchanged by the step that kills the snake.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the action is not one of the [`NUM_ACTIONS`] actions
+    pub fn step(&mut self, action : usize) -> (bool,bool,String){
+
+        let frame = self.frame.turn(action);
+        let head = self.snake_body[0] + frame.heading;
+
+        if !self.in_world(head){
+            return (true, false, "wall".to_string());
+        }
+        let eats = head == self.food_pos;
+        //the tail moves away in the same step unless the snake grows
+        let end = if eats { self.snake_body.len() } else { self.snake_body.len() - 1 };
+        if self.snake_body[..end].contains(&head){
+            return (true, false, "body".to_string());
+        }
+
+        self.frame = frame;
+        self.steps += 1;
+        let neck = self.snake_body[0];
+        self.set_cell(neck, Cell::Body);
+        self.snake_body.insert(0, head);
+        self.free_space.retain(|cell| *cell != head);
+
+        if !eats{
+            let tail = self.snake_body.pop().unwrap();
+            if tail != head{
+                self.set_cell(tail, Cell::Empty);
+                self.free_space.push(tail);
+            }
+        }
+        self.set_cell(head, Cell::Head);
+
+        if eats{
+            //like the other engines, the winning step reports no food eaten
+            if self.free_space.is_empty(){
+                return (true, false, "victory".to_string());
+            }
+            self.spawn_food();
+        }
+        (false, eats, "alive".to_string())
+    }
+
+    pub fn get_snake_head(&self) -> &Vec3{
+        &self.snake_body[0]
+    }
+
+    pub fn get_food_pos(&self) -> &Vec3{
+        &self.food_pos
+    }
+
+    pub fn get_score(&self) -> usize{
+        self.snake_body.len().saturating_sub(3)
+    }
+
+    /// encodes the current world as an observation
+    ///
+    /// * `Grid` - every cell in the order of the flat cell vector
+    /// * `Features` - 24 values, 6 flags for the food being in the direction
+    ///   of each of the [`DIRECTIONS`], 6 flags for danger next to the head
+    ///   in each direction and one hot encodings of the heading and the up vector
+    pub fn observe(&self, observation_type : ObservationType) -> Vec<f32>{
+        match observation_type {
+            ObservationType::Grid => self.cells.iter().map(|cell| *cell as f32).collect(),
+            ObservationType::Features => {
+                let head = self.snake_body[0];
+                let offset = Vec3 { x : self.food_pos.x - head.x, y : self.food_pos.y - head.y, z : self.food_pos.z - head.z };
+                let (heading, up) = self.frame.directions();
+
+                let mut obs = Vec::with_capacity(24);
+                obs.extend(DIRECTIONS.iter().map(|d| (offset.dot(*d) > 0) as i32 as f32));
+                obs.extend(DIRECTIONS.iter().map(|d| self.cell_at(head + *d).is_none_or(|cell| cell.is_blocking()) as i32 as f32));
+                obs.extend((0..6).map(|d| (d == heading) as i32 as f32));
+                obs.extend((0..6).map(|d| (d == up) as i32 as f32));
+                obs
+            }
+        }
+    }
+
+    /// the direction, which is the index of the frame, seed, score and
+    /// rng version, appended to the flattened world and the action when
+    /// a step is stored
+    pub fn get_info_string(&self) -> String{
+        format!(",{},{},{},{}", self.frame.index(), self.seed, self.get_score(), self.rng_version)
+    }
+
+    pub fn get_flattened_world(&self) -> String{
+        self.cells.iter().map(|cell| cell.to_string() + ", ").collect()
+    }
+}
+
+impl SnakeEnv for VoxelEngine {
+
+    fn num_actions(&self) -> usize{
+        NUM_ACTIONS
+    }
+
+    fn forward_action(&self) -> usize{
+        STRAIGHT
+    }
+
+    fn reset(&mut self, seed : u64){
+        VoxelEngine::reset(self, seed)
+    }
+
+    fn step(&mut self, action : usize) -> (bool,bool,String){
+        VoxelEngine::step(self, action)
+    }
+
+    fn observe(&self, observation_type : ObservationType) -> Vec<f32>{
+        VoxelEngine::observe(self, observation_type)
+    }
+
+    fn observation_size(&self, observation_type : ObservationType) -> usize{
+        match observation_type {
+            ObservationType::Grid => self.cells.len(),
+            ObservationType::Features => 24
+        }
+    }
+
+    fn world_shape(&self) -> Vec<usize>{
+        vec![self.world_size.0, self.world_size.1, self.world_size.2]
+    }
+
+    fn get_score(&self) -> usize{
+        VoxelEngine::get_score(self)
+    }
+
+    fn get_seed(&self) -> u64{
+        self.seed
+    }
+
+    fn get_flattened_world(&self) -> String{
+        VoxelEngine::get_flattened_world(self)
+    }
+
+    fn get_info_string(&self) -> String{
+        VoxelEngine::get_info_string(self)
+    }
+}
+
+/// a recorded voxel game, in the text format of
+/// [`crate::snake::replay::Replay`] with a third coordinate, see
+/// [`ReplayFormat`]
+///
+/// ```text
+/// world_size 8 8 8
+/// starting_pos 4 4 4
+/// starting_frame 1 5
+/// seed 42
+/// rng_version 2
+/// food 3 7 1
+/// step 3 alive 0 4 4 5 3 7 1 3
+/// ```
+///
+/// `starting_frame` holds the heading and up directions of [`Frame::directions`].
+///
+/// # Examples
+///
+/// ```
+/// use snake_game_rust::snake::voxel::*;
+///
+/// let mut game = VoxelEngine::new((6,6,6), (3,3,3), VoxelEngine::default_frame());
+/// let replay = VoxelReplay::record(&mut game, 42, &[PITCH_UP, TURN_LEFT, STRAIGHT]);
+///
+/// let parsed = VoxelReplay::parse(&replay.to_string()).unwrap();
+/// assert_eq!(parsed, replay);
+/// assert!(parsed.verify().is_ok());
+/// ```
+#[derive(Clone,PartialEq,Eq,Debug)]
+pub struct VoxelReplay{
+    pub world_size : (usize,usize,usize),
+    pub starting_pos : (usize,usize,usize),
+    pub starting_frame : Frame,
+    pub seed : u64,
+    pub rng_version : u32,
+    pub food_pos : Vec3,
+    pub steps : Vec<ReplayStep<Vec3>>
+}
+
+impl VoxelReplay {
+
+    /// resets the engine with the seed and plays the actions until the game ends
+    pub fn record(engine : &mut VoxelEngine, seed : u64, actions : &[usize]) -> VoxelReplay{
+        VoxelReplay::record_game(engine, seed, actions)
+    }
+
+    /// the recorded actions
+    pub fn actions(&self) -> Vec<usize>{
+        self.recorded_actions()
+    }
+
+    /// returns an engine with the settings of the replay, reset to its seed
+    pub fn engine(&self) -> VoxelEngine{
+        let mut engine = VoxelEngine::new(self.world_size, self.starting_pos, self.starting_frame);
+        engine.set_rng_version(self.rng_version);
+        engine.reset(self.seed);
+        engine
+    }
+
+    /// plays the replay again and checks that every step leads to the recorded result
+    pub fn verify(&self) -> Result<(),EngineError>{
+        self.verify_game()
+    }
+
+    /// parses a replay from its text format
+    pub fn parse(text : &str) -> Result<VoxelReplay,EngineError>{
+        VoxelReplay::parse_text(text)
+    }
+}
+
+impl ReplayFormat for VoxelReplay {
+
+    type Engine = VoxelEngine;
+    type Pos = Vec3;
+
+    fn header(engine : &VoxelEngine, seed : u64) -> VoxelReplay{
+        VoxelReplay {
+            world_size : engine.world_size,
+            starting_pos : engine.starting_pos,
+            starting_frame : engine.starting_frame,
+            seed,
+            rng_version : engine.rng_version,
+            food_pos : engine.food_pos,
+            steps : vec![]
+        }
+    }
+
+    fn empty() -> VoxelReplay{
+        VoxelReplay {
+            world_size : (0,0,0),
+            starting_pos : (0,0,0),
+            starting_frame : VoxelEngine::default_frame(),
+            seed : 0,
+            rng_version : CURRENT_RNG_VERSION,
+            food_pos : Vec3 { x : 0, y : 0, z : 0 },
+            steps : vec![]
+        }
+    }
+
+    fn new_engine(&self) -> VoxelEngine{
+        self.engine()
+    }
+
+    fn snake_state(engine : &VoxelEngine) -> (Vec3,Vec3,usize){
+        (engine.snake_body[0], engine.food_pos, engine.snake_body.len())
+    }
+
+    fn seed(&self) -> u64{
+        self.seed
+    }
+
+    fn food_pos(&self) -> Vec3{
+        self.food_pos
+    }
+
+    fn steps(&self) -> &[ReplayStep<Vec3>]{
+        &self.steps
+    }
+
+    fn steps_mut(&mut self) -> &mut Vec<ReplayStep<Vec3>>{
+        &mut self.steps
+    }
+
+    fn parse_header(&mut self, line : &ReplayLine) -> Result<bool,EngineError>{
+        match line.fields[0] {
+            "world_size" => self.world_size = (line.parse(1)?, line.parse(2)?, line.parse(3)?),
+            "starting_pos" => self.starting_pos = (line.parse(1)?, line.parse(2)?, line.parse(3)?),
+            "starting_frame" => self.starting_frame = Frame::from_directions(line.parse(1)?, line.parse(2)?)
+                .ok_or_else(|| line.invalid())?,
+            "seed" => self.seed = line.parse(1)?,
+            "rng_version" => self.rng_version = line.parse(1)?,
+            "food" => self.food_pos = line.pos(1)?,
+            _ => return Ok(false)
+        }
+        Ok(true)
+    }
+
+    fn write_header(&self, f : &mut Formatter<'_>) -> std::fmt::Result{
+        let (heading, up) = self.starting_frame.directions();
+        writeln!(f, "world_size {} {} {}", self.world_size.0, self.world_size.1, self.world_size.2)?;
+        writeln!(f, "starting_pos {} {} {}", self.starting_pos.0, self.starting_pos.1, self.starting_pos.2)?;
+        writeln!(f, "starting_frame {heading} {up}")?;
+        writeln!(f, "seed {}", self.seed)?;
+        writeln!(f, "rng_version {}", self.rng_version)?;
+        writeln!(f, "food {} {} {}", self.food_pos.x, self.food_pos.y, self.food_pos.z)
+    }
+}
+
+impl Display for VoxelReplay{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.write_text(f)
+    }
+}
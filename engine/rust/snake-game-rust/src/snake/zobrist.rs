@@ -0,0 +1,202 @@
+use std::sync::Arc;
+use crate::snake::GameEngine;
+use crate::snake::data_types::*;
+use crate::snake::cell::Cell;
+use crate::snake::symmetry::Symmetry;
+use crate::snake::rng::splitmix64;
+
+/// seed of the key table, fixed so that hashes are comparable
+/// between engines, processes and runs
+const TABLE_SEED : u64 = 0x5EED_5A4E_C0DE_0001;
+
+const HEAD : usize = 0;
+const FOOD : usize = 1;
+const OBSTACLE : usize = 2;
+const LINK : usize = 3;
+
+/// random keys of a Zobrist hash for a given world size and number of directions
+///
+/// Every cell has a key for the head, the food, an obstacle and one key per
+/// direction for a body segment linked to the next segment towards the head
+/// in that direction. The links encode the order of the body, so two states
+/// with the same cells covered by differently ordered bodies hash differently.
+pub struct ZobristTable{
+    world_size : (usize,usize),
+    kinds : usize,
+    cell_keys : Vec<u64>,
+    direction_keys : Vec<u64>
+}
+
+impl ZobristTable {
+
+    pub fn new(world_size : (usize,usize), num_directions : usize) -> ZobristTable{
+        let mut state = TABLE_SEED;
+        let kinds = LINK + num_directions;
+        let cell_keys = (0..world_size.0*world_size.1*kinds).map(|_| splitmix64(&mut state)).collect();
+        let direction_keys = (0..num_directions).map(|_| splitmix64(&mut state)).collect();
+        ZobristTable { world_size, kinds, cell_keys, direction_keys }
+    }
+
+    /// the key of a cell, cells outside of the world have no key
+    fn key(&self, cell : Vec2, kind : usize) -> u64{
+        if cell.x < 0 || cell.y < 0 || cell.x as usize >= self.world_size.0 || cell.y as usize >= self.world_size.1 {
+            return 0;
+        }
+        let index = (cell.x as usize * self.world_size.1 + cell.y as usize) * self.kinds + kind;
+        self.cell_keys[index]
+    }
+}
+
+/// an incrementally updated Zobrist hash of the game state
+///
+/// The hash covers the head, the order of the body, the direction,
+/// the food and the obstacles. It is kept up to date by the engine,
+/// use [`GameEngine::get_hash`] to read it.
+#[derive(Clone)]
+pub struct ZobristHash{
+    pub table : Arc<ZobristTable>,
+    pub hash : u64,
+    food : Option<Vec2>
+}
+
+impl ZobristHash {
+
+    pub fn new(world_size : (usize,usize), num_directions : usize) -> ZobristHash{
+        ZobristHash { table : Arc::new(ZobristTable::new(world_size, num_directions)), hash : 0, food : None }
+    }
+
+    pub(crate) fn move_head(&mut self, old_head : Vec2, new_head : Vec2, old_direction : usize, new_direction : usize){
+        self.hash ^= self.table.key(old_head, HEAD);
+        self.hash ^= self.table.key(old_head, LINK + new_direction);
+        self.hash ^= self.table.key(new_head, HEAD);
+        self.hash ^= self.table.direction_keys[old_direction];
+        self.hash ^= self.table.direction_keys[new_direction];
+    }
+
+    pub(crate) fn remove_tail(&mut self, tail : Vec2, link : usize){
+        self.hash ^= self.table.key(tail, LINK + link);
+    }
+
+    pub(crate) fn set_food(&mut self, food : Option<Vec2>){
+        if let Some(old_food) = self.food{
+            self.hash ^= self.table.key(old_food, FOOD);
+        }
+        if let Some(new_food) = food{
+            self.hash ^= self.table.key(new_food, FOOD);
+        }
+        self.food = food;
+    }
+
+    pub(crate) fn toggle_obstacle(&mut self, obstacle : Vec2){
+        self.hash ^= self.table.key(obstacle, OBSTACLE);
+    }
+}
+
+impl GameEngine {
+
+    /// the direction from a body segment to the next one towards the head
+    pub(crate) fn link_direction(&self, from : Vec2, to : Vec2) -> usize{
+        (0..self.topology.num_directions())
+            .find(|d| self.next_cell(from, *d) == to)
+            .unwrap_or(0)
+    }
+
+    fn hash_with(&self, symmetry : Symmetry) -> u64{
+
+        let table = &self.zobrist.table;
+        let point = |p : Vec2| symmetry.apply_vec2(p, self.world_size);
+        let direction = |d : usize| symmetry.apply_direction(d, self.topology.as_ref());
+
+        let body = &self.snake.snake_body;
+        let mut hash = table.key(point(body[0]), HEAD);
+        for i in 1..body.len(){
+            let link = self.link_direction(body[i], body[i-1]);
+            hash ^= table.key(point(body[i]), LINK + direction(link));
+        }
+        if let Some(food) = self.zobrist.food{
+            hash ^= table.key(point(food), FOOD);
+        }
+        for obstacle in &self.obstacles{
+            hash ^= table.key(point(*obstacle), OBSTACLE);
+        }
+        hash ^ table.direction_keys[direction(self.snake.direction)]
+    }
+
+    /// computes the Zobrist hash of the current state from scratch
+    ///
+    /// Always equal to [`GameEngine::get_hash`], which is updated incrementally
+    pub fn compute_hash(&self) -> u64{
+        self.hash_with(Symmetry::Identity)
+    }
+
+    /// recomputes the hash after the state was rebuilt, used by `new` and `reset`
+    pub(crate) fn rehash(&mut self){
+        let food = match Cell::from_code(self.game_world[(self.food_pos.x, self.food_pos.y)]) {
+            Some(Cell::Food) => Some(self.food_pos),
+            _ => None
+        };
+        self.zobrist.food = food;
+        self.zobrist.hash = self.compute_hash();
+    }
+
+    /// returns the Zobrist hash of the current state
+    ///
+    /// The hash covers the body order, head, direction, food and obstacles and
+    /// is updated incrementally on every step, so it is cheap to query.
+    /// Hashes of engines with the same world size and topology are comparable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snake_game_rust::snake::*;
+    ///
+    /// let mut game = GameEngine::new((10,10), (5,5), 1);
+    /// game.reset(4);
+    /// let start = game.get_hash();
+    ///
+    /// game.step(0);
+    /// assert_ne!(game.get_hash(), start);
+    /// assert_eq!(game.get_hash(), game.compute_hash());
+    ///
+    /// game.reset(4);
+    /// assert_eq!(game.get_hash(), start);
+    /// ```
+    pub fn get_hash(&self) -> u64{
+        self.zobrist.hash
+    }
+
+    /// returns a key of the current state that is the same for all states
+    /// related by a symmetry of the world grid
+    ///
+    /// The key is the smallest hash over the symmetries of the topology, see
+    /// [`crate::snake::topology::Topology::symmetries`], all 8 dihedral
+    /// symmetries on square worlds and the 4 that keep the axes on
+    /// rectangular worlds. Hexagonal grids are only related to themselves,
+    /// so their key equals the hash.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snake_game_rust::snake::*;
+    ///
+    /// //snakes in the left and right column of a 3x2 world, facing up,
+    /// //the seed places the food in the same row of the other column
+    /// let mut left = GameEngine::new((3,2), (0,0), 1);
+    /// let mut right = GameEngine::new((3,2), (0,1), 1);
+    /// left.reset(1);
+    /// right.reset(1);
+    ///
+    /// assert_ne!(left.get_hash(), right.get_hash());
+    /// assert_eq!(left.canonical_key(), right.canonical_key());
+    ///
+    /// let hex = GameEngine::with_topology((6,6), (3,3), 1, topology::from_name("hex").unwrap());
+    /// assert_eq!(hex.canonical_key(), hex.get_hash());
+    /// ```
+    pub fn canonical_key(&self) -> u64{
+        self.topology.symmetries(self.world_size).into_iter()
+            .map(|symmetry| self.hash_with(symmetry))
+            .min()
+            .unwrap()
+    }
+
+}
@@ -0,0 +1,1035 @@
+//! Conformance suite pinning the dynamics of the engine
+//!
+//! Every scenario is stored as a golden replay in `tests/golden`. The replays are
+//! played again on every test run, so any change of the dynamics, like the food
+//! placement or the collision rules, fails here instead of silently changing the
+//! game. When a change is intended, regenerate the files with
+//! `UPDATE_GOLDEN=1 cargo test --test conformance` and review the diff.
+//!
+//! The replays in `tests/golden/legacy` were recorded before the random
+//! generators were versioned and are never regenerated, they check that old
+//! seeds still replay bit for bit with [`LEGACY_RNG_VERSION`].
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use snake_game_rust::snake::*;
+use snake_game_rust::snake::data_types::{Vec2, Matrix};
+use snake_game_rust::snake::replay::Replay;
+use snake_game_rust::snake::rng::{RNG_VERSIONS, LEGACY_RNG_VERSION};
+use snake_game_rust::snake::food_schedule::{FoodPlacement, scheduled_cell};
+use snake_game_rust::snake::topology::{Topology, SquareGrid, HexGrid, OctileGrid};
+use snake_game_rust::snake::dynamics::Dynamics;
+use snake_game_rust::snake::action_noise::ActionNoise;
+use snake_game_rust::snake::rules::{Rules, TailRule, Walls, LEGACY_RULES_VERSION, CURRENT_RULES_VERSION};
+use snake_game_rust::snake::limits::Limits;
+use snake_game_rust::snake::registry;
+use snake_game_rust::snake::errors::EngineError;
+use snake_game_rust::snake::invariants::Violation;
+use snake_game_rust::snake::scenario::{ScenarioGenerator, BodyMethod};
+use snake_game_rust::snake::voxel::{self, VoxelEngine, VoxelReplay};
+use snake_game_rust::snake::env::SnakeEnv;
+use snake_game_rust::snake::sparse::SparseEngine;
+use snake_game_rust::snake::data_types::Vec3;
+use snake_game_rust::snake::policy::{Policy, GreedyPolicy, RandomPolicy};
+use snake_game_rust::snake::cell::Cell;
+use snake_game_rust::snake::observation::ObservationType;
+use snake_game_rust::snake::render::Renderer;
+use snake_game_rust::snake::symmetry::Symmetry;
+use snake_game_rust::snake::visibility::{Visibility, Metric, HIDDEN_CELL, MASK_HIDDEN, MASK_STALE, MASK_VISIBLE};
+
+fn golden_path(name : &str) -> PathBuf{
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden").join(name)
+}
+
+fn update_golden() -> bool{
+    std::env::var("UPDATE_GOLDEN").is_ok()
+}
+
+/// compares the text with the golden file, or overwrites the file when updating
+fn check_golden_text(name : &str, text : &str){
+    let path = golden_path(name);
+    if update_golden(){
+        fs::write(&path, text).unwrap();
+    }
+    let golden = fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("Missing golden file {name}, run with UPDATE_GOLDEN=1 to create it"));
+    assert_eq!(golden, text, "{name} differs from the golden file");
+}
+
+/// checks that the golden replay still plays the same, and that the
+/// scenario still records the golden replay
+fn check_golden_replay(name : &str, replay : &Replay){
+    let header = "# golden replay, regenerate with UPDATE_GOLDEN=1 cargo test --test conformance\n".to_string();
+    check_golden_text(name, &(header + &replay.to_string()));
+
+    let golden = Replay::parse(&fs::read_to_string(golden_path(name)).unwrap()).unwrap();
+    if let Err(err) = golden.verify(){
+        panic!("{name}: {err}");
+    }
+}
+
+fn is_safe(game : &GameEngine, cell : Vec2) -> bool{
+    let body = &game.snake.snake_body;
+    cell.x >= 0 && cell.y >= 0
+        && (cell.x as usize) < game.world_size.0 && (cell.y as usize) < game.world_size.1
+        && !body[..body.len()-1].contains(&cell)
+}
+
+/// a deterministic policy moving towards the food while avoiding instant death
+fn greedy_action(game : &GameEngine) -> usize{
+    let topology = game.topology.clone();
+    let forward = topology.forward_action();
+    let mut actions : Vec<usize> = (0..topology.num_actions()).collect();
+    actions.sort_by_key(|action| *action != forward);
+
+    let distance = |cell : Vec2| (cell.x - game.food_pos.x).abs() + (cell.y - game.food_pos.y).abs();
+
+    actions.into_iter()
+        .map(|action| {
+            let direction = topology.turn(game.snake.direction, action);
+            let cell = topology.advance(game.snake.snake_head, direction);
+            (action, is_safe(game, cell), distance(cell))
+        })
+        .min_by_key(|(_, safe, distance)| (!safe, *distance))
+        .map(|(action, _, _)| action)
+        .unwrap()
+}
+
+fn play_greedy(game : &mut GameEngine, seed : u64, until : impl Fn(&GameEngine) -> bool, max_steps : usize) -> Vec<usize>{
+    game.reset(seed);
+    let mut actions = vec![];
+    for _ in 0..max_steps{
+        if until(game){
+            break;
+        }
+        let action = greedy_action(game);
+        actions.push(action);
+        if game.step(action).0{
+            break;
+        }
+    }
+    actions
+}
+
+#[test]
+fn seeded_food_positions(){
+    let mut game = GameEngine::new((10,10), (5,5), 1);
+    let actions = play_greedy(&mut game, 42, |_| false, 200);
+    let replay = Replay::record(&mut game, 42, &actions);
+
+    assert!(replay.steps.iter().filter(|step| step.food_eaten).count() >= 5);
+    check_golden_replay("seeded_food.replay", &replay);
+}
+
+#[test]
+fn reset_food_positions(){
+    for rng_version in RNG_VERSIONS{
+        let mut text = String::new();
+        for size in [(10,10), (20,20)]{
+            let mut game = GameEngine::new(size, (size.0/2, size.1/2), 1);
+            game.set_rng_version(rng_version);
+            for seed in 0..32{
+                game.reset(seed);
+                text += &format!("{} {} {seed} {} {}\n", size.0, size.1, game.food_pos.x, game.food_pos.y);
+            }
+        }
+        check_golden_text(&format!("reset_food_v{rng_version}.golden"), &text);
+    }
+}
+
+#[test]
+fn scheduled_food(){
+    let mut game = GameEngine::new((10,10), (5,5), 1);
+    game.set_food_placement(FoodPlacement::Scheduled);
+    let actions = play_greedy(&mut game, 42, |_| false, 200);
+    let replay = Replay::record(&mut game, 42, &actions);
+    check_golden_replay("scheduled_food.replay", &replay);
+
+    //a snake taking another path gets the same food as long as the cells are free
+    let mut other = GameEngine::new((10,10), (2,2), 2);
+    other.set_food_placement(FoodPlacement::Scheduled);
+    other.reset(42);
+    let mut foods = vec![other.food_pos];
+    for _ in 0..200{
+        let (done, food_eaten, _) = other.step(greedy_action(&other));
+        if food_eaten{
+            foods.push(other.food_pos);
+        }
+        if done{
+            break;
+        }
+    }
+
+    let recorded : Vec<Vec2> = std::iter::once(replay.food_pos)
+        .chain(replay.steps.iter().filter(|step| step.food_eaten).map(|step| step.food_pos))
+        .collect();
+    assert!(recorded.len() >= 5 && foods.len() >= 5);
+    assert_eq!(recorded[0], scheduled_cell(42, 0, 0, (10,10)));
+    assert_eq!(recorded[0], foods[0]);
+
+    //with short snakes on a 10x10 world the first candidate is almost always free
+    let pairs = recorded.len().min(foods.len());
+    let same = recorded.iter().zip(&foods).filter(|(a, b)| a == b).count();
+    assert!(same*4 >= pairs*3, "{same} of {pairs} food positions match");
+}
+
+#[test]
+fn delta_rebuilds_the_world(){
+    let topologies : [(Arc<dyn Topology>, usize); 3] = [
+        (Arc::new(SquareGrid), 1),
+        (Arc::new(HexGrid), 3),
+        (Arc::new(OctileGrid), 4)
+    ];
+    for (topology, direction) in topologies{
+        let mut game = GameEngine::with_topology((10,10), (5,5), direction, topology);
+        game.set_dynamics(Dynamics { food_move_interval : Some(2), obstacle_interval : Some(7) });
+        for seed in 0..5{
+            game.reset(seed);
+            let mut world = game.get_world();
+            for _ in 0..300{
+                let outcome = game.step_outcome(greedy_action(&game));
+                for change in &outcome.delta{
+                    assert_eq!(world[(change.pos.x, change.pos.y)], change.old);
+                    world[(change.pos.x, change.pos.y)] = change.new;
+                }
+                assert_eq!(world, game.get_world());
+                if outcome.done{
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn undo_restores_the_exact_state(){
+    let mut game = GameEngine::new((10,10), (5,5), 1);
+    game.set_dynamics(Dynamics { food_move_interval : Some(3), obstacle_interval : Some(5) });
+    game.set_action_noise(ActionNoise { sticky_prob : 0.1, epsilon : 0.1 });
+    game.set_history(8);
+    game.reset(13);
+
+    let mut states = vec![];
+    let mut actions = vec![];
+    for _ in 0..40{
+        states.push((game.get_world(), game.free_space.clone(), game.obstacles.clone(), game.get_hash()));
+        let action = greedy_action(&game);
+        actions.push(action);
+        if game.step(action).0{
+            break;
+        }
+    }
+    let end = (game.get_world(), game.free_space.clone(), game.get_hash());
+
+    //only the last 8 steps are kept
+    assert_eq!(game.undo_len(), 8);
+    for (world, free_space, obstacles, hash) in states.iter().rev().take(8){
+        assert!(game.undo());
+        assert_eq!(&game.get_world(), world);
+        assert_eq!(&game.free_space, free_space);
+        assert_eq!(&game.obstacles, obstacles);
+        assert_eq!(game.get_hash(), *hash);
+        assert_eq!(game.get_hash(), game.compute_hash());
+    }
+    assert!(!game.undo());
+
+    //the random generator is restored too, so playing on gives the same game
+    for action in &actions[actions.len() - 8..]{
+        game.step(*action);
+    }
+    assert_eq!((game.get_world(), game.free_space.clone(), game.get_hash()), end);
+
+    game.undo();
+    game.undo();
+    assert!(game.redo());
+    assert_eq!(game.redo_len(), 1);
+    game.step(1);
+    assert_eq!(game.redo_len(), 0);
+}
+
+#[test]
+fn incremental_hashes(){
+    let mut game = GameEngine::new((6,6), (3,3), 1);
+    game.set_dynamics(Dynamics { food_move_interval : Some(7), obstacle_interval : None });
+    game.set_action_noise(ActionNoise { sticky_prob : 0.0, epsilon : 0.2 });
+    game.set_history(4);
+    game.reset(21);
+
+    let (mut meals, mut tail_chases, mut undos) = (0, 0, 0);
+    for step in 0..2000{
+        let tail = *game.snake.snake_body.last().unwrap();
+        let length = game.snake.snake_body.len();
+        let (done, food_eaten, _) = game.step(greedy_action(&game));
+        if done{
+            game.reset(step as u64);
+            continue;
+        }
+        meals += food_eaten as usize;
+        tail_chases += (game.snake.snake_head == tail && game.snake.snake_body.len() == length) as usize;
+        assert_eq!(game.get_hash(), game.compute_hash(), "step {step}");
+
+        //undoing and redoing restores the hashes too
+        if step % 10 == 0 && game.undo_len() >= 2{
+            let hash = game.get_hash();
+            game.undo();
+            game.undo();
+            assert_eq!(game.get_hash(), game.compute_hash());
+            game.redo();
+            game.redo();
+            assert_eq!(game.get_hash(), hash);
+            undos += 1;
+        }
+
+        //an engine built from the same parts has the same hash
+        let copy = GameEngine::from_parts((6,6), game.snake.snake_body.clone(), game.snake.direction, Some(game.food_pos), 0).unwrap();
+        assert_eq!(copy.get_hash(), game.get_hash());
+
+        //and every symmetric image of the state has the same canonical key
+        for symmetry in Symmetry::ALL{
+            let image = symmetric_engine(&game, symmetry);
+            assert_eq!(image.get_hash(), image.compute_hash());
+            assert_eq!(image.canonical_key(), game.canonical_key(), "{symmetry:?}");
+        }
+    }
+    assert!(meals >= 20 && tail_chases >= 5 && undos >= 20, "{meals} meals, {tail_chases} tail chases, {undos} undos");
+}
+
+#[test]
+fn legacy_replays(){
+    let mut count = 0;
+    for entry in fs::read_dir(golden_path("legacy")).unwrap(){
+        let path = entry.unwrap().path();
+        let replay = Replay::parse(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(replay.rng_version, LEGACY_RNG_VERSION);
+        if let Err(err) = replay.verify(){
+            panic!("{}: {err}", path.display());
+        }
+        count += 1;
+    }
+    assert!(count > 0);
+}
+
+#[test]
+fn wall_death(){
+    let mut game = GameEngine::new((10,10), (5,5), 1);
+    let replay = Replay::record(&mut game, 7, &[1;10]);
+
+    let last = replay.steps.last().unwrap();
+    assert_eq!(replay.steps.len(), 6);
+    assert_eq!(last.msg, "wall");
+    check_golden_replay("wall_death.replay", &replay);
+}
+
+#[test]
+fn body_death(){
+    let mut game = GameEngine::new((10,10), (5,5), 1);
+    let mut actions = play_greedy(&mut game, 1, |game| game.snake.snake_body.len() >= 5, 200);
+    actions.extend([2, 2, 2]);
+    let replay = Replay::record(&mut game, 1, &actions);
+
+    assert_eq!(replay.steps.last().unwrap().msg, "body");
+    check_golden_replay("body_death.replay", &replay);
+}
+
+#[test]
+fn tail_chasing_is_legal(){
+    let mut game = GameEngine::new((10,10), (5,5), 1);
+    let mut actions = play_greedy(&mut game, 11, |game| game.snake.snake_body.len() >= 4, 200);
+    let loop_start = actions.len();
+    actions.extend([2;12]);
+    let replay = Replay::record(&mut game, 11, &actions);
+
+    //after closing the loop the head enters the cell the tail is leaving on every step
+    assert_eq!(replay.steps.len(), actions.len());
+    for step in &replay.steps[loop_start..]{
+        assert_eq!(step.msg, "alive");
+        assert_eq!(step.length, 4);
+    }
+    for i in loop_start + 4..replay.steps.len(){
+        assert_eq!(replay.steps[i].head, replay.steps[i-4].head);
+    }
+    check_golden_replay("tail_chasing.replay", &replay);
+}
+
+#[test]
+fn tail_blocks_under_strict_rules(){
+    let mut game = GameEngine::new((10,10), (5,5), 1);
+    let mut actions = play_greedy(&mut game, 11, |game| game.snake.snake_body.len() >= 4, 200);
+    let loop_start = actions.len();
+    actions.extend([2;12]);
+
+    game.set_rules(Rules { tail : TailRule::Block, ..Rules::classic() });
+    let replay = Replay::record(&mut game, 11, &actions);
+
+    //the same loop as in tail_chasing_is_legal ends when the head reaches the tail
+    let last = replay.steps.last().unwrap();
+    assert_eq!(last.msg, "body");
+    assert_eq!(replay.steps.len(), loop_start + 3);
+    check_golden_replay("tail_blocks.replay", &replay);
+}
+
+#[test]
+fn reversal_rules(){
+    for (rules, done) in [(Rules::classic(), false), (Rules::strict(), true)]{
+        let mut game = GameEngine::new((10,10), (5,5), 1);
+        game.set_rules(rules);
+        game.reset(0);
+        let outcome = game.step_direction(3);
+        assert_eq!(outcome.done, done);
+        if done{
+            assert_eq!(outcome.msg, "body");
+            assert_eq!(game.snake.snake_head, Vec2{ x : 6, y : 5 });
+        } else {
+            assert_eq!(game.snake.snake_head, Vec2{ x : 4, y : 5 });
+        }
+    }
+
+    //directions reached by a turn are played as that turn under both rules
+    for rules in [Rules::classic(), Rules::strict()]{
+        let mut game = GameEngine::new((10,10), (5,5), 1);
+        game.set_rules(rules);
+        let outcome = game.step_direction(0);
+        assert_eq!(outcome.executed_action, 0);
+        assert_eq!(game.get_direction(), 0);
+    }
+}
+
+#[test]
+fn victory_on_tiny_board(){
+    //a hamiltonian cycle of the 2x3 world, the snake starts on it facing left
+    let cycle = [(1,0), (0,0), (0,1), (0,2), (1,2), (1,1)];
+    let next = |head : Vec2| {
+        let i = cycle.iter().position(|cell| *cell == (head.x, head.y)).unwrap();
+        let (x, y) = cycle[(i + 1) % cycle.len()];
+        Vec2{ x, y }
+    };
+
+    let mut game = GameEngine::new((2,3), (1,0), 0);
+    game.reset(5);
+    let mut actions = vec![];
+    for _ in 0..100{
+        let target = next(game.snake.snake_head);
+        let action = (0..3)
+            .find(|action| {
+                let direction = game.topology.turn(game.snake.direction, *action);
+                game.topology.advance(game.snake.snake_head, direction) == target
+            })
+            .unwrap();
+        actions.push(action);
+        if game.step(action).0{
+            break;
+        }
+    }
+    let replay = Replay::record(&mut game, 5, &actions);
+
+    let last = replay.steps.last().unwrap();
+    assert_eq!(last.msg, "victory");
+    assert_eq!(last.length, 6);
+    check_golden_replay("victory_2x3.replay", &replay);
+}
+
+#[test]
+fn full_world_tail_chase(){
+    //the snake fills every free cell but the food and follows its tail
+    //around the 2x2 square, which is not a victory
+    let mut game = GameEngine::new((2,3), (0,0), 0);
+    game.set_obstacle_layout(vec![Vec2{ x : 1, y : 2 }]).unwrap();
+    let replay = Replay::record(&mut game, 0, &[0, 0, 0, 0, 0]);
+
+    let chase = &replay.steps[2];
+    assert_eq!((chase.msg.as_str(), chase.food_eaten, chase.length), ("alive", false, 4));
+    assert!(replay.steps[1..].iter().all(|step| step.msg == "alive" && step.food_pos == chase.food_pos));
+    assert_eq!(game.validate(), Ok(()));
+    assert_eq!(game.cell_at(chase.food_pos), Some(Cell::Food));
+    check_golden_replay("tail_chase_full_2x3.replay", &replay);
+
+    //the legacy rules count chasing the tail on a full world as a victory
+    game.set_rng_version(LEGACY_RNG_VERSION);
+    game.set_rules_version(LEGACY_RULES_VERSION);
+    let replay = Replay::record(&mut game, 1, &[0, 0, 0]);
+    let (chase, last) = (&replay.steps[1], &replay.steps[2]);
+    assert_eq!((chase.msg.as_str(), chase.length), ("alive", 4));
+    assert_eq!((last.msg.as_str(), last.length), ("victory", 5));
+    assert_ne!(last.head, chase.food_pos);
+    //the legacy goldens are never regenerated
+    let golden = fs::read_to_string(golden_path("legacy/tail_chase_full_2x3.replay")).unwrap();
+    assert_eq!(Replay::parse(&golden).unwrap(), replay);
+}
+
+#[test]
+fn other_topologies(){
+    let topologies : [(Arc<dyn Topology>, &str, usize); 2] = [
+        (Arc::new(HexGrid), "hex_greedy.replay", 3),
+        (Arc::new(OctileGrid), "octile_greedy.replay", 4)
+    ];
+    for (topology, name, direction) in topologies{
+        let mut game = GameEngine::with_topology((10,10), (5,5), direction, topology);
+        let actions = play_greedy(&mut game, 9, |_| false, 200);
+        let replay = Replay::record(&mut game, 9, &actions);
+        check_golden_replay(name, &replay);
+    }
+}
+
+/// the image of the game under the symmetry, built from its current state
+fn symmetric_engine(game : &GameEngine, symmetry : Symmetry) -> GameEngine{
+    let topology = game.topology.clone();
+    let body = game.snake.snake_body.iter().map(|cell| symmetry.apply_vec2(*cell, game.world_size)).collect();
+    let direction = symmetry.apply_direction(game.snake.direction, &*topology);
+    let food_pos = symmetry.apply_vec2(game.food_pos, game.world_size);
+    GameEngine::from_parts_with_topology(symmetry.apply_size(game.world_size), body, direction, Some(food_pos), 0, topology).unwrap()
+}
+
+/// the world with the food taken out, new food is drawn at random and does
+/// not follow the symmetry
+fn without_food(mut world : Matrix) -> Matrix{
+    for row in world.matrix.iter_mut(){
+        for cell in row.iter_mut().filter(|cell| **cell == Cell::Food.code()){
+            *cell = Cell::Empty.code();
+        }
+    }
+    world
+}
+
+#[test]
+fn symmetric_games(){
+    //the hexagonal grid only has the identity
+    let cases : [(Arc<dyn Topology>, (usize,usize), usize, usize); 3] = [
+        (Arc::new(SquareGrid), (8,8), 1, 8),
+        (Arc::new(SquareGrid), (6,9), 1, 4),
+        (Arc::new(HexGrid), (8,8), 3, 1)
+    ];
+    for (topology, world_size, direction, count) in cases{
+        let mut game = GameEngine::with_topology(world_size, (world_size.0/2, world_size.1/2), direction, topology.clone());
+        let actions = play_greedy(&mut game, 5, |_| false, 300);
+        assert!(game.get_score() >= 3);
+
+        let symmetries = topology.symmetries(world_size);
+        assert_eq!(symmetries.len(), count);
+        for symmetry in symmetries{
+            game.reset(5);
+            let mut image = symmetric_engine(&game, symmetry);
+            for action in &actions{
+                let outcome = game.step(*action);
+                assert_eq!(image.step(symmetry.apply_action(*action, &*topology)), outcome, "{symmetry:?}");
+                assert_eq!(image.snake.direction, symmetry.apply_direction(game.snake.direction, &*topology));
+                assert_eq!(without_food(image.get_world()), without_food(symmetry.apply_matrix(&game.get_world())), "{symmetry:?}");
+                //the food follows the original again after every meal
+                if outcome.1 && !outcome.0{
+                    image = symmetric_engine(&game, symmetry);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn reset_is_deterministic(){
+    let mut game = GameEngine::new((10,10), (5,5), 1);
+    let actions = play_greedy(&mut game, 21, |_| false, 100);
+
+    game.reset(21);
+    let world = game.get_world();
+    let food = game.food_pos;
+    let hash = game.get_hash();
+
+    for action in &actions{
+        game.step(*action);
+    }
+    game.reset(21);
+
+    assert_eq!(game.get_world(), world);
+    assert_eq!(game.food_pos, food);
+    assert_eq!(game.get_hash(), hash);
+    assert_eq!(game.steps, 0);
+    assert_eq!(game.free_space.len(), 100 - 3);
+    assert_eq!(Replay::record(&mut game, 21, &actions), Replay::record(&mut game, 21, &actions));
+}
+
+#[test]
+fn cell_queries_match_the_world(){
+    let mut game = GameEngine::new((8,8), (4,4), 1);
+    game.set_dynamics(Dynamics { food_move_interval : Some(3), obstacle_interval : Some(5) });
+    let actions = play_greedy(&mut game, 5, |_| false, 60);
+    game.reset(5);
+
+    for action in actions{
+        let (done, _, _) = game.step(action);
+        if done{
+            break;
+        }
+        let world = game.get_world();
+        for x in -1..9{
+            for y in -1..9{
+                let pos = Vec2{ x, y };
+                let cell = game.cell_at(pos);
+                if x < 0 || y < 0 || x == 8 || y == 8{
+                    assert_eq!(cell, None);
+                    assert!(game.is_blocked(pos));
+                    continue;
+                }
+                assert_eq!(cell.map(|cell| cell.code()), Some(world[(x,y)]));
+                assert_eq!(game.is_blocked(pos), game.snake.snake_body.contains(&pos) || game.obstacles.contains(&pos));
+                assert_eq!(game.body_index_at(pos).map(|i| game.snake.snake_body[i]), game.snake.snake_body.contains(&pos).then_some(pos));
+            }
+        }
+        assert_eq!(game.cell_at(*game.get_food_pos()), Some(Cell::Food));
+    }
+}
+
+#[test]
+fn rendered_replays_follow_the_game(){
+    let replay = Replay::parse(&fs::read_to_string(golden_path("body_death.replay")).unwrap()).unwrap();
+    let renderer = Renderer::new(3);
+    let frames = renderer.render_replay(&replay);
+    assert_eq!(frames.len(), replay.steps.len() + 1);
+
+    let mut game = replay.engine();
+    for (step, frame) in replay.steps.iter().zip(&frames[1..]){
+        game.step(step.action);
+        assert_eq!(*frame, game.render(&renderer));
+        if step.msg == "alive"{
+            assert_eq!(frame.pixel(3*step.head.y as usize, 3*step.head.x as usize), renderer.palette.head);
+        }
+    }
+
+    #[cfg(feature = "render")]
+    {
+        use snake_game_rust::snake::render::{write_gif, write_apng};
+
+        let mut png = vec![];
+        frames[0].write_png(&mut png).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+
+        let mut gif = vec![];
+        write_gif(&frames, &renderer.colors(), 100, &mut gif).unwrap();
+        let mut decoder = gif::DecodeOptions::new().read_info(gif.as_slice()).unwrap();
+        let mut decoded = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap(){
+            assert_eq!((frame.width as usize, frame.height as usize), (frames[0].width, frames[0].height));
+            decoded += 1;
+        }
+        assert_eq!(decoded, frames.len());
+
+        let mut apng = vec![];
+        write_apng(&frames, 100, &mut apng).unwrap();
+        assert!(apng.windows(4).any(|chunk| chunk == b"acTL"));
+        assert!(write_apng(&[], 100, &mut vec![]).is_err());
+    }
+}
+
+#[test]
+fn registered_presets(){
+    //the presets never change, each one plays a pinned greedy game
+    for spec in registry::specs(){
+        let mut game = spec.make();
+        game.reset(7);
+        let actions = game.play(&mut GreedyPolicy, 10_000);
+        let replay = Replay::record(&mut game, 7, &actions);
+
+        assert_eq!(replay.rules, spec.rules, "{}", spec.name);
+        assert_eq!(replay.limits, spec.limits, "{}", spec.name);
+        assert_eq!(replay.rules_version, spec.rules_version, "{}", spec.name);
+        check_golden_replay(&format!("presets/{}.replay", spec.name), &replay);
+    }
+
+    let maze = registry::make("maze-1-v0").unwrap();
+    assert_eq!(maze.get_obstacle_layout().len(), 40);
+    assert!(maze.get_obstacle_layout().iter().all(|cell| maze.cell_at(*cell) == Some(Cell::Obstacle)));
+    assert!(matches!(registry::make("classic-10x10-v2"), Err(EngineError::UnknownEnv(_))));
+    //the published presets keep the rules they were recorded with
+    for spec in registry::specs(){
+        let expected = if spec.name.ends_with("-v0") { LEGACY_RULES_VERSION } else { CURRENT_RULES_VERSION };
+        assert_eq!(spec.rules_version, expected, "{}", spec.name);
+    }
+}
+
+#[test]
+fn wrapping_walls_and_limits(){
+    //running up from the top row enters the bottom row
+    let mut game = GameEngine::new((5,5), (2,2), 1);
+    game.set_rules(Rules { walls : Walls::Wrap, ..Rules::classic() });
+    game.reset(1);
+    for _ in 0..3{
+        assert_eq!(game.step(1).2, "alive");
+    }
+    assert_eq!(*game.get_snake_head(), Vec2 { x : 4, y : 2 });
+
+    //circling on the spot never reaches the food
+    let mut game = GameEngine::new((10,10), (5,5), 1);
+    game.set_limits(Limits { max_steps : None, max_steps_without_food : Some(30) });
+    game.reset(3);
+    let msgs : Vec<String> = (0..30).map(|_| game.step(0).2).collect();
+    assert!(msgs[..29].iter().all(|msg| msg == "alive"));
+    assert_eq!(msgs[29], "starved");
+    assert_eq!(game.get_steps_since_food(), 30);
+}
+
+#[test]
+fn invariants_hold_during_play(){
+    let topologies : [Arc<dyn Topology>;3] = [Arc::new(SquareGrid), Arc::new(HexGrid), Arc::new(OctileGrid)];
+    for topology in topologies{
+        for rules in [Rules::classic(), Rules::strict(), Rules { walls : Walls::Wrap, ..Rules::classic() }]{
+            let mut game = GameEngine::with_topology((6,7), (3,3), 1, topology.clone());
+            game.set_rules(rules);
+            for seed in 0..20{
+                game.reset(seed);
+                assert_eq!(game.validate(), Ok(()));
+                let mut policy = RandomPolicy::new(seed);
+                for _ in 0..200{
+                    let action = policy.act(&game);
+                    let (done, _, msg) = game.step(action);
+                    if done{
+                        break;
+                    }
+                    if let Err(report) = game.validate(){
+                        panic!("{} {rules:?} seed {seed} after {msg}, {report}", topology.name());
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn legacy_rules_drift(){
+    //the legacy rules list the cell the head chased the tail into as free,
+    //the checker reports it instead of skipping the engine
+    let text = fs::read_to_string(golden_path("legacy/tail_chasing.replay")).unwrap();
+    let replay = Replay::parse(&text).unwrap();
+    assert_eq!(replay.rules_version, LEGACY_RULES_VERSION);
+
+    let mut game = replay.engine();
+    let mut reports = vec![];
+    for action in replay.actions(){
+        let (done, _, _) = game.step(action);
+        if done{
+            break;
+        }
+        reports.extend(game.validate().err());
+    }
+    assert!(!reports.is_empty());
+    for report in &reports{
+        assert!(report.violations.iter().all(|violation| matches!(violation, Violation::TakenFreeCell(_))), "{report}");
+    }
+    if cfg!(feature = "debug-invariants"){
+        assert_eq!(game.get_invariant_reports(), &reports[..]);
+    } else {
+        assert!(game.get_invariant_reports().is_empty());
+    }
+
+    //the current rules keep the cell taken
+    let mut game = replay.engine();
+    game.set_rules_version(CURRENT_RULES_VERSION);
+    for action in replay.actions(){
+        if game.step(action).0{
+            break;
+        }
+        assert_eq!(game.validate(), Ok(()));
+    }
+}
+
+/// the fewest steps to the food found by trying every action sequence
+fn brute_force_steps_to_food(game : &mut GameEngine, depth : usize) -> Option<usize>{
+    if depth == 0{
+        return None;
+    }
+    let mut best : Option<usize> = None;
+    for action in 0..game.topology.num_actions(){
+        let snapshot = game.snapshot();
+        let (done, food_eaten, _) = game.step(action);
+        let steps = match (done, food_eaten) {
+            (_, true) => Some(1),
+            (true, false) => None,
+            (false, false) => brute_force_steps_to_food(game, depth - 1).map(|steps| steps + 1)
+        };
+        game.restore(&snapshot);
+        best = match (best, steps) {
+            (Some(best), Some(steps)) => Some(best.min(steps)),
+            (best, steps) => best.or(steps)
+        };
+    }
+    best
+}
+
+#[test]
+fn generated_scenarios(){
+    let topologies : [Arc<dyn Topology>;3] = [Arc::new(SquareGrid), Arc::new(HexGrid), Arc::new(OctileGrid)];
+    for topology in topologies{
+        for method in [BodyMethod::RandomWalk, BodyMethod::Solver]{
+            let generator = ScenarioGenerator::new((8,8), 12).with_method(method).with_topology(topology.clone()).with_steps_to_food(true);
+            for seed in 0..10{
+                let scenario = generator.generate(seed).unwrap();
+                assert_eq!(generator.generate(seed).unwrap(), scenario);
+                assert_eq!(scenario.body.len(), 12);
+
+                let mut game = scenario.engine();
+                assert_eq!(game.validate(), Ok(()));
+                let Some(path) = game.path_to_food() else { continue };
+                assert_eq!(Some(path.len()), scenario.steps_to_food);
+                let last = path.len() - 1;
+                for (i, action) in path.into_iter().enumerate(){
+                    let (done, food_eaten, msg) = game.step(action);
+                    assert_eq!((food_eaten, msg.as_str()), (i == last, "alive"), "{} {} seed {seed}", topology.name(), method.name());
+                    assert!(!done);
+                }
+            }
+        }
+    }
+
+    //the search agrees with trying every action on small worlds
+    let generator = ScenarioGenerator::new((4,4), 8);
+    assert_eq!(generator.generate(0).unwrap().steps_to_food, None);
+    let mut unsolvable = 0;
+    for seed in 0..40{
+        let mut game = generator.generate(seed).unwrap().engine();
+        let steps = game.path_to_food().map(|path| path.len());
+        let brute_force = brute_force_steps_to_food(&mut game, 9);
+        match steps {
+            Some(steps) if steps <= 9 => assert_eq!(brute_force, Some(steps), "seed {seed}"),
+            _ => assert_eq!(brute_force, None, "seed {seed}")
+        }
+        unsolvable += usize::from(steps.is_none());
+    }
+    assert!(unsolvable > 0);
+    assert!(ScenarioGenerator::new((4,4), 8).with_solvable_only(true).generate(0).unwrap().steps_to_food.is_some());
+
+    assert!(matches!(ScenarioGenerator::new((4,4), 16).generate(0), Err(EngineError::NoScenario(_))));
+
+    //a 2000 cell snake winding through the top 40 rows of a large board,
+    //the search only stores the states it reaches
+    let mut body : Vec<Vec2> = (0..40)
+        .flat_map(|x| (0..50).map(move |y| Vec2{ x, y : if x % 2 == 0 { y } else { 49 - y } }))
+        .collect();
+    body.reverse();
+    let mut game = GameEngine::from_parts((50,50), body, 3, Some(Vec2{ x : 49, y : 0 }), 0).unwrap();
+    let path = game.path_to_food().unwrap();
+    assert_eq!(path.len(), 10);
+    for action in path{
+        game.step(action);
+    }
+    assert_eq!(game.snake.snake_body.len(), 2001);
+}
+
+/// a voxel policy moving towards the food while avoiding instant death
+fn greedy_voxel_action(game : &VoxelEngine) -> usize{
+    let head = *game.get_snake_head();
+    let food = *game.get_food_pos();
+    let tail = *game.snake_body.last().unwrap();
+    let distance = |cell : Vec3| (cell.x - food.x).abs() + (cell.y - food.y).abs() + (cell.z - food.z).abs();
+
+    (0..voxel::NUM_ACTIONS)
+        .map(|action| {
+            let cell = head + game.frame.turn(action).heading;
+            let safe = game.cell_at(cell).is_some_and(|cell| !cell.is_blocking()) || cell == tail;
+            (action, safe, distance(cell))
+        })
+        .min_by_key(|(action, safe, distance)| (!safe, *distance, *action != voxel::STRAIGHT))
+        .map(|(action, _, _)| action)
+        .unwrap()
+}
+
+#[test]
+fn voxel_games(){
+    let mut game = VoxelEngine::new((4,5,4), (1,2,2), VoxelEngine::default_frame());
+    let actions = game.rollout(5, &mut greedy_voxel_action, 2000);
+    let replay = VoxelReplay::record(&mut game, 5, &actions);
+    assert!(replay.steps.iter().filter(|step| step.food_eaten).count() >= 10);
+
+    let header = "# golden replay, regenerate with UPDATE_GOLDEN=1 cargo test --test conformance\n".to_string();
+    check_golden_text("voxel_greedy.replay", &(header + &replay.to_string()));
+    let golden = VoxelReplay::parse(&fs::read_to_string(golden_path("voxel_greedy.replay")).unwrap()).unwrap();
+    golden.verify().unwrap();
+
+    //the world, the body and the free space stay in agreement
+    game.reset(5);
+    for action in actions{
+        let (done, _, msg) = game.step(action);
+        let cells = |cell : Cell| game.cells.iter().filter(|code| **code == cell.code()).count();
+        let mut free = game.free_space.clone();
+        free.sort_by_key(|cell| (cell.x, cell.y, cell.z));
+        free.dedup();
+        assert_eq!(free.len(), game.free_space.len());
+        assert_eq!(free.len(), cells(Cell::Empty) + cells(Cell::Food), "{msg}");
+        assert_eq!(cells(Cell::Body) + cells(Cell::Head), game.snake_body.len());
+        assert_eq!(cells(Cell::Food), usize::from(msg != "victory"));
+        if done{
+            break;
+        }
+    }
+
+    //a snake filling the world wins, the food has a single free cell
+    let mut game = VoxelEngine::new((1,1,4), (0,0,2), voxel::Frame::from_directions(5, 2).unwrap());
+    assert_eq!(*game.get_food_pos(), Vec3 { x : 0, y : 0, z : 3 });
+    assert_eq!(game.step(voxel::STRAIGHT), (true, false, "victory".to_string()));
+
+    assert_eq!(game.collection_row(1).split(", ").count(), 5);
+    assert!(game.collection_row(1).ends_with(&game.get_info_string()));
+}
+
+#[test]
+fn sparse_engine(){
+    //the sparse engine plays like the dense one, apart from where the food spawns
+    let mut game = SparseEngine::new((12,12), (6,6), 1);
+    game.reset(11);
+    let mut eaten = 0;
+    for _ in 0..2000{
+        let body : Vec<Vec2> = game.snake_body.iter().copied().collect();
+        let mut dense = GameEngine::from_parts((12,12), body, game.get_direction(), game.food_pos, 0).unwrap();
+        assert_eq!(game.get_world(), dense.get_world());
+        assert_eq!(game.observe_viewport(3), dense.observe_viewport(3));
+        assert_eq!(game.observe(ObservationType::Features), dense.observe(ObservationType::Features));
+
+        let action = greedy_action(&dense);
+        let result = game.step(action);
+        assert_eq!(result, dense.step(action));
+        eaten += usize::from(result.1);
+        if result.0{
+            break;
+        }
+        assert_eq!(game.get_snake_head(), dense.get_snake_head());
+    }
+    assert!(eaten >= 10);
+
+    //a long game on a huge board
+    let mut game = SparseEngine::new((1000,1000), (500,500), 1);
+    game.set_limits(Limits { max_steps : Some(20000), max_steps_without_food : None });
+    let actions = game.rollout(3, &mut |game : &SparseEngine| {
+        let head = *game.get_snake_head();
+        let food = *game.get_food_pos().unwrap();
+        (0..3).min_by_key(|action| {
+            let cell = SquareGrid.advance(head, SquareGrid.turn(game.get_direction(), *action));
+            (game.cell_at(cell).is_none_or(|cell| cell.is_blocking()), (cell.x - food.x).abs() + (cell.y - food.y).abs())
+        }).unwrap()
+    }, 20000);
+    assert_eq!(actions.len(), 20000);
+    assert!(game.get_score() >= 10);
+    assert_eq!(game.occupied.len(), game.snake_body.len());
+
+    //the food falls back to the last free cell and the snake wins
+    let mut game = SparseEngine::new((1,4), (0,1), 0);
+    assert_eq!(game.get_food_pos(), Some(&Vec2 { x : 0, y : 0 }));
+    assert_eq!(game.step(1), (true, false, "victory".to_string()));
+    assert_eq!(game.get_food_pos(), None);
+}
+
+#[test]
+fn sparse_observations(){
+    //the dense and the sparse engine encode the same snake and food alike
+    let v = |x, y| Vec2 { x, y };
+    let states = [
+        //in the top left corner, facing left
+        (vec![v(0,0), v(0,1), v(0,2)], 0, v(5,5)),
+        //along the bottom wall, the food behind the head
+        (vec![v(7,3), v(7,4), v(7,5), v(6,5)], 0, v(7,6)),
+        //a U-shape, the body next to the head on two sides
+        (vec![v(3,3), v(3,4), v(4,4), v(4,3), v(4,2), v(3,2)], 1, v(0,3)),
+        //next to the right wall, the food straight above
+        (vec![v(4,9), v(5,9), v(6,9)], 1, v(1,9))
+    ];
+    for (body, direction, food) in states{
+        let dense = GameEngine::from_parts((8,10), body.clone(), direction, Some(food), 0).unwrap();
+        let mut sparse = SparseEngine::new((8,10), (4,5), 1);
+        sparse.snake_body = body.iter().copied().collect();
+        sparse.occupied = body.iter().copied().collect();
+        sparse.direction = direction;
+        sparse.food_pos = Some(food);
+
+        assert_eq!(sparse.get_world(), dense.get_world());
+        for observation_type in [ObservationType::Grid, ObservationType::Features]{
+            assert_eq!(sparse.observe(observation_type), dense.observe(observation_type), "{body:?} {observation_type:?}");
+        }
+        for radius in [0, 1, 3, 10]{
+            assert_eq!(sparse.observe_viewport(radius), dense.observe_viewport(radius), "{body:?} radius {radius}");
+        }
+        //every state puts danger next to the head
+        assert!(dense.observe(ObservationType::Features)[4..8].contains(&1.0));
+    }
+}
+
+/// the cells of the mask with the given value
+fn masked_cells(game : &GameEngine, value : i32) -> Vec<Vec2>{
+    let mask = game.get_visibility_mask();
+    (0..game.world_size.0*game.world_size.1)
+        .map(|i| Vec2{ x : (i / game.world_size.1) as i32, y : (i % game.world_size.1) as i32 })
+        .filter(|cell| mask[(cell.x, cell.y)] == value)
+        .collect()
+}
+
+#[test]
+fn fog_of_war(){
+    //a snake in the middle of the world facing up, its body below the head
+    let body = vec![Vec2{ x : 5, y : 5 }, Vec2{ x : 6, y : 5 }, Vec2{ x : 7, y : 5 }];
+    let far_food = Some(Vec2{ x : 0, y : 0 });
+    let fog = |radius, metric, line_of_sight| Some(Visibility { radius, metric, line_of_sight });
+
+    //the radius is inclusive, for both metrics
+    let mut game = GameEngine::from_parts((11,11), body.clone(), 1, far_food, 3).unwrap();
+    for (metric, count) in [(Metric::Manhattan, 25), (Metric::Euclidean, 29)]{
+        game.set_visibility(fog(3, metric, false));
+        let visible = masked_cells(&game, MASK_VISIBLE);
+        assert_eq!(visible.len(), count, "{metric:?}");
+        assert!(visible.contains(&Vec2{ x : 2, y : 5 }) && visible.contains(&Vec2{ x : 5, y : 8 }));
+        assert!(!visible.contains(&Vec2{ x : 1, y : 5 }) && !visible.contains(&Vec2{ x : 5, y : 9 }));
+        //(7,7) is 4 steps but less than 3 cells away in a straight line, (8,6) is more in both metrics
+        assert_eq!(visible.contains(&Vec2{ x : 7, y : 7 }), metric == Metric::Euclidean);
+        assert!(!visible.contains(&Vec2{ x : 8, y : 6 }));
+        assert_eq!(masked_cells(&game, MASK_HIDDEN).len(), 121 - count);
+        assert_eq!(game.get_masked_world()[(0,0)], HIDDEN_CELL);
+    }
+
+    //the body and obstacles hide the cells behind them, but are seen themselves
+    game.set_obstacle_layout(vec![Vec2{ x : 3, y : 5 }]).unwrap();
+    game.set_visibility(fog(4, Metric::Manhattan, true));
+    let visible = masked_cells(&game, MASK_VISIBLE);
+    for cell in [Vec2{ x : 4, y : 5 }, Vec2{ x : 3, y : 5 }, Vec2{ x : 6, y : 5 }, Vec2{ x : 5, y : 9 }]{
+        assert!(visible.contains(&cell), "{cell:?}");
+    }
+    for cell in [Vec2{ x : 2, y : 5 }, Vec2{ x : 1, y : 5 }, Vec2{ x : 7, y : 5 }, Vec2{ x : 8, y : 5 }]{
+        assert!(!visible.contains(&cell), "{cell:?}");
+    }
+    game.set_visibility(fog(4, Metric::Manhattan, false));
+    assert!(masked_cells(&game, MASK_VISIBLE).contains(&Vec2{ x : 2, y : 5 }));
+
+    //cells out of sight keep the value they had when last seen
+    let mut game = GameEngine::from_parts((11,11), body.clone(), 1, far_food, 3).unwrap();
+    game.set_visibility(fog(1, Metric::Manhattan, false));
+    assert_eq!(game.get_masked_world()[(6,5)], Cell::Body.code());
+    game.step(1);
+    game.step(1);
+    game.step(1);
+    assert_eq!(game.get_visibility_mask()[(6,5)], MASK_STALE);
+    assert_eq!(game.get_masked_world()[(6,5)], Cell::Body.code());
+    assert_eq!(game.get_world()[(6,5)], Cell::Empty.code());
+    //visible cells follow the world
+    let world = game.get_world();
+    let masked = game.get_masked_world();
+    for cell in masked_cells(&game, MASK_VISIBLE){
+        assert_eq!(masked[(cell.x, cell.y)], world[(cell.x, cell.y)]);
+    }
+    //a reset forgets everything
+    game.reset(3);
+    assert!(masked_cells(&game, MASK_STALE).is_empty());
+    assert_eq!(masked_cells(&game, MASK_VISIBLE).len(), 5);
+
+    //the masked observations only differ from the full ones while the food is hidden
+    let mut game = GameEngine::from_parts((11,11), body, 1, far_food, 3).unwrap();
+    for observation_type in [ObservationType::Grid, ObservationType::Features]{
+        assert_eq!(game.observe_masked(observation_type), game.observe(observation_type));
+    }
+    game.set_visibility(fog(2, Metric::Manhattan, true));
+    let grid : Vec<f32> = game.get_masked_world().matrix.iter().flatten().map(|cell| *cell as f32).collect();
+    assert_eq!(game.observe_masked(ObservationType::Grid), grid);
+    let (masked, full) = (game.observe_masked(ObservationType::Features), game.observe(ObservationType::Features));
+    assert_eq!(&masked[..4], &[0.0;4]);
+    assert_eq!(&full[..4], &[1.0, 0.0, 1.0, 0.0]);
+    assert_eq!(&masked[4..], &full[4..]);
+    for _ in 0..4{
+        game.step(1);
+    }
+    for action in [0, 1, 1, 1]{
+        game.step(action);
+    }
+    assert_eq!(game.snake.snake_head, Vec2{ x : 1, y : 1 });
+    assert_eq!(game.observe_masked(ObservationType::Features), game.observe(ObservationType::Features));
+}
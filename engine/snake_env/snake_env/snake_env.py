@@ -1,54 +1,36 @@
 import numpy as np
-import gym
+import gymnasium as gym
 from typing import Tuple,Optional
 import python_wrapper
-from gym import spaces
 
 
 class SnakeEnv(gym.Env):
+    """a gymnasium.Env subclass around the native python_wrapper.SnakeGymEnv,
+    for code that checks isinstance(env, gym.Env) or registers the env
 
-    def __init__(self,size : Tuple[int,int], starting_pos : Tuple[int,int]) -> None:
-        
-        for value in list(size) + list(starting_pos):
-            if value <= 0:
-                print("arguments equal or less than 0 make no sense in this context")
-                raise ValueError
-
-        self.ge = python_wrapper.EngineWrapper(size,starting_pos)
-        self.action_space = spaces.Discrete(3)
-
-        obs_high = np.zeros(size).flatten() + 2
-        obs_low = np.zeros(size).flatten()
-        self.observation_space = spaces.Box(low=obs_low,high=obs_high,dtype=np.int32)
+    The step, the rewards and the observations are computed in rust, the
+    keyword arguments are the ones of SnakeGymEnv
+    """
 
-    def reward(self) -> float:
-        return 0
-    
-    def info(self) -> dict:
-        return {}
-
-    def step(self, action):
-        
-        done,msg = self.ge.step()
+    metadata = {"render_modes": ["rgb_array"]}
 
-        observation = np.array(self.ge.py_get_world()).flatten()
+    def __init__(self, size : Tuple[int,int], starting_pos : Optional[Tuple[int,int]] = None, starting_direction : int = 1, **kwargs) -> None:
 
-        reward = self.reward()
-
-        info = self.info()
-
-        return observation,reward,done,info
-    
+        for value in list(size) + list(starting_pos or ()):
+            if value <= 0:
+                raise ValueError("arguments equal or less than 0 make no sense in this context")
 
-    def reset(self,seed: Optional[int] = None) -> Tuple[np.ndarray, dict]:
-        
-        if seed != None:
-            print("set seeding not yet implemented")
-        
-        self.ge.py_reset()
+        self.env = python_wrapper.SnakeGymEnv(size, starting_pos, starting_direction, **kwargs)
+        self.action_space = self.env.action_space
+        self.observation_space = self.env.observation_space
+        self.render_mode = self.env.render_mode
 
-        observation = np.array(self.ge.py_get_world()).flatten()
+    def step(self, action):
+        return self.env.step(int(action))
 
-        info = self.info()
+    def render(self) -> Optional[np.ndarray]:
+        return self.env.render()
 
-        return observation,info
\ No newline at end of file
+    def reset(self, seed : Optional[int] = None, options : Optional[dict] = None) -> Tuple[np.ndarray, dict]:
+        super().reset(seed=seed)
+        return self.env.reset(seed=seed, options=options)
@@ -18,7 +18,7 @@ class DQN:
 
     def __init__(self, env, params):
 
-        self.action_space = env.action_space
+        self.action_space = env.action_space.n
         self.state_space = env.state_space
         self.epsilon = params['epsilon'] 
         self.gamma = params['gamma'] 
@@ -86,7 +86,7 @@ def train_dqn(episode, env):
     sum_of_rewards = []
     agent = DQN(env, params)
     for e in range(episode):
-        state = env.reset()
+        state, _ = env.reset()
         state = np.reshape(state, (1, env.state_space))
         score = 0
         max_steps = 10000
@@ -94,7 +94,8 @@ def train_dqn(episode, env):
             action = agent.act(state)
             # print(action)
             prev_state = state
-            next_state, reward, done, _ = env.step(action)
+            next_state, reward, terminated, truncated, _ = env.step(action)
+            done = terminated or truncated
             score += reward
             next_state = np.reshape(next_state, (1, env.state_space))
             agent.remember(state, action, reward, next_state, done)
@@ -140,7 +141,7 @@ if __name__ == '__main__':
     def init2():
         global state
         ax.grid(visible=False)
-        state = env.reset()
+        state, _ = env.reset()
         state = np.reshape(state, (1, env.state_space))
         im = ax.imshow(env.view_game())
         im.set_data(env.view_game())
@@ -153,7 +154,8 @@ if __name__ == '__main__':
         # From environment state
         state = np.reshape(state, (1, env.state_space))
         action = agent.act(state)
-        new_state, _, done, _ = env.step(action)
+        new_state, _, terminated, truncated, _ = env.step(action)
+        done = terminated or truncated
         state = new_state
 
 
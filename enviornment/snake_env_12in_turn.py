@@ -1,5 +1,5 @@
 import numpy as np
-import gym as gym
+import gymnasium as gym
 from gymnasium import spaces
 
 from typing import Tuple,Optional
@@ -11,8 +11,9 @@ class SnakeEnvTurn(gym.Env):
     def __init__(self,size : Tuple[int,int], starting_pos : Tuple[int,int], starting_direction : int):
         
         self.engine = python_wrapper.EngineWrapper(size,starting_pos,starting_direction)
-        self.action_space = 3
+        self.action_space = spaces.Discrete(3)
 
+        self.starting_direction = starting_direction
         self.direction = starting_direction
         
         self.food_pos = self.get_food_pos()
@@ -20,6 +21,7 @@ class SnakeEnvTurn(gym.Env):
         self.last_head_pos = self.head_pos
 
         self.state_space = 12
+        self.observation_space = spaces.Box(low=0, high=1, shape=(self.state_space,), dtype=np.int64)
 
     def get_food_pos(self):
 
@@ -53,28 +55,28 @@ class SnakeEnvTurn(gym.Env):
         i,j = self.head_pos[0], self.head_pos[1]
         
         if j + 1 < y and (i >=0 and i < x): 
-            obstacle_right = world[i,j+1] == 2 and (self.direction != 0)
+            obstacle_right = world[i,j+1] == python_wrapper.CELL_BODY and (self.direction != 0)
         elif j + 1 < y:
             obstacle_right = False
         else:
             obstacle_right = True
  
         if i + 1 < x and (j >=0 and j < y):
-            obstacle_up = world[i+1,j] == 2 and (self.direction != 1)
+            obstacle_up = world[i+1,j] == python_wrapper.CELL_BODY and (self.direction != 1)
         elif i + 1 < x:
             obstacle_up = False
         else:
             obstacle_up = True
  
         if j - 1 >= 0 and (i >=0 and i < x):
-            obstacle_left = world[i,j-1] == 2 and (self.direction != 2)
+            obstacle_left = world[i,j-1] == python_wrapper.CELL_BODY and (self.direction != 2)
         elif j - 1 >= 0:
             obstacle_left = False
         else:
             obstacle_left = True
  
         if i - 1 >= 0 and (j >=0 and j < y):
-            obstacle_down = world[i-1,j] == 2 and (self.direction != 3)
+            obstacle_down = world[i-1,j] == python_wrapper.CELL_BODY and (self.direction != 3)
         elif i - 1 >= 0:
             obstacle_down = False
         else:
@@ -151,21 +153,27 @@ class SnakeEnvTurn(gym.Env):
         observation = self.get_obs()
         info = self.get_info(reward, food_eaten)
 
-        return observation,reward,terminated, info
+        return observation,reward,terminated,False,info
     
     def view_game(self):
         return np.array(self.engine.py_get_world())
 
+    def render(self, mode="rgb_array", cell_size=16):
+        if mode != "rgb_array":
+            raise NotImplementedError(f"render mode {mode} is not supported")
+        pixels, shape = self.engine.py_render(cell_size)
+        return np.frombuffer(pixels, dtype=np.uint8).reshape(shape)
+
 
     def reset(self, seed=None, options=None,manual_seeding = False):
         self.steps_without_food = 0
         if not manual_seeding:
             super().reset(seed=seed)
-            seed = np.random.randint(0, 100000)
+            seed = int(self.np_random.integers(0, 100000))
         print(seed)
         self.engine.py_reset(seed)
-        
-        self.update_information()        
+        self.direction = self.starting_direction
+
+        self.update_information()
         observation = self.get_obs()
-        self.direction = 1
-        return observation
\ No newline at end of file
+        return observation, self.get_info(0, False)
\ No newline at end of file
@@ -0,0 +1,42 @@
+use snake_game_rust::snake::data_types::Matrix;
+use snake_game_rust::snake::render::Palette;
+use termion::color::{Bg, Rgb, Reset};
+
+/// the line break of the raw terminal mode, which does not return the cursor
+/// to the start of the line on its own
+pub const NEWLINE : &str = "\r\n";
+
+/// draws the world as a framed board, two columns per cell so the cells
+/// are roughly square, row `x` of the matrix is the `x`-th line of the board
+///
+/// The cells are colored with the palette of the headless renderer,
+/// the lines are separated with [`NEWLINE`].
+pub fn draw_board(world : &Matrix, palette : &Palette) -> String{
+
+    let width = 2*world.size.1;
+    let mut board = format!("┌{}┐{NEWLINE}", "─".repeat(width));
+
+    for row in &world.matrix{
+        board += "│";
+        for cell in row{
+            let [r, g, b] = palette.color(*cell);
+            board += &format!("{}  ", Bg(Rgb(r, g, b)));
+        }
+        board += &format!("{}│{NEWLINE}", Bg(Reset));
+    }
+    board += &format!("└{}┘", "─".repeat(width));
+    board
+}
+
+/// centers lines of text over a board of the given width in columns
+pub fn draw_text(lines : &[&str], width : usize) -> String{
+    lines.iter()
+        .map(|line| format!("{:^width$}", line, width = width.max(line.chars().count())))
+        .collect::<Vec<_>>()
+        .join(NEWLINE)
+}
+
+/// draws the status line below the board
+pub fn draw_status(score : usize, steps : usize, status : &str) -> String{
+    format!("score {score}  steps {steps}  {status}")
+}
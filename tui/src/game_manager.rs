@@ -0,0 +1,190 @@
+use snake_game_rust::snake::GameEngine;
+use snake_game_rust::snake::render::Palette;
+use termion::event::Key;
+use crate::board::{self, NEWLINE};
+use crate::spectator::Spectator;
+
+#[derive(Clone,Copy,PartialEq,Eq,Debug)]
+pub enum UIState {
+    StartScreen,
+    InGame,
+    GameOver
+}
+
+/// what a key asks the game to do
+#[derive(Clone,Copy,PartialEq,Eq,Debug)]
+pub enum Command{
+    /// a relative action of the engine, 0 turns left and 2 turns right
+    Turn(usize),
+    /// an absolute direction, 0 - left, 1 - up, 2 - right, 3 - down
+    Direction(usize),
+    Start,
+    Restart,
+    Quit
+}
+
+/// maps the keys to commands, the arrows move in their direction on the board,
+/// `a` and `d` turn left and right like in the speedy2d UI
+pub fn command(key : Key) -> Option<Command>{
+    match key {
+        Key::Left => Some(Command::Direction(0)),
+        Key::Up => Some(Command::Direction(1)),
+        Key::Right => Some(Command::Direction(2)),
+        Key::Down => Some(Command::Direction(3)),
+        Key::Char('a') | Key::Char('A') => Some(Command::Turn(0)),
+        Key::Char('d') | Key::Char('D') => Some(Command::Turn(2)),
+        Key::Char(' ') => Some(Command::Start),
+        Key::Char('r') | Key::Char('R') => Some(Command::Restart),
+        Key::Char('q') | Key::Char('Q') | Key::Esc | Key::Ctrl('c') => Some(Command::Quit),
+        _ => None
+    }
+}
+
+/// who moves the snake
+pub enum Controller{
+    Player,
+    Spectator(Spectator)
+}
+
+/// the start, game over and restart flow of the speedy2d `GameManager`,
+/// drawing to a string of terminal output instead of a window
+pub struct GameManager{
+    pub state : UIState,
+    ge : GameEngine,
+    controller : Controller,
+    seed : Option<u64>,
+    pending : Option<Command>,
+    msg : String,
+    palette : Palette
+}
+
+impl GameManager{
+
+    /// `seed` fixes the seed of every game, otherwise each game gets a new one
+    pub fn new(ge : GameEngine, controller : Controller, seed : Option<u64>) -> GameManager{
+        GameManager {
+            state : UIState::StartScreen,
+            ge,
+            controller,
+            seed,
+            pending : None,
+            msg : String::new(),
+            palette : Palette::default()
+        }
+    }
+
+    pub fn engine(&self) -> &GameEngine{
+        &self.ge
+    }
+
+    pub fn score(&self) -> usize{
+        self.ge.get_score()
+    }
+
+    /// handles a command, returns false when the game should quit
+    pub fn handle(&mut self, command : Command) -> bool{
+        match command {
+            Command::Quit => return false,
+            Command::Start => self.start_game(),
+            Command::Restart => self.reset_game(),
+            Command::Turn(_) | Command::Direction(_) => {
+                if self.state == UIState::InGame && matches!(self.controller, Controller::Player){
+                    self.pending = Some(command);
+                }
+            }
+        }
+        true
+    }
+
+    /// advances the game by one step, called once per tick
+    pub fn tick(&mut self){
+        if self.state != UIState::InGame{
+            return;
+        }
+
+        let outcome = match &mut self.controller {
+            Controller::Player => match self.pending.take() {
+                //absolute directions only exist on the square grid
+                Some(Command::Direction(direction)) if self.ge.topology.num_directions() == 4 => self.ge.step_direction(direction),
+                Some(Command::Turn(action)) if action < self.ge.topology.num_actions() => self.ge.step_outcome(action),
+                _ => self.ge.step_outcome(self.ge.topology.forward_action())
+            },
+            Controller::Spectator(spectator) => match spectator.next_action(&self.ge) {
+                Some(action) => self.ge.step_outcome(action),
+                None => {
+                    self.msg = "end of replay".to_string();
+                    self.state = UIState::GameOver;
+                    return;
+                }
+            }
+        };
+
+        self.msg = outcome.msg;
+        if outcome.done{
+            self.state = UIState::GameOver;
+        }
+    }
+
+    pub fn start_game(&mut self){
+        if self.state == UIState::StartScreen{
+            self.new_game();
+        }
+    }
+
+    pub fn reset_game(&mut self){
+        if self.state == UIState::GameOver{
+            self.new_game();
+        }
+    }
+
+    fn new_game(&mut self){
+        let seed = match &mut self.controller {
+            Controller::Spectator(spectator) => {
+                spectator.restart();
+                spectator.seed()
+            },
+            Controller::Player => None
+        };
+        self.ge.reset(seed.or(self.seed).unwrap_or_else(entropy_seed));
+        self.pending = None;
+        self.msg = "alive".to_string();
+        self.state = UIState::InGame;
+    }
+
+    /// draws the current screen
+    pub fn draw(&self) -> String{
+        let width = 2*self.ge.world_size.1 + 2;
+        let watching = matches!(self.controller, Controller::Spectator(_));
+        match self.state {
+            UIState::StartScreen => {
+                let start = match watching {
+                    true => "Press Space to start watching",
+                    false => "Press Space to start"
+                };
+                let controls = match watching {
+                    true => "R restarts, Q quits",
+                    false => "arrows move, A and D turn, Q quits"
+                };
+                board::draw_text(&["Snake", "", start, controls], width)
+            },
+            UIState::InGame => {
+                let world = self.ge.get_world();
+                board::draw_board(&world, &self.palette) + NEWLINE + &board::draw_status(self.score(), self.ge.steps, &self.msg)
+            },
+            UIState::GameOver => {
+                let score = format!("Score : {}", self.score());
+                let reason = format!("({})", self.msg);
+                board::draw_text(&["Game Over", &score, &reason, "Press R to restart, Q to quit"], width)
+            }
+        }
+    }
+
+}
+
+/// a seed from the clock, for games without a fixed seed
+fn entropy_seed() -> u64{
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|time| time.as_nanos() as u64)
+        .unwrap_or(0)
+}
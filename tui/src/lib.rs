@@ -0,0 +1,18 @@
+//! A terminal front end of the snake game, for playing and watching games
+//! over SSH without a display
+//!
+//! The board is drawn with ANSI colors by [`board`], the start, game over and
+//! restart flow of the speedy2d UI lives in [`game_manager`] and the
+//! [`spectator`] module plays back replays or lets a
+//! [`snake_game_rust::snake::policy::Policy`] play.
+//!
+//! ```text
+//! snake-tui                                 play on a 20x20 board
+//! snake-tui --size 15x30 --tick-ms 80       a wider board and a faster game
+//! snake-tui --replay tests/golden/seeded_food.replay
+//! snake-tui --policy greedy --seed 7        watch the greedy policy
+//! ```
+
+pub mod board;
+pub mod game_manager;
+pub mod spectator;
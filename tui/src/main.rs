@@ -0,0 +1,116 @@
+use std::io::{self, Write};
+use std::process::ExitCode;
+use std::thread;
+use std::time::{Duration, Instant};
+use termion::{clear, cursor};
+use termion::input::TermRead;
+use termion::raw::IntoRawMode;
+use termion::screen::IntoAlternateScreen;
+use snake_game_rust::snake::GameEngine;
+use snake_game_rust::snake::replay::Replay;
+use snake_game_rust::snake::policy::{GreedyPolicy, RandomPolicy};
+use snake_tui::game_manager::{self, GameManager, Controller};
+use snake_tui::spectator::Spectator;
+
+const USAGE : &str = "usage: snake-tui [--size <rows>x<columns>] [--tick-ms <ms>] [--seed <seed>]
+                 [--replay <file> | --policy greedy|random]";
+const WORLD_SIZE : (usize, usize) = (20, 20);
+const STARTING_DIRECTION : usize = 1;
+//the frame time of the speedy2d UI
+const TICK_MS : u64 = 111;
+
+struct Options{
+    world_size : (usize,usize),
+    tick : Duration,
+    seed : Option<u64>,
+    replay : Option<String>,
+    policy : Option<String>
+}
+
+fn parse_options(args : &[String]) -> Result<Options,String>{
+
+    let mut options = Options { world_size : WORLD_SIZE, tick : Duration::from_millis(TICK_MS), seed : None, replay : None, policy : None };
+    let mut args = args.iter();
+
+    while let Some(flag) = args.next(){
+        let value = args.next().ok_or(format!("{flag} needs a value"))?;
+        match flag.as_str() {
+            "--size" => {
+                let size = value.split_once('x')
+                    .and_then(|(rows, columns)| Some((rows.parse().ok()?, columns.parse().ok()?)))
+                    .filter(|(rows, columns)| *rows >= 4 && *columns >= 4);
+                options.world_size = size.ok_or(format!("invalid size {value}, expected at least 4x4"))?;
+            },
+            "--tick-ms" => options.tick = Duration::from_millis(value.parse().map_err(|_| format!("invalid tick {value}"))?),
+            "--seed" => options.seed = Some(value.parse().map_err(|_| format!("invalid seed {value}"))?),
+            "--replay" => options.replay = Some(value.clone()),
+            "--policy" => options.policy = Some(value.clone()),
+            _ => return Err(format!("unknown option {flag}"))
+        }
+    }
+    Ok(options)
+}
+
+fn game_manager(options : &Options) -> Result<GameManager,String>{
+
+    let starting_pos = (options.world_size.0/2, options.world_size.1/2);
+    let new_engine = || GameEngine::new(options.world_size, starting_pos, STARTING_DIRECTION);
+
+    if let Some(path) = &options.replay{
+        let text = std::fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+        let replay = Replay::parse(&text).map_err(|err| format!("{path}: {err}"))?;
+        return Ok(GameManager::new(replay.engine(), Controller::Spectator(Spectator::replay(replay)), None));
+    }
+    let controller = match options.policy.as_deref() {
+        None => Controller::Player,
+        Some("greedy") => Controller::Spectator(Spectator::policy(GreedyPolicy)),
+        Some("random") => Controller::Spectator(Spectator::policy(RandomPolicy::new(options.seed.unwrap_or(0)))),
+        Some(name) => return Err(format!("unknown policy {name}"))
+    };
+    Ok(GameManager::new(new_engine(), controller, options.seed))
+}
+
+fn run(mut manager : GameManager, tick : Duration) -> io::Result<()>{
+
+    let mut screen = cursor::HideCursor::from(io::stdout().into_raw_mode()?.into_alternate_screen()?);
+    let mut keys = termion::async_stdin().keys();
+    let mut next_tick = Instant::now();
+
+    loop {
+        for key in keys.by_ref(){
+            if let Some(command) = game_manager::command(key?){
+                if !manager.handle(command){
+                    return Ok(());
+                }
+            }
+        }
+
+        if Instant::now() >= next_tick{
+            manager.tick();
+            next_tick = Instant::now() + tick;
+            write!(screen, "{}{}{}", clear::All, cursor::Goto(1, 1), manager.draw())?;
+            screen.flush()?;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+fn main() -> ExitCode{
+
+    let args : Vec<String> = std::env::args().skip(1).collect();
+    let (manager, tick) = match parse_options(&args).and_then(|options| Ok((game_manager(&options)?, options.tick))) {
+        Ok(setup) => setup,
+        Err(err) => {
+            eprintln!("snake-tui: {err}\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match run(manager, tick) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("snake-tui: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
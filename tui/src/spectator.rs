@@ -0,0 +1,49 @@
+use snake_game_rust::snake::GameEngine;
+use snake_game_rust::snake::replay::Replay;
+use snake_game_rust::snake::policy::Policy;
+
+/// the source of the actions of a watched game
+pub enum Spectator{
+    /// plays back the actions of a recorded game
+    Replay{ replay : Replay, next : usize },
+    /// lets a policy play
+    Policy(Box<dyn Policy>)
+}
+
+impl Spectator {
+
+    pub fn replay(replay : Replay) -> Spectator{
+        Spectator::Replay { replay, next : 0 }
+    }
+
+    pub fn policy(policy : impl Policy + 'static) -> Spectator{
+        Spectator::Policy(Box::new(policy))
+    }
+
+    /// the seed the game has to be reset with, a replay always shows its own game
+    pub fn seed(&self) -> Option<u64>{
+        match self {
+            Spectator::Replay { replay, .. } => Some(replay.seed),
+            Spectator::Policy(_) => None
+        }
+    }
+
+    /// starts watching from the first step again
+    pub fn restart(&mut self){
+        if let Spectator::Replay { next, .. } = self{
+            *next = 0;
+        }
+    }
+
+    /// the next action, `None` when a replay has no more steps
+    pub fn next_action(&mut self, engine : &GameEngine) -> Option<usize>{
+        match self {
+            Spectator::Replay { replay, next } => {
+                let action = replay.steps.get(*next)?.action;
+                *next += 1;
+                Some(action)
+            },
+            Spectator::Policy(policy) => Some(policy.act(engine))
+        }
+    }
+}
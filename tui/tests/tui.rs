@@ -0,0 +1,106 @@
+//! Tests of the terminal front end without a terminal
+
+use std::fs;
+use termion::event::Key;
+use snake_game_rust::snake::*;
+use snake_game_rust::snake::replay::Replay;
+use snake_game_rust::snake::render::Palette;
+use snake_game_rust::snake::policy::GreedyPolicy;
+use snake_tui::board::{draw_board, NEWLINE};
+use snake_tui::game_manager::{command, Command, Controller, GameManager, UIState};
+use snake_tui::spectator::Spectator;
+
+fn golden_replay(name : &str) -> Replay{
+    let path = format!("{}/../engine/rust/snake-game-rust/tests/golden/{name}", env!("CARGO_MANIFEST_DIR"));
+    Replay::parse(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[test]
+fn draws_every_cell_with_its_color(){
+    let game = GameEngine::new((6,9), (3,3), 1);
+    let board = draw_board(&game.get_world(), &Palette::default());
+    let lines : Vec<&str> = board.split(NEWLINE).collect();
+
+    //a frame around 6 rows
+    assert_eq!(lines.len(), 8);
+    assert_eq!(lines[0].chars().count(), 2*9 + 2);
+    //the head is the only blue cell, on the row 3
+    let head = format!("{}  ", termion::color::Bg(termion::color::Rgb(0, 0, 255)));
+    assert_eq!(board.matches(&head).count(), 1);
+    assert!(lines[1 + 3].contains(&head));
+}
+
+#[test]
+fn follows_the_start_game_over_and_restart_flow(){
+    let mut manager = GameManager::new(GameEngine::new((6,6), (3,3), 1), Controller::Player, Some(5));
+    assert_eq!(manager.state, UIState::StartScreen);
+
+    //restarting and turning do nothing before the game starts
+    assert!(manager.handle(Command::Restart));
+    assert!(manager.handle(Command::Turn(0)));
+    manager.tick();
+    assert_eq!(manager.state, UIState::StartScreen);
+
+    assert!(manager.handle(Command::Start));
+    assert_eq!(manager.state, UIState::InGame);
+    assert_eq!(manager.engine().get_seed(), 5);
+
+    //moving back is ignored, then the snake runs up into the wall
+    manager.handle(Command::Direction(3));
+    manager.tick();
+    assert_eq!(manager.engine().get_direction(), 1);
+    for _ in 0..3{
+        manager.tick();
+    }
+    assert_eq!(manager.state, UIState::GameOver);
+    assert!(manager.draw().contains("(wall)"));
+
+    assert!(manager.handle(Command::Restart));
+    assert_eq!(manager.state, UIState::InGame);
+    assert_eq!(manager.engine().steps, 0);
+    assert!(!manager.handle(Command::Quit));
+}
+
+#[test]
+fn maps_keys_to_commands(){
+    assert_eq!(command(Key::Up), Some(Command::Direction(1)));
+    assert_eq!(command(Key::Char('a')), Some(Command::Turn(0)));
+    assert_eq!(command(Key::Char('D')), Some(Command::Turn(2)));
+    assert_eq!(command(Key::Ctrl('c')), Some(Command::Quit));
+    assert_eq!(command(Key::Char('x')), None);
+}
+
+#[test]
+fn spectators_play_back_replays(){
+    let replay = golden_replay("seeded_food.replay");
+    let mut manager = GameManager::new(replay.engine(), Controller::Spectator(Spectator::replay(replay.clone())), None);
+
+    for _ in 0..2{
+        manager.handle(Command::Start);
+        manager.handle(Command::Restart);
+        for step in &replay.steps{
+            //the player keys are ignored while watching
+            manager.handle(Command::Turn(0));
+            manager.tick();
+            assert_eq!(*manager.engine().get_snake_head(), step.head);
+            assert_eq!(*manager.engine().get_food_pos(), step.food_pos);
+        }
+        manager.tick();
+        assert_eq!(manager.state, UIState::GameOver);
+    }
+}
+
+#[test]
+fn spectators_watch_policies(){
+    let mut manager = GameManager::new(GameEngine::new((10,10), (5,5), 1), Controller::Spectator(Spectator::policy(GreedyPolicy)), Some(3));
+    let mut game = GameEngine::new((10,10), (5,5), 1);
+    game.reset(3);
+    let actions = game.play(&mut GreedyPolicy, 1000);
+
+    manager.handle(Command::Start);
+    for _ in 0..actions.len(){
+        manager.tick();
+    }
+    assert_eq!(manager.state, UIState::GameOver);
+    assert_eq!(manager.score(), game.get_score());
+}
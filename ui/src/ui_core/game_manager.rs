@@ -1,4 +1,5 @@
 use snake_game_rust::snake::GameEngine;
+use snake_game_rust::snake::env::SnakeEnv;
 use speedy2d::Graphics2D;
 use dataEngine::data_engine_mod::DataEngine;
 use super::{graphics_manager::GraphicManager};
@@ -60,10 +61,7 @@ impl GameManager{
             if food_eaten{
                 self.score += 1;
             }
-            let mut world_str = self.ge.get_flattened_world();
-            world_str += &format!("{user_action}");
-            world_str += &self.ge.get_info_string();
-            self.de.create_statement(world_str);
+            self.de.create_statement(self.ge.collection_row(user_action));
         } 
         if is_game_done && self.state != UIState::GameOver{
             self.state = UIState::GameOver;
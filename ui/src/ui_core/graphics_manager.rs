@@ -1,4 +1,5 @@
 use snake_game_rust::snake::data_types::Matrix;
+use snake_game_rust::snake::cell::Cell;
 use speedy2d::dimen::Vec2;
 use speedy2d::error::{BacktraceError, ErrorMessage};
 use speedy2d::shape::Rect;
@@ -10,13 +11,17 @@ const SCREEN_CLEAR_COLOR : Color = Color::BLACK;
 const _ASSETS_FOLDER_PATH : &str = "../../assets/";
 
 
-const COLORS : [Color;4] = [
-    Color::GRAY,
-    Color::BLUE,
-    Color::CYAN,
-    Color::RED
+const BACKGROUND_COLOR : Color = Color::GRAY;
 
-];
+fn cell_color(cell : Cell) -> Option<Color>{
+    match cell {
+        Cell::Empty => None,
+        Cell::Head => Some(Color::BLUE),
+        Cell::Body => Some(Color::CYAN),
+        Cell::Food => Some(Color::RED),
+        Cell::Obstacle => Some(Color::DARK_GRAY)
+    }
+}
 // TODO : switch to Path module
 pub struct GraphicManager{
     windows_size : (u32,u32),
@@ -78,7 +83,7 @@ impl GraphicManager{
         let rect = Rect::new(
             Vec2::new((windows_size_x-self.game_size.0)/(2.0), (windows_size_y+self.game_size.1)/2.0),
             Vec2::new((windows_size_x+self.game_size.0)/2.0, (windows_size_y-self.game_size.1)/(2.0)));
-        graphics.draw_rectangle(rect, COLORS[0]);
+        graphics.draw_rectangle(rect, BACKGROUND_COLOR);
 
         for x in 0..board.size.0{
             let x_float = x as f32;
@@ -86,20 +91,14 @@ impl GraphicManager{
             for y in 0..board.size.1{
             let y_float = y as f32;
 
-            let cell = board[(x,y)];
+            let color = Cell::from_code(board[(x,y)]).and_then(cell_color);
             
-            match cell {
-                1 | 2 | 3 => {
-                    let verticies = self.construct_simple_square(
-                        Vec2::new(x_float*square_size.0+(windows_size_x-self.game_size.0)/(2.0), y_float*square_size.1 + (windows_size_y-self.game_size.1)/(2.0)), 
-                        square_size.0
-                    );
-                    let cell_as_index = cell as usize;
-                    let color = COLORS[cell_as_index];
-                    graphics.draw_quad(verticies, color);
-                }
-
-                _ => ()
+            if let Some(color) = color {
+                let verticies = self.construct_simple_square(
+                    Vec2::new(x_float*square_size.0+(windows_size_x-self.game_size.0)/(2.0), y_float*square_size.1 + (windows_size_y-self.game_size.1)/(2.0)), 
+                    square_size.0
+                );
+                graphics.draw_quad(verticies, color);
             }
             
             }